@@ -0,0 +1,68 @@
+//! Benchmark of the reorientation step on a synthetic mesh, run with `cargo bench`.
+//!
+//! The mesh is a UV sphere whose triangles alternate between both windings, so that half of them get flipped.
+//! Every approach is timed over several runs on a fresh copy of the mesh and the fastest run is reported,
+//! which is the least disturbed by other processes.
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use mesh_reorienter::{compute_centroid, make_winding_consistent, reorient_mesh, Mesh};
+use nalgebra::Vector3;
+
+/// Number of timed runs of every approach.
+const RUNS: usize = 10;
+
+/// A UV sphere with `rings` latitude bands and `segments` longitude slices, wound inconsistently.
+fn sphere(rings: usize, segments: usize) -> Mesh {
+    let mut vertices = vec![Vector3::new(0.0, 0.0, 1.0), Vector3::new(0.0, 0.0, -1.0)];
+    for ring in 1..rings {
+        let polar = std::f64::consts::PI * ring as f64 / rings as f64;
+        for segment in 0..segments {
+            let azimuth = 2.0 * std::f64::consts::PI * segment as f64 / segments as f64;
+            vertices.push(Vector3::new(polar.sin() * azimuth.cos(), polar.sin() * azimuth.sin(), polar.cos()));
+        }
+    }
+    let point = |ring: usize, segment: usize| 2 + (ring - 1) * segments + segment % segments;
+
+    let mut triangles: Vec<[usize; 3]> = Vec::new();
+    for segment in 0..segments {
+        triangles.push([0, point(1, segment), point(1, segment + 1)]);
+        triangles.push([1, point(rings - 1, segment + 1), point(rings - 1, segment)]);
+    }
+    for ring in 1..rings - 1 {
+        for segment in 0..segments {
+            triangles.push([point(ring, segment), point(ring + 1, segment), point(ring + 1, segment + 1)]);
+            triangles.push([point(ring, segment), point(ring + 1, segment + 1), point(ring, segment + 1)]);
+        }
+    }
+    for (t, triangle) in triangles.iter_mut().enumerate() {
+        if t % 2 == 1 {
+            triangle.swap(1, 2);
+        }
+    }
+    Mesh { vertices, triangles, colors: None, groups: None }
+}
+
+/// Time `run` on a fresh copy of `mesh` and return the fastest of [`RUNS`] runs.
+fn bench(mesh: &Mesh, run: impl Fn(&mut Mesh)) -> Duration {
+    (0..RUNS)
+        .map(|_| {
+            let mut copy = mesh.clone();
+            let start = Instant::now();
+            run(black_box(&mut copy));
+            start.elapsed()
+        })
+        .min()
+        .expect("at least one run")
+}
+
+fn main() {
+    let mesh = sphere(500, 1000);
+    println!("{} vertices, {} triangles", mesh.vertices.len(), mesh.triangles.len());
+    println!("compute_centroid: {:?}", bench(&mesh, |mesh| {
+        black_box(compute_centroid(mesh));
+    }));
+    println!("reorient_mesh: {:?}", bench(&mesh, reorient_mesh));
+    println!("make_winding_consistent: {:?}", bench(&mesh, make_winding_consistent));
+}
@@ -0,0 +1,125 @@
+//! Compact binary counterpart of the native text format, selected by the `.bin` extension.
+//!
+//! The layout mirrors the text format, with every value little-endian:
+//! the 8-byte magic `MESHBIN1`, the u64 point count, three f64 coordinates per point,
+//! the u64 triangle count and three u64 point indices per triangle.
+//! Coordinates are stored exactly, so no precision applies.
+
+use nalgebra::Vector3;
+
+use crate::{validate_faces, Mesh, ParseError};
+
+/// Magic bytes at the start of every binary mesh file, ending with the format version.
+const BIN_MAGIC: &[u8; 8] = b"MESHBIN1";
+
+#[test]
+fn test_bin_round_trip() {
+    let out_path = std::env::temp_dir().join("mesh_reorienter_torus.bin");
+    let out_path = out_path.to_str().unwrap();
+
+    let mesh = crate::parse_obj("tests/torus.obj").unwrap();
+    write_bin(out_path, &mesh);
+    assert_eq!(parse_bin(out_path).unwrap(), mesh);
+}
+
+#[test]
+fn test_parse_bin_truncated() {
+    let mesh = crate::parse_input("tests/input.txt").unwrap();
+    let mut bytes = bin_contents(&mesh);
+    bytes.truncate(bytes.len() - 4);
+    assert!(matches!(bin_from_bytes(&bytes), Err(ParseError::Binary { offset: 208, .. })));
+    assert!(matches!(bin_from_bytes(b"MESHBIN2"), Err(ParseError::Binary { offset: 0, .. })));
+}
+
+/// Parse a binary mesh file written by [`write_bin`].
+pub fn parse_bin(in_path: &str) -> Result<Mesh, ParseError> {
+    let bytes = crate::read_input(in_path)?;
+    bin_from_bytes(&bytes)
+}
+
+/// Reads little-endian values from a byte buffer, reporting where the buffer ends too early.
+struct ByteCursor<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+impl ByteCursor<'_> {
+    /// Read the next 8 bytes, which hold a value described by `what`.
+    fn next_8(&mut self, what: &str) -> Result<[u8; 8], ParseError> {
+        let value = self.bytes
+            .get(self.offset..self.offset + 8)
+            .ok_or_else(|| ParseError::Binary { offset: self.offset, reason: format!("file ends before the {}", what) })?;
+        self.offset += 8;
+        Ok(value.try_into().expect("the slice holds 8 bytes"))
+    }
+
+    fn next_u64(&mut self, what: &str) -> Result<u64, ParseError> {
+        self.next_8(what).map(u64::from_le_bytes)
+    }
+
+    fn next_f64(&mut self, what: &str) -> Result<f64, ParseError> {
+        self.next_8(what).map(f64::from_le_bytes)
+    }
+
+    /// Read a count or an index, which must fit in a `usize`.
+    fn next_usize(&mut self, what: &str) -> Result<usize, ParseError> {
+        let offset = self.offset;
+        let value = self.next_u64(what)?;
+        usize::try_from(value).map_err(|_| ParseError::Binary { offset, reason: format!("{} {} is too large", what, value) })
+    }
+}
+
+/// Parse the contents of a binary mesh file.
+fn bin_from_bytes(bytes: &[u8]) -> Result<Mesh, ParseError> {
+    if !bytes.starts_with(BIN_MAGIC) {
+        return Err(ParseError::Binary { offset: 0, reason: String::from("missing 'MESHBIN1' magic bytes") });
+    }
+    let mut cursor = ByteCursor { bytes, offset: BIN_MAGIC.len() };
+
+    let n_points = cursor.next_usize("number of points")?;
+    let mut vertices: Vec<Vector3<f64>> = Vec::with_capacity(n_points.min(bytes.len() / 24));
+    for _ in 0..n_points {
+        let x = cursor.next_f64("x coordinate")?;
+        let y = cursor.next_f64("y coordinate")?;
+        let z = cursor.next_f64("z coordinate")?;
+        vertices.push(Vector3::new(x, y, z));
+    }
+
+    let n_triangles = cursor.next_usize("number of triangles")?;
+    let mut triangles: Vec<[usize; 3]> = Vec::with_capacity(n_triangles.min(bytes.len() / 24));
+    for _ in 0..n_triangles {
+        let mut triangle = [0; 3];
+        for index in &mut triangle {
+            *index = cursor.next_usize("point index")?;
+        }
+        triangles.push(triangle);
+    }
+
+    validate_faces(vertices.len(), &triangles)?;
+    Ok(Mesh { vertices, triangles, colors: None, groups: None })
+}
+
+/// Write the mesh as a binary mesh file. A path ending in `.gz` is written gzip-compressed.
+pub fn write_bin(out_path: &str, mesh: &Mesh) {
+    crate::write_file(out_path, |writer| writer.write_all(&bin_contents(mesh)))
+        .expect("Something went wrong writing the file");
+}
+
+/// Encode the mesh in the binary mesh format.
+fn bin_contents(mesh: &Mesh) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(BIN_MAGIC.len() + 16 + 24 * (mesh.vertices.len() + mesh.triangles.len()));
+    bytes.extend_from_slice(BIN_MAGIC);
+    bytes.extend_from_slice(&(mesh.vertices.len() as u64).to_le_bytes());
+    for point in &mesh.vertices {
+        for coord in point.iter() {
+            bytes.extend_from_slice(&coord.to_le_bytes());
+        }
+    }
+    bytes.extend_from_slice(&(mesh.triangles.len() as u64).to_le_bytes());
+    for triangle in &mesh.triangles {
+        for &index in triangle {
+            bytes.extend_from_slice(&(index as u64).to_le_bytes());
+        }
+    }
+    bytes
+}
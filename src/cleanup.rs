@@ -0,0 +1,248 @@
+//! Mesh cleanup: removing triangles and vertices that carry no geometry, and the one-shot repair of [`repair_mesh`].
+
+use std::collections::{HashMap, HashSet};
+
+use nalgebra::Vector3;
+
+use crate::{find_inconsistent_triangles, flip_all, flip_triangles, is_inside_out, Mesh};
+
+/// Default threshold below which the normal magnitude of a triangle is considered zero.
+pub const DEFAULT_DEGENERATE_EPSILON: f64 = 1e-12;
+
+/// Default distance within which [`repair_mesh`] merges vertices, when no other is given.
+pub const DEFAULT_WELD_EPSILON: f64 = 1e-9;
+
+#[test]
+fn test_find_degenerate_triangles() {
+    use nalgebra::Vector3;
+
+    let mesh = Mesh {
+        vertices: vec![
+            Vector3::new(0.0, 0.0, 0.0),
+            Vector3::new(1.0, 0.0, 0.0),
+            Vector3::new(0.0, 1.0, 0.0),
+            Vector3::new(2.0, 0.0, 0.0),
+        ],
+        // a valid triangle, a triangle with a repeated index and a triangle with collinear points
+        triangles: vec![[0, 1, 2], [0, 1, 1], [0, 1, 3]],
+        colors: None,
+        groups: None,
+    };
+    assert_eq!(find_degenerate_triangles(&mesh, DEFAULT_DEGENERATE_EPSILON), vec![1, 2]);
+}
+
+/// Return the indices of the triangles whose normal vector, the cross product of the edges 0->1 and 0->2,
+/// has a magnitude below `epsilon`. Such triangles have zero area and no meaningful orientation.
+pub fn find_degenerate_triangles(mesh: &Mesh, epsilon: f64) -> Vec<usize> {
+    mesh.triangles
+        .iter()
+        .enumerate()
+        .filter(|(_, triangle)| {
+            let [v0, v1, v2] = triangle.map(|i| mesh.vertices[i]);
+            (v1 - v0).cross(&(v2 - v0)).norm() < epsilon
+        })
+        .map(|(t, _)| t)
+        .collect()
+}
+
+#[test]
+fn test_drop_degenerate_triangles() {
+    let mut mesh = crate::parse_input("tests/input.txt").unwrap();
+    mesh.triangles.push([0, 0, 1]);
+    assert_eq!(drop_degenerate_triangles(&mut mesh, DEFAULT_DEGENERATE_EPSILON), 1);
+    assert_eq!(mesh.triangles.len(), 4);
+}
+
+/// Remove the triangles found by [`find_degenerate_triangles`] and return how many were removed.
+pub fn drop_degenerate_triangles(mesh: &mut Mesh, epsilon: f64) -> usize {
+    let degenerate = find_degenerate_triangles(mesh, epsilon);
+    remove_triangles(mesh, &degenerate);
+    degenerate.len()
+}
+
+/// Remove the triangles at the given indices, which must be in increasing order, together with their groups.
+fn remove_triangles(mesh: &mut Mesh, indices: &[usize]) {
+    let mut t = 0;
+    mesh.triangles.retain(|_| {
+        t += 1;
+        indices.binary_search(&(t - 1)).is_err()
+    });
+    if let Some(groups) = &mut mesh.groups {
+        let mut t = 0;
+        groups.retain(|_| {
+            t += 1;
+            indices.binary_search(&(t - 1)).is_err()
+        });
+    }
+}
+
+#[test]
+fn test_find_duplicate_triangles() {
+    let mut mesh = crate::parse_input("tests/input.txt").unwrap();
+    // a rotated copy of triangle 0 and a reversed copy of triangle 3
+    mesh.triangles.extend([[1, 2, 0], [1, 3, 2]]);
+    assert_eq!(find_duplicate_triangles(&mesh, false), vec![4]);
+    assert_eq!(find_duplicate_triangles(&mesh, true), vec![4, 5]);
+
+    let mut reversed = mesh.clone();
+    assert_eq!(drop_duplicate_triangles(&mut mesh, false), 1);
+    assert_eq!(mesh.triangles[4], [1, 3, 2]);
+    assert_eq!(drop_duplicate_triangles(&mut reversed, true), 2);
+    assert_eq!(reversed.triangles, crate::parse_input("tests/input.txt").unwrap().triangles);
+}
+
+/// Return the indices of the triangles that repeat an earlier triangle, that is one with the same three
+/// indices in the same cyclic order, whatever the first index. With `ignore_winding`, a triangle with the
+/// same indices in the reverse order is a repeat too.
+pub fn find_duplicate_triangles(mesh: &Mesh, ignore_winding: bool) -> Vec<usize> {
+    let mut seen: HashSet<[usize; 3]> = HashSet::new();
+    mesh.triangles
+        .iter()
+        .enumerate()
+        .filter(|(_, triangle)| {
+            let mut key = **triangle;
+            if ignore_winding {
+                key.sort_unstable();
+            } else {
+                // rotating the smallest index to the front keeps the winding
+                let first = (0..3).min_by_key(|&k| key[k]).unwrap_or_default();
+                key.rotate_left(first);
+            }
+            !seen.insert(key)
+        })
+        .map(|(t, _)| t)
+        .collect()
+}
+
+/// Remove the triangles found by [`find_duplicate_triangles`], keeping the first occurrence of every triangle,
+/// and return how many were removed.
+pub fn drop_duplicate_triangles(mesh: &mut Mesh, ignore_winding: bool) -> usize {
+    let duplicates = find_duplicate_triangles(mesh, ignore_winding);
+    remove_triangles(mesh, &duplicates);
+    duplicates.len()
+}
+
+/// Incrementally builds a shared vertex list, merging vertices whose coordinates round to the same
+/// multiple of `epsilon` on every axis.
+pub(crate) struct VertexMerger {
+    pub(crate) vertices: Vec<Vector3<f64>>,
+    lookup: HashMap<[i64; 3], usize>,
+    epsilon: f64,
+}
+
+impl VertexMerger {
+    pub(crate) fn new(epsilon: f64) -> Self {
+        VertexMerger { vertices: Vec::new(), lookup: HashMap::new(), epsilon }
+    }
+
+    /// Return the index of the vertex at `point`, adding it if no coincident vertex exists yet.
+    pub(crate) fn index_of(&mut self, point: Vector3<f64>) -> usize {
+        let key = [
+            (point.x / self.epsilon).round() as i64,
+            (point.y / self.epsilon).round() as i64,
+            (point.z / self.epsilon).round() as i64,
+        ];
+        let vertices = &mut self.vertices;
+        *self.lookup.entry(key).or_insert_with(|| {
+            vertices.push(point);
+            vertices.len() - 1
+        })
+    }
+}
+
+#[test]
+fn test_weld_vertices() {
+    let mut mesh = crate::parse_input("tests/input.txt").unwrap();
+    // duplicate the first two points and point the last triangle at the copies
+    mesh.vertices.push(mesh.vertices[0]);
+    mesh.vertices.push(mesh.vertices[1]);
+    mesh.triangles[3] = [5, 2, 4];
+    mesh.colors = Some(vec![[0, 0, 0], [1, 1, 1], [2, 2, 2], [3, 3, 3], [4, 4, 4], [5, 5, 5]]);
+
+    assert_eq!(weld_vertices(&mut mesh, 1e-9), 2);
+    assert_eq!(mesh.vertices.len(), 4);
+    assert_eq!(mesh.triangles[3], [1, 2, 0]);
+    assert_eq!(mesh.colors, Some(vec![[0, 0, 0], [1, 1, 1], [2, 2, 2], [3, 3, 3]]));
+    assert!(crate::validate_mesh(&mesh).is_ok());
+}
+
+/// Merge vertices whose coordinates coincide within `epsilon`, rewrite the triangle indices to the
+/// merged vertices and remove the duplicates. The first vertex of every group of coincident vertices is kept,
+/// together with its color. Returns the number of vertices removed.
+pub fn weld_vertices(mesh: &mut Mesh, epsilon: f64) -> usize {
+    let mut merger = VertexMerger::new(epsilon);
+    let remap: Vec<usize> = mesh.vertices.iter().map(|&point| merger.index_of(point)).collect();
+    for triangle in &mut mesh.triangles {
+        *triangle = triangle.map(|i| remap[i]);
+    }
+    if let Some(colors) = &mut mesh.colors {
+        // merged vertices are numbered in order of first appearance, so each one takes the color of its first vertex
+        let mut merged_colors: Vec<[u8; 3]> = Vec::with_capacity(merger.vertices.len());
+        for (&color, &k) in colors.iter().zip(&remap) {
+            if k == merged_colors.len() {
+                merged_colors.push(color);
+            }
+        }
+        *colors = merged_colors;
+    }
+    let removed = mesh.vertices.len() - merger.vertices.len();
+    mesh.vertices = merger.vertices;
+    removed
+}
+
+/// How many fixes each step of [`repair_mesh`] applied.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RepairReport {
+    /// Vertices merged into a coincident one, see [`weld_vertices`].
+    pub merged_vertices: usize,
+    /// Triangles without area removed, see [`drop_degenerate_triangles`].
+    pub degenerate_triangles: usize,
+    /// Triangles flipped to agree with their neighbours, see [`crate::make_winding_consistent`].
+    pub inconsistent_triangles: usize,
+    /// Triangles flipped because the consistently wound surface faced inwards, see [`crate::orient_outward`].
+    pub inside_out_triangles: usize,
+}
+
+#[test]
+fn test_repair_report_flipped_triangles() {
+    let report = RepairReport { merged_vertices: 1, degenerate_triangles: 1, inconsistent_triangles: 1, inside_out_triangles: 4 };
+    assert_eq!(report.flipped_triangles(), 3);
+    assert_eq!(RepairReport { inside_out_triangles: 0, ..report }.flipped_triangles(), 1);
+}
+
+impl RepairReport {
+    /// The number of triangles whose winding differs from the input after the repair. Flipping the whole surface
+    /// turns the triangles the flood fill flipped back to their input winding.
+    pub fn flipped_triangles(&self) -> usize {
+        if self.inside_out_triangles > 0 {
+            self.inside_out_triangles - self.inconsistent_triangles
+        } else {
+            self.inconsistent_triangles
+        }
+    }
+}
+
+#[test]
+fn test_repair_mesh() {
+    let mut mesh = crate::parse_input("tests/messy.txt").unwrap();
+    let report = repair_mesh(&mut mesh, DEFAULT_WELD_EPSILON);
+    assert_eq!(report, RepairReport { merged_vertices: 1, degenerate_triangles: 1, inconsistent_triangles: 1, inside_out_triangles: 4 });
+    assert_eq!(mesh, crate::parse_input("tests/reordered.txt").unwrap());
+}
+
+/// Clean up a dirty mesh in one go: merge the vertices closer than `weld_epsilon`, drop the triangles left without
+/// area, make the winding consistent by flood fill and flip the whole surface if it faces inwards. The steps run in
+/// this order since welding can collapse triangles, and a degenerate triangle has no winding to propagate.
+pub fn repair_mesh(mesh: &mut Mesh, weld_epsilon: f64) -> RepairReport {
+    let merged_vertices = weld_vertices(mesh, weld_epsilon);
+    let degenerate_triangles = drop_degenerate_triangles(mesh, DEFAULT_DEGENERATE_EPSILON);
+    let inconsistent = find_inconsistent_triangles(mesh);
+    flip_triangles(mesh, &inconsistent);
+    let inside_out_triangles = if is_inside_out(mesh) {
+        flip_all(mesh);
+        mesh.triangles.len()
+    } else {
+        0
+    };
+    RepairReport { merged_vertices, degenerate_triangles, inconsistent_triangles: inconsistent.len(), inside_out_triangles }
+}
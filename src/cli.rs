@@ -0,0 +1,873 @@
+//! Command line parsing for the `mesh-reorienter` binary.
+
+/// Centroid that triangles are reoriented against when the centroid approach is selected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Centroid {
+    /// The average of the vertex positions.
+    Vertex,
+    /// The area-weighted average of the triangle centroids.
+    Area,
+}
+
+/// Subcommand given as the first argument; without one, the mesh is reoriented.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Command {
+    /// Reorient the mesh and write it.
+    #[default]
+    Reorient,
+    /// List the triangles that would be flipped and fail if there are any, like `--check`.
+    Check,
+    /// Write the mesh in the output format without reorienting it, like `--no-reorient`.
+    Convert,
+    /// Print the statistics of the input mesh and how many triangles would be flipped, without writing anything.
+    Stats,
+    /// Weld the vertices, drop degenerate triangles, make the winding consistent and orient the mesh outwards,
+    /// then print how many fixes each step applied.
+    Repair,
+}
+
+impl Command {
+    /// The name of the subcommand on the command line.
+    fn name(self) -> &'static str {
+        match self {
+            Command::Reorient => "reorient",
+            Command::Check => "check",
+            Command::Convert => "convert",
+            Command::Stats => "stats",
+            Command::Repair => "repair",
+        }
+    }
+}
+
+use mesh_reorienter::{NormalBackend, PrecisionSpec, Winding};
+use nalgebra::Vector3;
+
+/// Help text printed by `--help` and after usage errors.
+pub const USAGE: &str = "\
+Usage: mesh-reorienter [reorient] [OPTIONS] <INPUT> <OUTPUT> [PRECISION]
+       mesh-reorienter check [OPTIONS] <INPUT>
+       mesh-reorienter convert [OPTIONS] <INPUT> <OUTPUT> [PRECISION]
+       mesh-reorienter stats [OPTIONS] <INPUT>
+       mesh-reorienter repair [OPTIONS] <INPUT> <OUTPUT> [PRECISION]
+       mesh-reorienter [OPTIONS] --input <PATH>... --output <PATH>
+       mesh-reorienter [OPTIONS] --dry-run --input <PATH>
+       mesh-reorienter [OPTIONS] --vertices <PATH> --faces <PATH> <OUTPUT> [PRECISION]
+
+Reorients the triangles of a meshgrid so that their winding is consistent.
+
+Commands:
+  reorient                 reorient the mesh and write it; the default when no command is given
+  check                    list the triangles that would be flipped and exit with status 1 if there
+                           are any, like --check
+  convert                  write the mesh in the output format without reorienting it, like
+                           --no-reorient
+  stats                    print the statistics of the input mesh, as with --stats, and how many
+                           triangles would be flipped to standard error, without writing any output
+  repair                   merge coincident vertices, within the --weld distance or 1e-9, drop
+                           degenerate triangles, make the winding consistent, orient the mesh
+                           outwards and write it, printing how many fixes each step applied
+
+Options:
+  -i, --input <PATH>       input mesh file, or - for standard input; may be repeated to merge
+                           several meshes into one output, which is then reoriented as a whole
+      --vertices <PATH>    read the points from a file of native format point rows, optionally
+                           preceded by their count, instead of an input mesh file; requires --faces
+      --faces <PATH>       read the triangles from a file of native format triangle rows, optionally
+                           preceded by their count; requires --vertices
+  -o, --output <PATH>      output mesh file, or - for standard output
+  -p, --precision <N>      number of decimals written for coordinates [default: 1];
+                           either one number for all axes or a per-axis triple such as 2,2,5
+      --auto-precision     count the precision in significant figures [default: 6] rather than
+                           decimals, choosing the decimals of every coordinate from its magnitude
+      --preserve-coords    write the coordinates of every point exactly as they were read, unless a
+                           precision is given; only applies when reading and writing the native format
+  -f, --format <FORMAT>    input format, overriding the input extension: txt, obj, off, ply, stl, bin,
+                           json
+      --output-format <FORMAT>
+                           output format, overriding the output extension: native, obj, off, ply,
+                           stl, bin, json; the only way to pick a format when writing to standard output
+      --allow-nonfinite    accept nan and inf coordinates in the native format, and null ones,
+                           read as nan, in JSON, instead of failing on the first one
+      --index-base <BASE>  index of the first point in the triangle rows of a native format output,
+                           0 (the default) or 1, whatever the input format
+      --input-index-base <BASE>
+                           index of the first point in the triangle rows of a native format input,
+                           0 (the default) or 1
+      --no-counts          read a native format input without the point and triangle count lines,
+                           whose points end at the first blank line or at a line holding just faces
+      --2d                 read points with just x and y coordinates, in the z = 0 plane, or with a z
+                           of 0; a native file whose first point has two coordinates is read this way
+                           without it
+      --indexed-points     read point rows starting with the index of the point, I X Y Z, which the
+                           triangle rows refer to
+      --centroid <KIND>    reorient every triangle against the centroid instead of propagating
+                           the winding between neighbours; KIND is vertex or area
+      --robust-reference   reorient every triangle against a point found inside the solid by ray
+                           casting instead of the centroid, for shapes such as crescents whose
+                           centroid lies outside; implies the centroid approach; an open surface,
+                           or one where no such point turns up, falls back to the vertex centroid
+      --reference <X> <Y> <Z>
+                           reorient every triangle against the given point, known to lie inside the
+                           solid, instead of the centroid; implies the centroid approach and cannot
+                           be combined with --centroid, --robust-reference or --components
+      --components         reorient every connected component, a group of triangles sharing vertices,
+                           against its own centroid or interior point, for files holding several
+                           separate surfaces; implies the centroid approach
+      --split-output       write every connected component to its own file, numbered from 0 before
+                           the extension of the output path, such as out_0.txt and out_1.txt for
+                           out.txt, each reoriented like with --components
+      --reference-mesh <PATH>
+                           give every triangle also found in the previously oriented mesh at PATH,
+                           with the same three points, the winding it has there, and only reorient
+                           the other triangles; cannot be combined with --dry-run, --check, --range
+                           or --verbose
+      --normal-method <METHOD>
+                           normal used by --centroid: cross (cross product of two edges, the
+                           default) or newell (Newell's method, robust for sliver triangles);
+                           requires --centroid, --robust-reference, --reference, --components,
+                           --split-output or --compare-algorithms, since the flood fill uses none
+      --ambiguous-epsilon <EPSILON>
+                           with --centroid, --robust-reference or --reference, which it requires,
+                           leave triangles whose normal is within EPSILON of perpendicular to the
+                           centroid ray unchanged instead of flipping them
+      --outward            flip the whole mesh if its signed volume is negative
+      --recenter           move the center of the bounding box to the origin while analysing the
+                           orientation, for meshes far from the origin; the output keeps the
+                           original coordinates
+      --range <START:END>  only reorient the triangles with indices from START up to, but not
+                           including, END; the analysis still looks at the whole mesh
+      --winding <ORDER>    vertex order of outward-facing triangles seen from outside: ccw (the
+                           default) or cw; cw requires --centroid, --robust-reference, --reference
+                           or --outward
+      --no-reorient        only convert the mesh: parse, triangulate, clean up and write it without
+                           changing the winding of any face; cannot be combined with the options
+                           that pick or check the orientation
+      --flip-all           reverse every triangle without any orientation analysis, for meshes
+                           known to be uniformly inside-out; cannot be combined with the options
+                           that pick or check the orientation
+      --polygons           keep faces with more than three vertices intact instead of splitting them
+                           into triangles; cannot be combined with --weld, --scale, --translate,
+                           --preserve-coords, --drop-degenerate, --dedup-triangles, --dedup-reversed,
+                           --emit-normals, --emit-vertex-normals, --embed-normals, --flip-mask,
+                           --dump-reference, --check-manifold, --stats, --area-report, --obb,
+                           --topology, --compare-algorithms, --dry-run, --check, --range,
+                           --robust-reference, --reference, --reference-mesh, --ambiguous-epsilon,
+                           --allow-nonfinite, --index-base, --input-index-base, --no-counts, --2d,
+                           --indexed-points, --verbose, --flip-all, --components, --split-output,
+                           --recenter, --time, --progress, --sort-triangles, --double-sided,
+                           --max-flip-ratio, --stable-order or several inputs
+      --drop-degenerate    remove zero-area triangles before reorienting
+      --dedup-triangles    remove triangles repeating an earlier one with the same indices in the same
+                           cyclic order, keeping the first, before reorienting
+      --dedup-reversed     like --dedup-triangles, also removing triangles that repeat an earlier one
+                           in the reverse order
+      --weld <EPSILON>     merge vertices closer than EPSILON before reorienting; cannot be
+                           combined with --preserve-coords
+      --stable-order       guarantee that the i-th output triangle is the i-th input triangle, with
+                           at most its last two indices swapped, and fail otherwise; cannot be
+                           combined with --drop-degenerate, --dedup-triangles, --dedup-reversed,
+                           --weld, --double-sided or --split-output, which add, remove or renumber
+                           triangles
+      --sort-triangles     write the triangles sorted by their indices, each rotated to start at its
+                           smallest index, so that the output is the same byte for byte whatever
+                           the order of the input triangles; cannot be combined with --stable-order
+                           or --flip-mask
+      --double-sided       append a reversed copy of every triangle after reorienting, sharing its
+                           points, so that renderers show the surface from both sides
+      --max-flip-ratio <R>
+                           fail without writing any output if more than the fraction R, between 0
+                           and 1, of the triangles would be flipped, which usually means a bad
+                           reference point or an inside-out import; reversing the whole mesh for
+                           --outward or --winding cw does not count
+      --scale <S|X,Y,Z>    multiply the written coordinates by S, or by X, Y and Z along each axis,
+                           such as 0.001 to convert millimeters to meters; the factors must be
+                           positive, since a mirroring scale would turn the mesh inside out
+      --translate <X,Y,Z>  add the vector X,Y,Z to the written coordinates, after any --scale,
+                           for instance to align several meshes
+      --emit-normals <PATH>
+                           write the unit normal of every reoriented triangle to PATH
+      --emit-vertex-normals <PATH>
+                           write the unit normal of every point to PATH, averaging the normals of
+                           its triangles weighted by their angle at the point, for smooth shading
+      --embed-normals      store the unit normal of every point in the output mesh, as vn lines
+                           referenced by the faces in OBJ and as nx, ny and nz properties in PLY,
+                           the only output formats holding normals
+      --flip-mask <PATH>   write one line per triangle to PATH, in input order, holding 1 if the
+                           triangle was flipped and 0 if it was left unchanged; cannot be combined
+                           with --drop-degenerate, --dedup-triangles, --dedup-reversed or the
+                           repair command, which remove triangles
+      --dump-reference <PATH>
+                           write the reference point selected by --centroid, --robust-reference or
+                           --reference to PATH as one line of full-precision coordinates, after any
+                           --scale and --translate; a planar mesh has no reference point
+      --check-manifold     warn about boundary edges, shared by a single triangle, and
+                           non-manifold edges, shared by three or more triangles
+      --stats              print the vertex and triangle counts, surface area, signed volume,
+                           bounding box and number of flipped triangles to standard error, with the
+                           area and signed volume of each connected component if there are several
+      --area-report        print the smallest, largest and mean triangle area, the standard deviation
+                           of the areas and the indices of the smallest and largest triangles to
+                           standard error
+      --obb                print the oriented bounding box along the principal axes of the points,
+                           with its center, axes and side lengths, to standard error
+      --topology           print the vertex, edge and face counts, the Euler characteristic and the
+                           genus of the surface to standard error, with a warning if the surface
+                           is not a closed manifold, for which the genus is meaningless
+      --compare-algorithms
+                           print to standard error on how many triangles the centroid approach and
+                           the flood fill of the default mode disagree, a diagnostic which should
+                           report none on a convex closed mesh
+      --dry-run            print how many triangles would be flipped and their indices,
+                           without writing any output; the output path may be omitted
+      --check              exit with status 1 and list the triangles that would be flipped if
+                           there are any, without writing any output; the output path may be omitted
+      --time               print the wall-clock time spent parsing, computing the centroid, reorienting,
+                           which includes the centroid, and writing to standard error
+      --progress           show a progress bar with the estimated time left while reorienting against
+                           a centroid or a reference point, when standard error is a terminal;
+                           requires --centroid, --robust-reference or --reference, and cannot be
+                           combined with --components, --split-output, --reference-mesh,
+                           --ambiguous-epsilon, --verbose, --range, --dry-run, --check or the stats
+                           command, where it would show nothing
+  -v, --verbose            log the normal, the dot product with the centroid ray and the decision for
+                           every triangle to standard error; requires --centroid, --robust-reference
+                           or --reference
+  -h, --help               print this help
+
+Input and output formats are picked from the file extensions (.obj, .off, .ply, .stl, .bin, .json);
+JSON files hold an object with a vertices array of [x, y, z] and a triangles array of [i, j, k];
+PLY files are read in the ascii or binary_little_endian format and written in ascii;
+input and output files may be gzip-compressed with an additional .gz extension, as in mesh.obj.gz;
+any other extension is read and written in the native text format:
+  <number of points>
+  <x0> <y0> <z0>
+  ...
+  <number of triangles>
+  <point_index0> <point_index1> <point_index2>
+  ...
+where the triangle indices are 0-based indices into the point list. Values may be separated
+by whitespace or commas. Blank lines and lines starting with # are ignored.
+The .bin format holds the same values in binary: the magic bytes MESHBIN1, then little-endian u64
+counts and indices and f64 coordinates.";
+
+/// Options of a run of the binary.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Args {
+    pub command: Command,
+    /// Input paths, at least one unless `split_input` is given; the meshes of several inputs are merged in order.
+    pub inputs: Vec<String>,
+    /// Vertex file and face file read together instead of `inputs`.
+    pub split_input: Option<(String, String)>,
+    /// Output path, only missing with `--dry-run` or `--check`.
+    pub output: Option<String>,
+    pub precision: PrecisionSpec,
+    /// Set by `--preserve-coords` when no precision is given.
+    pub preserve_coords: bool,
+    pub format: Option<String>,
+    pub output_format: Option<String>,
+    pub allow_nonfinite: bool,
+    /// Index base of the native format output.
+    pub index_base: usize,
+    /// Index base of the native format input.
+    pub input_index_base: usize,
+    pub no_counts: bool,
+    pub planar: bool,
+    pub indexed_points: bool,
+    pub centroid: Option<Centroid>,
+    pub robust_reference: bool,
+    /// Point given with `--reference`, reoriented against instead of a computed centroid.
+    pub reference: Option<Vector3<f64>>,
+    pub components: bool,
+    pub split_output: bool,
+    pub reference_mesh: Option<String>,
+    pub normal_method: NormalBackend,
+    pub ambiguous_epsilon: Option<f64>,
+    pub outward: bool,
+    pub recenter: bool,
+    pub winding: Winding,
+    pub range: Option<std::ops::Range<usize>>,
+    pub no_reorient: bool,
+    pub flip_all: bool,
+    pub polygons: bool,
+    pub drop_degenerate: bool,
+    /// Remove repeated triangles, also those repeating another in reverse if `dedup_reversed` is set.
+    pub dedup_triangles: bool,
+    pub dedup_reversed: bool,
+    pub stable_order: bool,
+    pub sort_triangles: bool,
+    pub double_sided: bool,
+    pub max_flip_ratio: Option<f64>,
+    pub weld: Option<f64>,
+    pub scale: Option<Vector3<f64>>,
+    pub translate: Option<Vector3<f64>>,
+    pub emit_normals: Option<String>,
+    pub emit_vertex_normals: Option<String>,
+    pub embed_normals: bool,
+    pub flip_mask: Option<String>,
+    pub dump_reference: Option<String>,
+    pub check_manifold: bool,
+    pub stats: bool,
+    pub area_report: bool,
+    pub obb: bool,
+    pub topology: bool,
+    pub compare_algorithms: bool,
+    pub dry_run: bool,
+    pub check: bool,
+    pub verbose: bool,
+    pub time: bool,
+    pub progress: bool,
+}
+
+/// Reason why the command line did not produce a set of [`Args`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum CliError {
+    /// `--help` was requested.
+    Help,
+    /// The arguments are invalid; the message explains why.
+    Usage(String),
+}
+
+#[cfg(test)]
+fn strings(args: &[&str]) -> Vec<String> {
+    args.iter().map(|arg| arg.to_string()).collect()
+}
+
+#[test]
+fn test_parse_args() {
+    let args = parse_args(strings(&["--input", "in.obj", "--output=out.off", "-p", "2,2,5", "--outward"])).unwrap();
+    assert_eq!(args, Args { command: Command::Reorient, inputs: vec!["in.obj".into()], split_input: None, output: Some("out.off".into()), precision: PrecisionSpec { x: 2, y: 2, z: 5, significant: false }, preserve_coords: false, format: None, output_format: None, allow_nonfinite: false, index_base: 0, input_index_base: 0, no_counts: false, planar: false, indexed_points: false, centroid: None, robust_reference: false, reference: None, components: false, split_output: false, reference_mesh: None, normal_method: NormalBackend::CrossProduct, ambiguous_epsilon: None, outward: true, recenter: false, winding: Winding::CounterClockwise, range: None, no_reorient: false, flip_all: false, polygons: false, drop_degenerate: false, dedup_triangles: false, dedup_reversed: false, stable_order: false, sort_triangles: false, double_sided: false, max_flip_ratio: None, weld: None, scale: None, translate: None, emit_normals: None, emit_vertex_normals: None, embed_normals: false, flip_mask: None, dump_reference: None, check_manifold: false, stats: false, area_report: false, obb: false, topology: false, compare_algorithms: false, dry_run: false, check: false, verbose: false, time: false, progress: false });
+
+    let positional = parse_args(strings(&["in.txt", "out.txt", "4"])).unwrap();
+    assert_eq!((positional.inputs, positional.output.as_deref(), positional.precision), (vec!["in.txt".into()], Some("out.txt"), PrecisionSpec::uniform(4)));
+    assert_eq!(parse_args(strings(&["--auto-precision", "in.txt", "out.txt"])).map(|args| args.precision), Ok(PrecisionSpec::significant(6)));
+    assert_eq!(parse_args(strings(&["--auto-precision", "-p", "4", "in.txt", "out.txt"])).map(|args| args.precision), Ok(PrecisionSpec::significant(4)));
+    assert_eq!(parse_args(strings(&["--auto-precision", "-p", "3,0,3", "in.txt", "out.txt"])), Err(CliError::Usage("--auto-precision needs at least one significant figure on every axis".into())));
+    assert_eq!(parse_args(strings(&["-i", "a.obj", "--input=b.obj", "out.obj"])).map(|args| args.inputs), Ok(vec!["a.obj".into(), "b.obj".into()]));
+
+    assert!(parse_args(strings(&["in.txt", "out.txt", "--preserve-coords"])).unwrap().preserve_coords);
+    assert!(!parse_args(strings(&["in.txt", "out.txt", "3", "--preserve-coords"])).unwrap().preserve_coords);
+    assert_eq!(parse_args(strings(&["in.txt"])), Err(CliError::Usage("missing output path".into())));
+    assert_eq!(parse_args(strings(&["--dry-run", "in.txt"])).map(|args| args.output), Ok(None));
+    assert_eq!(parse_args(strings(&["--check", "in.txt"])).map(|args| (args.output, args.check)), Ok((None, true)));
+    assert_eq!(parse_args(strings(&["in.txt", "out.txt", "--format", "xyz"])), Err(CliError::Usage("unknown format 'xyz'".into())));
+    assert_eq!(parse_args(strings(&["in.txt", "-", "--output-format", "txt"])), Err(CliError::Usage("unknown output format 'txt'".into())));
+    assert_eq!(parse_args(strings(&["in.txt", "out.txt", "--polygons", "--weld", "0.1"])), Err(CliError::Usage("--weld cannot be combined with --polygons".into())));
+    assert_eq!(parse_args(strings(&["in.txt", "out.txt", "--winding", "cw"])), Err(CliError::Usage("--winding cw requires --centroid, --robust-reference, --reference or --outward".into())));
+    assert_eq!(parse_args(strings(&["in.txt", "out.txt", "-v"])), Err(CliError::Usage("--verbose requires --centroid, --robust-reference or --reference".into())));
+    assert_eq!(parse_args(strings(&["in.txt", "out.txt", "-v", "--centroid", "area"])).map(|args| args.verbose), Ok(true));
+    assert_eq!(parse_args(strings(&["in.txt", "out.txt", "--ambiguous-epsilon", "0.5"])), Err(CliError::Usage("--ambiguous-epsilon requires --centroid, --robust-reference or --reference".into())));
+    assert!(matches!(parse_args(strings(&["in.txt", "out.txt", "--normal-method", "newell"])), Err(CliError::Usage(message)) if message.starts_with("--normal-method requires")));
+    assert_eq!(parse_args(strings(&["in.txt", "out.txt", "--normal-method", "newell", "--components"])).map(|args| args.normal_method), Ok(NormalBackend::Newell));
+    assert_eq!(parse_args(strings(&["in.txt", "out.txt", "--progress"])), Err(CliError::Usage("--progress requires --centroid, --robust-reference or --reference".into())));
+    assert_eq!(parse_args(strings(&["in.txt", "out.txt", "--progress", "--components", "--centroid", "vertex"])), Err(CliError::Usage("--progress cannot be combined with --components".into())));
+    assert_eq!(parse_args(strings(&["in.txt", "out.txt", "--range=2:4"])).map(|args| args.range), Ok(Some(2..4)));
+    assert_eq!(parse_args(strings(&["in.txt", "out.txt", "--range", "4:2"])), Err(CliError::Usage("invalid range '4:2', expected START:END with START <= END".into())));
+    assert_eq!(parse_args(strings(&["in.txt", "out.txt", "--no-reorient", "--outward"])), Err(CliError::Usage("--outward cannot be combined with --no-reorient".into())));
+    assert_eq!(parse_args(strings(&["in.txt", "out.txt", "--index-base=1"])).map(|args| args.index_base), Ok(1));
+    assert_eq!(parse_args(strings(&["in.txt", "out.txt", "--index-base", "2"])), Err(CliError::Usage("invalid index base '2', expected 0 or 1".into())));
+    assert_eq!(parse_args(strings(&["in.txt", "out.txt", "--flip-all", "--check"])), Err(CliError::Usage("--check cannot be combined with --flip-all".into())));
+    assert_eq!(parse_args(strings(&["in.txt", "out.txt", "--components", "--winding=cw"])).map(|args| (args.components, args.winding)), Ok((true, Winding::Clockwise)));
+    assert_eq!(parse_args(strings(&["in.txt", "out.txt", "--stable-order", "--weld=0.1"])), Err(CliError::Usage("--weld cannot be combined with --stable-order".into())));
+    assert_eq!(parse_args(strings(&["--vertices", "v.txt", "--faces=f.txt", "out.txt"])).map(|args| (args.inputs, args.split_input, args.output)), Ok((vec![], Some(("v.txt".into(), "f.txt".into())), Some("out.txt".into()))));
+    assert_eq!(parse_args(strings(&["--vertices", "v.txt", "out.txt"])), Err(CliError::Usage("--vertices requires --faces".into())));
+    assert_eq!(parse_args(strings(&["--vertices", "v.txt", "--faces", "f.txt", "-i", "in.txt", "out.txt"])), Err(CliError::Usage("--input cannot be combined with --vertices and --faces".into())));
+    assert_eq!(parse_args(strings(&["in.txt", "out.txt", "--dedup-reversed"])).map(|args| (args.dedup_triangles, args.dedup_reversed)), Ok((true, true)));
+    assert_eq!(parse_args(strings(&["--reference", "0.5", "-1", "2e-1", "in.txt", "out.txt"])).map(|args| args.reference), Ok(Some(Vector3::new(0.5, -1.0, 0.2))));
+    assert_eq!(parse_args(strings(&["--reference=1,2,3", "in.txt", "out.txt"])).map(|args| args.reference), Ok(Some(Vector3::new(1.0, 2.0, 3.0))));
+    assert_eq!(parse_args(strings(&["--reference", "1", "2", "in.txt", "out.txt"])), Err(CliError::Usage("invalid reference point '1 2 in.txt', expected three coordinates X Y Z".into())));
+    assert_eq!(parse_args(strings(&["--reference=1,2,3", "--centroid=area", "in.txt", "out.txt"])), Err(CliError::Usage("--centroid cannot be combined with --reference".into())));
+    assert_eq!(parse_args(strings(&["--reference-mesh", "old.txt", "--check", "in.txt"])), Err(CliError::Usage("--check cannot be combined with --reference-mesh".into())));
+    assert_eq!(parse_args(strings(&["--max-flip-ratio", "0.25", "in.txt", "out.txt"])).map(|args| args.max_flip_ratio), Ok(Some(0.25)));
+    assert_eq!(parse_args(strings(&["--max-flip-ratio=1.5", "in.txt", "out.txt"])), Err(CliError::Usage("invalid ratio '1.5', expected a number between 0 and 1".into())));
+    assert_eq!(parse_args(strings(&["--scale", "2", "in.txt", "out.txt"])).map(|args| args.scale), Ok(Some(Vector3::new(2.0, 2.0, 2.0))));
+    assert_eq!(parse_args(strings(&["--scale=1,0.5,1e-3", "in.txt", "out.txt"])).map(|args| args.scale), Ok(Some(Vector3::new(1.0, 0.5, 1e-3))));
+    assert_eq!(parse_args(strings(&["--scale=1,-1,1", "in.txt", "out.txt"])), Err(CliError::Usage("invalid scale '1,-1,1', expected a positive number or three positive numbers X,Y,Z".into())));
+    assert_eq!(parse_args(strings(&["--translate=1,-2,0.5", "in.txt", "out.txt"])).map(|args| args.translate), Ok(Some(Vector3::new(1.0, -2.0, 0.5))));
+    assert_eq!(parse_args(strings(&["--translate", "1,2", "in.txt", "out.txt"])), Err(CliError::Usage("invalid translation '1,2', expected three numbers X,Y,Z".into())));
+    assert_eq!(parse_args(strings(&["--split-output", "in.txt", "out.txt"])).map(|args| (args.split_output, args.components)), Ok((true, false)));
+    assert_eq!(parse_args(strings(&["--split-output", "in.txt", "-"])), Err(CliError::Usage("standard output cannot be combined with --split-output".into())));
+    assert_eq!(parse_args(strings(&["--dump-reference", "ref.txt", "in.txt", "out.txt"])), Err(CliError::Usage("--dump-reference requires --centroid, --robust-reference or --reference, since the default flood fill uses no reference point".into())));
+    assert_eq!(parse_args(strings(&["--dump-reference", "ref.txt", "--components", "--centroid", "vertex", "in.txt", "out.txt"])), Err(CliError::Usage("--dump-reference cannot be combined with --components, which uses one reference point per component".into())));
+    assert_eq!(parse_args(strings(&["--embed-normals", "--double-sided", "in.txt", "out.obj"])), Err(CliError::Usage("--embed-normals cannot be combined with --double-sided".into())));
+    assert_eq!(parse_args(strings(&["--sort-triangles", "--flip-mask", "mask.txt", "in.txt", "out.txt"])), Err(CliError::Usage("--flip-mask cannot be combined with --sort-triangles".into())));
+    assert_eq!(parse_args(strings(&["--drop-degenerate", "--flip-mask", "mask.txt", "in.txt", "out.txt"])), Err(CliError::Usage("--drop-degenerate cannot be combined with --flip-mask".into())));
+    assert_eq!(parse_args(strings(&["--dedup-reversed", "--flip-mask", "mask.txt", "in.txt", "out.txt"])), Err(CliError::Usage("--dedup-reversed cannot be combined with --flip-mask".into())));
+    assert_eq!(parse_args(strings(&["reorient", "in.txt", "out.txt"])).map(|args| (args.command, args.output)), Ok((Command::Reorient, Some("out.txt".into()))));
+    assert_eq!(parse_args(strings(&["check", "in.txt"])).map(|args| (args.command, args.check)), Ok((Command::Check, true)));
+    assert_eq!(parse_args(strings(&["check", "in.txt", "out.txt"])), Err(CliError::Usage("an output path cannot be given to the check command, which writes no output".into())));
+    assert_eq!(parse_args(strings(&["convert", "in.obj", "out.ply"])).map(|args| (args.command, args.no_reorient)), Ok((Command::Convert, true)));
+    assert_eq!(parse_args(strings(&["convert", "in.obj", "out.ply", "--outward"])), Err(CliError::Usage("--outward cannot be combined with the convert command".into())));
+    assert_eq!(parse_args(strings(&["repair", "in.txt", "out.txt", "--outward"])), Err(CliError::Usage("--outward cannot be combined with the repair command".into())));
+    assert_eq!(parse_args(strings(&["stats", "in.txt"])).map(|args| (args.command, args.output)), Ok((Command::Stats, None)));
+    assert_eq!(parse_args(strings(&["stats", "--emit-normals", "n.txt", "in.txt"])), Err(CliError::Usage("--emit-normals cannot be given to the stats command, which writes no output".into())));
+    // a command is only recognised as the first argument
+    assert_eq!(parse_args(strings(&["in.txt", "stats"])).map(|args| (args.command, args.output)), Ok((Command::Reorient, Some("stats".into()))));
+    assert_eq!(parse_args(strings(&["--help"])), Err(CliError::Help));
+}
+
+/// Significant figures written with `--auto-precision` when no precision is given.
+const DEFAULT_SIGNIFICANT_FIGURES: usize = 6;
+
+/// Parse the command line arguments, excluding the program name.
+/// Options accept their value either as the next argument or after `=`, as in `--precision=3`.
+pub fn parse_args(args: impl IntoIterator<Item = String>) -> Result<Args, CliError> {
+    let mut inputs: Vec<String> = Vec::new();
+    let mut vertices: Option<String> = None;
+    let mut faces: Option<String> = None;
+    let mut output: Option<String> = None;
+    let mut precision: Option<PrecisionSpec> = None;
+    let mut auto_precision = false;
+    let mut preserve_coords = false;
+    let mut format: Option<String> = None;
+    let mut output_format: Option<String> = None;
+    let mut allow_nonfinite = false;
+    let mut index_base = 0;
+    let mut input_index_base = 0;
+    let mut no_counts = false;
+    let mut planar = false;
+    let mut indexed_points = false;
+    let mut centroid: Option<Centroid> = None;
+    let mut robust_reference = false;
+    let mut reference: Option<Vector3<f64>> = None;
+    let mut components = false;
+    let mut split_output = false;
+    let mut reference_mesh: Option<String> = None;
+    let mut normal_method: Option<NormalBackend> = None;
+    let mut ambiguous_epsilon: Option<f64> = None;
+    let mut outward = false;
+    let mut recenter = false;
+    let mut winding = Winding::default();
+    let mut range: Option<std::ops::Range<usize>> = None;
+    let mut no_reorient = false;
+    let mut flip_all = false;
+    let mut polygons = false;
+    let mut drop_degenerate = false;
+    let mut dedup_triangles = false;
+    let mut dedup_reversed = false;
+    let mut stable_order = false;
+    let mut sort_triangles = false;
+    let mut double_sided = false;
+    let mut max_flip_ratio: Option<f64> = None;
+    let mut weld: Option<f64> = None;
+    let mut scale = None;
+    let mut translate = None;
+    let mut emit_normals: Option<String> = None;
+    let mut emit_vertex_normals: Option<String> = None;
+    let mut embed_normals = false;
+    let mut flip_mask: Option<String> = None;
+    let mut dump_reference = None;
+    let mut check_manifold = false;
+    let mut stats = false;
+    let mut area_report = false;
+    let mut obb = false;
+    let mut topology = false;
+    let mut compare_algorithms = false;
+    let mut dry_run = false;
+    let mut check = false;
+    let mut verbose = false;
+    let mut time = false;
+    let mut progress = false;
+    let mut positional: Vec<String> = Vec::new();
+
+    let mut args = args.into_iter().peekable();
+    let command = args.peek().and_then(|arg| parse_command(arg));
+    if command.is_some() {
+        args.next();
+    }
+    let command = command.unwrap_or_default();
+    while let Some(arg) = args.next() {
+        if !arg.starts_with('-') || arg == "-" {
+            positional.push(arg);
+            continue;
+        }
+        let (name, inline_value) = match arg.split_once('=') {
+            Some((name, value)) => (name.to_string(), Some(value.to_string())),
+            None => (arg.clone(), None),
+        };
+        let mut value = || {
+            inline_value
+                .clone()
+                .or_else(|| args.next())
+                .ok_or_else(|| CliError::Usage(format!("missing value for {}", name)))
+        };
+        match name.as_str() {
+            "-h" | "--help" => return Err(CliError::Help),
+            "-i" | "--input" => inputs.push(value()?),
+            "--vertices" => vertices = Some(value()?),
+            "--faces" => faces = Some(value()?),
+            "-o" | "--output" => output = Some(value()?),
+            "-p" | "--precision" => precision = Some(parse_precision(&value()?)?),
+            "--auto-precision" => auto_precision = true,
+            "--preserve-coords" => preserve_coords = true,
+            "-f" | "--format" => format = Some(parse_format(&value()?)?),
+            "--output-format" => output_format = Some(parse_output_format(&value()?)?),
+            "--allow-nonfinite" => allow_nonfinite = true,
+            "--index-base" => index_base = parse_index_base(&value()?)?,
+            "--input-index-base" => input_index_base = parse_index_base(&value()?)?,
+            "--no-counts" => no_counts = true,
+            "--2d" => planar = true,
+            "--indexed-points" => indexed_points = true,
+            "--centroid" => centroid = Some(parse_centroid(&value()?)?),
+            "--robust-reference" => robust_reference = true,
+            "--reference" => {
+                // the coordinates follow as three arguments, or after `=` separated by commas
+                let coords: Vec<String> = match &inline_value {
+                    Some(inline_value) => inline_value.split(',').map(str::to_string).collect(),
+                    None => args.by_ref().take(3).collect(),
+                };
+                reference = Some(parse_reference(&coords)?);
+            }
+            "--components" => components = true,
+            "--split-output" => split_output = true,
+            "--reference-mesh" => reference_mesh = Some(value()?),
+            "--normal-method" => normal_method = Some(parse_normal_method(&value()?)?),
+            "--ambiguous-epsilon" => ambiguous_epsilon = Some(parse_epsilon(&value()?)?),
+            "--outward" => outward = true,
+            "--recenter" => recenter = true,
+            "--winding" => winding = parse_winding(&value()?)?,
+            "--range" => range = Some(parse_range(&value()?)?),
+            "--no-reorient" => no_reorient = true,
+            "--flip-all" => flip_all = true,
+            "--polygons" => polygons = true,
+            "--drop-degenerate" => drop_degenerate = true,
+            "--dedup-triangles" => dedup_triangles = true,
+            "--dedup-reversed" => (dedup_triangles, dedup_reversed) = (true, true),
+            "--stable-order" => stable_order = true,
+            "--sort-triangles" => sort_triangles = true,
+            "--double-sided" => double_sided = true,
+            "--max-flip-ratio" => max_flip_ratio = Some(parse_ratio(&value()?)?),
+            "--weld" => weld = Some(parse_epsilon(&value()?)?),
+            "--scale" => scale = Some(parse_scale(&value()?)?),
+            "--translate" => translate = Some(parse_translation(&value()?)?),
+            "--emit-normals" => emit_normals = Some(value()?),
+            "--emit-vertex-normals" => emit_vertex_normals = Some(value()?),
+            "--embed-normals" => embed_normals = true,
+            "--flip-mask" => flip_mask = Some(value()?),
+            "--dump-reference" => dump_reference = Some(value()?),
+            "--check-manifold" => check_manifold = true,
+            "--stats" => stats = true,
+            "--area-report" => area_report = true,
+            "--obb" => obb = true,
+            "--topology" => topology = true,
+            "--compare-algorithms" => compare_algorithms = true,
+            "--dry-run" => dry_run = true,
+            "--check" => check = true,
+            "-v" | "--verbose" => verbose = true,
+            "--time" => time = true,
+            "--progress" => progress = true,
+            _ => return Err(CliError::Usage(format!("unknown option {}", arg))),
+        }
+    }
+
+    let split_input = match (vertices, faces) {
+        (Some(vertices), Some(faces)) => Some((vertices, faces)),
+        (Some(_), None) => return Err(CliError::Usage(String::from("--vertices requires --faces"))),
+        (None, Some(_)) => return Err(CliError::Usage(String::from("--faces requires --vertices"))),
+        (None, None) => None,
+    };
+    if split_input.is_some() {
+        let whole_file = [("--input", !inputs.is_empty()), ("--format", format.is_some()), ("--no-counts", no_counts), ("--preserve-coords", preserve_coords), ("--polygons", polygons)];
+        if let Some((option, _)) = whole_file.iter().find(|(_, given)| *given) {
+            return Err(CliError::Usage(format!("{} cannot be combined with --vertices and --faces", option)));
+        }
+    }
+
+    match command {
+        Command::Reorient | Command::Stats => {}
+        Command::Check => check = true,
+        // the repair runs its own reorientation, so the options that pick one do not apply
+        Command::Convert | Command::Repair => no_reorient = true,
+    }
+
+    // positional arguments fill in whatever was not given as a named option
+    let mut positional = positional.into_iter();
+    if inputs.is_empty() && split_input.is_none() {
+        inputs.push(positional.next().ok_or_else(|| CliError::Usage(String::from("missing input path")))?);
+    }
+    let output = output.or_else(|| positional.next());
+    if output.is_none() && !dry_run && !check && command != Command::Stats {
+        return Err(CliError::Usage(String::from("missing output path")));
+    }
+    let precision = match (precision, positional.next()) {
+        (Some(precision), _) => Some(precision),
+        (None, Some(arg)) => Some(parse_precision(&arg)?),
+        (None, None) => None,
+    };
+    // an explicit precision asks for the coordinates to be reformatted
+    let preserve_coords = preserve_coords && precision.is_none() && !auto_precision;
+    let precision = match precision {
+        Some(precision) => PrecisionSpec { significant: auto_precision, ..precision },
+        None if auto_precision => PrecisionSpec::significant(DEFAULT_SIGNIFICANT_FIGURES),
+        None => PrecisionSpec::uniform(1),
+    };
+    if precision.significant && precision.x.min(precision.y).min(precision.z) == 0 {
+        return Err(CliError::Usage(String::from("--auto-precision needs at least one significant figure on every axis")));
+    }
+    if let Some(extra) = positional.next() {
+        return Err(CliError::Usage(format!("unexpected argument {}", extra)));
+    }
+
+    // the flood fill alone keeps the winding of the first triangle, so there is no outside to follow a convention from
+    if winding == Winding::Clockwise && centroid.is_none() && !robust_reference && reference.is_none() && !components && !outward {
+        return Err(CliError::Usage(String::from("--winding cw requires --centroid, --robust-reference, --reference or --outward")));
+    }
+    // the per-component reference points leave no single ray to measure the ambiguity against
+    if components && ambiguous_epsilon.is_some() {
+        return Err(CliError::Usage(String::from("--ambiguous-epsilon cannot be combined with --components")));
+    }
+    if components && verbose {
+        return Err(CliError::Usage(String::from("--verbose cannot be combined with --components")));
+    }
+    // the flood fill makes no per-triangle decision against a centroid that could be logged, or measured
+    let single_reference = centroid.is_some() || robust_reference || reference.is_some();
+    if verbose && !single_reference {
+        return Err(CliError::Usage(String::from("--verbose requires --centroid, --robust-reference or --reference")));
+    }
+    if ambiguous_epsilon.is_some() && !single_reference {
+        return Err(CliError::Usage(String::from("--ambiguous-epsilon requires --centroid, --robust-reference or --reference")));
+    }
+    // nor computes any triangle normal
+    if normal_method.is_some() && !single_reference && !components && !split_output && !compare_algorithms {
+        return Err(CliError::Usage(String::from("--normal-method requires --centroid, --robust-reference, --reference, --components, --split-output or --compare-algorithms")));
+    }
+    // the progress bar follows the reorientation of the whole mesh against a single reference point, and nothing else
+    if progress {
+        if !single_reference {
+            return Err(CliError::Usage(String::from("--progress requires --centroid, --robust-reference or --reference")));
+        }
+        let silent = [("--components", components), ("--split-output", split_output), ("--reference-mesh", reference_mesh.is_some()), ("--ambiguous-epsilon", ambiguous_epsilon.is_some()), ("--verbose", verbose), ("--range", range.is_some()), ("--dry-run", dry_run), ("--check", check), ("the stats command", command == Command::Stats)];
+        if let Some((option, _)) = silent.iter().find(|(_, given)| *given) {
+            return Err(CliError::Usage(format!("--progress cannot be combined with {}", option)));
+        }
+    }
+    if reference.is_some() {
+        let other_references = [("--centroid", centroid.is_some()), ("--robust-reference", robust_reference), ("--components", components)];
+        if let Some((option, _)) = other_references.iter().find(|(_, given)| *given) {
+            return Err(CliError::Usage(format!("{} cannot be combined with --reference", option)));
+        }
+    }
+    // the matched triangles are settled before any analysis, which these options would have to report or log
+    if reference_mesh.is_some() {
+        let analysing = [("--dry-run", dry_run), ("--check", check), ("--range", range.is_some()), ("--verbose", verbose)];
+        if let Some((option, _)) = analysing.iter().find(|(_, given)| *given) {
+            return Err(CliError::Usage(format!("{} cannot be combined with --reference-mesh", option)));
+        }
+    }
+    if stable_order && drop_degenerate {
+        return Err(CliError::Usage(String::from("--drop-degenerate cannot be combined with --stable-order")));
+    }
+    if stable_order && dedup_triangles {
+        let option = if dedup_reversed { "--dedup-reversed" } else { "--dedup-triangles" };
+        return Err(CliError::Usage(format!("{} cannot be combined with --stable-order", option)));
+    }
+    if stable_order && weld.is_some() {
+        return Err(CliError::Usage(String::from("--weld cannot be combined with --stable-order")));
+    }
+    if stable_order && command == Command::Repair {
+        return Err(CliError::Usage(String::from("--stable-order cannot be combined with the repair command")));
+    }
+    // both change the triangle list after the reorientation: one appends reversed copies, the other renumbers
+    // the triangles of every component into a file of its own
+    if stable_order && (double_sided || split_output) {
+        let option = if double_sided { "--double-sided" } else { "--split-output" };
+        return Err(CliError::Usage(format!("{} cannot be combined with --stable-order", option)));
+    }
+    // the mask lists the input triangles by position, which removing triangles shifts
+    if flip_mask.is_some() {
+        let removing = [("--drop-degenerate", drop_degenerate), ("--dedup-reversed", dedup_reversed), ("--dedup-triangles", dedup_triangles), ("the repair command", command == Command::Repair)];
+        if let Some((option, _)) = removing.iter().find(|(_, given)| *given) {
+            return Err(CliError::Usage(format!("{} cannot be combined with --flip-mask", option)));
+        }
+    }
+    // a point shared by a triangle and its reversed copy gets opposite normals, which cancel out
+    if embed_normals && double_sided {
+        return Err(CliError::Usage(String::from("--embed-normals cannot be combined with --double-sided")));
+    }
+    // the mask lists the triangles in their input order, which sorting loses
+    if sort_triangles && (stable_order || flip_mask.is_some()) {
+        let option = if stable_order { "--stable-order" } else { "--flip-mask" };
+        return Err(CliError::Usage(format!("{} cannot be combined with --sort-triangles", option)));
+    }
+    if preserve_coords && weld.is_some() {
+        return Err(CliError::Usage(String::from("--weld cannot be combined with --preserve-coords")));
+    }
+    if preserve_coords && scale.is_some() {
+        return Err(CliError::Usage(String::from("--scale cannot be combined with --preserve-coords")));
+    }
+    if preserve_coords && translate.is_some() {
+        return Err(CliError::Usage(String::from("--translate cannot be combined with --preserve-coords")));
+    }
+    if dump_reference.is_some() {
+        if components || split_output {
+            let option = if components { "--components" } else { "--split-output" };
+            return Err(CliError::Usage(format!("--dump-reference cannot be combined with {}, which uses one reference point per component", option)));
+        }
+        if centroid.is_none() && !robust_reference && reference.is_none() {
+            return Err(CliError::Usage(String::from("--dump-reference requires --centroid, --robust-reference or --reference, since the default flood fill uses no reference point")));
+        }
+    }
+    // every component is reoriented against its own reference point like with --components, and written on its own
+    if split_output {
+        let conflicting = [("--ambiguous-epsilon", ambiguous_epsilon.is_some()), ("--verbose", verbose), ("--reference", reference.is_some()), ("--preserve-coords", preserve_coords), ("--dry-run", dry_run), ("--check", check), ("standard output", output.as_deref() == Some("-"))];
+        if let Some((option, _)) = conflicting.iter().find(|(_, given)| *given) {
+            return Err(CliError::Usage(format!("{} cannot be combined with --split-output", option)));
+        }
+    }
+    if matches!(command, Command::Check | Command::Stats) {
+        let writing = [("an output path", output.is_some()), ("--output-format", output_format.is_some()), ("--auto-precision", auto_precision), ("--emit-normals", emit_normals.is_some()), ("--emit-vertex-normals", emit_vertex_normals.is_some()), ("--embed-normals", embed_normals), ("--flip-mask", flip_mask.is_some()), ("--split-output", split_output), ("--sort-triangles", sort_triangles), ("--double-sided", double_sided), ("--scale", scale.is_some()), ("--translate", translate.is_some()), ("--dry-run", dry_run), ("--check", command == Command::Stats && check)];
+        if let Some((option, _)) = writing.iter().find(|(_, given)| *given) {
+            return Err(CliError::Usage(format!("{} cannot be given to the {} command, which writes no output", option, command.name())));
+        }
+    }
+    if no_reorient || flip_all {
+        let command_name = format!("the {} command", command.name());
+        let mode = match command {
+            Command::Convert | Command::Repair => command_name.as_str(),
+            _ if no_reorient => "--no-reorient",
+            _ => "--flip-all",
+        };
+        let reorienting = [("--centroid", centroid.is_some()), ("--robust-reference", robust_reference), ("--reference", reference.is_some()), ("--components", components), ("--reference-mesh", reference_mesh.is_some()), ("--ambiguous-epsilon", ambiguous_epsilon.is_some()), ("--outward", outward), ("--winding cw", winding == Winding::Clockwise), ("--range", range.is_some()), ("--max-flip-ratio", max_flip_ratio.is_some()), ("--dry-run", dry_run), ("--check", check), ("--flip-all", no_reorient && flip_all)];
+        if let Some((option, _)) = reorienting.iter().find(|(_, given)| *given) {
+            return Err(CliError::Usage(format!("{} cannot be combined with {}", option, mode)));
+        }
+    }
+    if polygons {
+        let triangle_only = [("--weld", weld.is_some()), ("--scale", scale.is_some()), ("--translate", translate.is_some()), ("--preserve-coords", preserve_coords), ("--drop-degenerate", drop_degenerate), ("--dedup-reversed", dedup_reversed), ("--dedup-triangles", dedup_triangles), ("--emit-normals", emit_normals.is_some()), ("--emit-vertex-normals", emit_vertex_normals.is_some()), ("--embed-normals", embed_normals), ("--flip-mask", flip_mask.is_some()), ("--dump-reference", dump_reference.is_some()), ("--check-manifold", check_manifold), ("--stats", stats), ("--area-report", area_report), ("--obb", obb), ("--topology", topology), ("--compare-algorithms", compare_algorithms), ("--dry-run", dry_run), ("--check", check), ("--range", range.is_some()), ("--robust-reference", robust_reference), ("--reference", reference.is_some()), ("--reference-mesh", reference_mesh.is_some()), ("--ambiguous-epsilon", ambiguous_epsilon.is_some()), ("--allow-nonfinite", allow_nonfinite), ("--index-base", index_base != 0), ("--input-index-base", input_index_base != 0), ("--no-counts", no_counts), ("--2d", planar), ("--indexed-points", indexed_points), ("--verbose", verbose), ("--flip-all", flip_all), ("--components", components), ("--split-output", split_output), ("--recenter", recenter), ("--time", time), ("--progress", progress), ("--sort-triangles", sort_triangles), ("--double-sided", double_sided), ("--max-flip-ratio", max_flip_ratio.is_some()), ("--stable-order", stable_order), ("the stats command", command == Command::Stats), ("the repair command", command == Command::Repair), ("several inputs", inputs.len() > 1)];
+        if let Some((option, _)) = triangle_only.iter().find(|(_, given)| *given) {
+            return Err(CliError::Usage(format!("{} cannot be combined with --polygons", option)));
+        }
+    }
+
+    Ok(Args { command, inputs, split_input, output, precision, preserve_coords, format, output_format, allow_nonfinite, index_base, input_index_base, no_counts, planar, indexed_points, centroid, robust_reference, reference, components, split_output, reference_mesh, normal_method: normal_method.unwrap_or_default(), ambiguous_epsilon, outward, recenter, winding, range, no_reorient, flip_all, polygons, drop_degenerate, dedup_triangles, dedup_reversed, stable_order, sort_triangles, double_sided, max_flip_ratio, weld, scale, translate, emit_normals, emit_vertex_normals, embed_normals, flip_mask, dump_reference, check_manifold, stats, area_report, obb, topology, compare_algorithms, dry_run, check, verbose, time, progress })
+}
+
+/// The subcommand named by `arg`, if it names one.
+fn parse_command(arg: &str) -> Option<Command> {
+    [Command::Reorient, Command::Check, Command::Convert, Command::Stats, Command::Repair].into_iter().find(|command| command.name() == arg)
+}
+
+/// Parse the name of a supported input format.
+fn parse_format(value: &str) -> Result<String, CliError> {
+    let format = value.to_lowercase();
+    match format.as_str() {
+        "txt" | "obj" | "off" | "ply" | "stl" | "bin" | "json" => Ok(format),
+        _ => Err(CliError::Usage(format!("unknown format '{}'", value))),
+    }
+}
+
+/// Parse the name of a supported output format, where the native text format is called `native`.
+fn parse_output_format(value: &str) -> Result<String, CliError> {
+    let format = value.to_lowercase();
+    match format.as_str() {
+        "native" | "obj" | "off" | "ply" | "stl" | "bin" | "json" => Ok(format),
+        _ => Err(CliError::Usage(format!("unknown output format '{}'", value))),
+    }
+}
+
+/// Parse the index base of the native format, which is 0 or 1.
+fn parse_index_base(value: &str) -> Result<usize, CliError> {
+    match value {
+        "0" => Ok(0),
+        "1" => Ok(1),
+        _ => Err(CliError::Usage(format!("invalid index base '{}', expected 0 or 1", value))),
+    }
+}
+
+/// Parse the kind of centroid to reorient against.
+fn parse_centroid(value: &str) -> Result<Centroid, CliError> {
+    match value {
+        "vertex" => Ok(Centroid::Vertex),
+        "area" => Ok(Centroid::Area),
+        _ => Err(CliError::Usage(format!("unknown centroid '{}', expected vertex or area", value))),
+    }
+}
+
+/// Parse the three coordinates of a reference point.
+fn parse_reference(coords: &[String]) -> Result<Vector3<f64>, CliError> {
+    let invalid = || CliError::Usage(format!("invalid reference point '{}', expected three coordinates X Y Z", coords.join(" ")));
+    let coords = coords
+        .iter()
+        .map(|coord| coord.parse::<f64>().ok().filter(|coord| coord.is_finite()).ok_or_else(invalid))
+        .collect::<Result<Vec<f64>, CliError>>()?;
+    match coords[..] {
+        [x, y, z] => Ok(Vector3::new(x, y, z)),
+        _ => Err(invalid()),
+    }
+}
+
+/// Parse the method used to compute normal vectors.
+fn parse_normal_method(value: &str) -> Result<NormalBackend, CliError> {
+    match value {
+        "cross" => Ok(NormalBackend::CrossProduct),
+        "newell" => Ok(NormalBackend::Newell),
+        _ => Err(CliError::Usage(format!("unknown normal method '{}', expected cross or newell", value))),
+    }
+}
+
+/// Parse the vertex order of outward-facing triangles.
+fn parse_winding(value: &str) -> Result<Winding, CliError> {
+    match value {
+        "ccw" => Ok(Winding::CounterClockwise),
+        "cw" => Ok(Winding::Clockwise),
+        _ => Err(CliError::Usage(format!("unknown winding '{}', expected ccw or cw", value))),
+    }
+}
+
+/// Parse a half-open range of triangle indices written as `START:END`.
+fn parse_range(value: &str) -> Result<std::ops::Range<usize>, CliError> {
+    let bounds = value.split_once(':')
+        .and_then(|(start, end)| Some((start.parse::<usize>().ok()?, end.parse::<usize>().ok()?)));
+    match bounds {
+        Some((start, end)) if start <= end => Ok(start..end),
+        _ => Err(CliError::Usage(format!("invalid range '{}', expected START:END with START <= END", value))),
+    }
+}
+
+/// Parse a number of decimals, either for all axes or as a comma-separated per-axis triple.
+fn parse_precision(value: &str) -> Result<PrecisionSpec, CliError> {
+    value.parse::<PrecisionSpec>()
+        .map_err(|_| CliError::Usage(format!("invalid precision '{}'", value)))
+}
+
+/// Parse a scale, either one factor for every axis or three comma-separated factors X,Y,Z, all strictly positive.
+fn parse_scale(value: &str) -> Result<Vector3<f64>, CliError> {
+    let invalid = || CliError::Usage(format!("invalid scale '{}', expected a positive number or three positive numbers X,Y,Z", value));
+    let factors = value
+        .split(',')
+        .map(|factor| factor.trim().parse::<f64>().ok().filter(|factor| factor.is_finite() && *factor > 0.0).ok_or_else(invalid))
+        .collect::<Result<Vec<f64>, CliError>>()?;
+    match factors[..] {
+        [s] => Ok(Vector3::new(s, s, s)),
+        [x, y, z] => Ok(Vector3::new(x, y, z)),
+        _ => Err(invalid()),
+    }
+}
+
+/// Parse a translation vector given as three comma-separated coordinates X,Y,Z.
+fn parse_translation(value: &str) -> Result<Vector3<f64>, CliError> {
+    let invalid = || CliError::Usage(format!("invalid translation '{}', expected three numbers X,Y,Z", value));
+    let coords = value
+        .split(',')
+        .map(|coord| coord.trim().parse::<f64>().ok().filter(|coord| coord.is_finite()).ok_or_else(invalid))
+        .collect::<Result<Vec<f64>, CliError>>()?;
+    match coords[..] {
+        [x, y, z] => Ok(Vector3::new(x, y, z)),
+        _ => Err(invalid()),
+    }
+}
+
+/// Parse a fraction between 0 and 1, both included.
+fn parse_ratio(value: &str) -> Result<f64, CliError> {
+    match value.parse::<f64>() {
+        Ok(ratio) if (0.0..=1.0).contains(&ratio) => Ok(ratio),
+        _ => Err(CliError::Usage(format!("invalid ratio '{}', expected a number between 0 and 1", value))),
+    }
+}
+
+/// Parse a strictly positive tolerance.
+fn parse_epsilon(value: &str) -> Result<f64, CliError> {
+    match value.parse::<f64>() {
+        Ok(epsilon) if epsilon > 0.0 => Ok(epsilon),
+        _ => Err(CliError::Usage(format!("invalid tolerance '{}', expected a positive number", value))),
+    }
+}
@@ -0,0 +1,121 @@
+//! Connected components, for files holding several disjoint surfaces.
+//!
+//! A single centroid lies between the surfaces rather than inside any of them, so the centroid
+//! approach of [`crate::reorient_mesh`] gets every component wrong. Each component is instead
+//! reoriented against a reference point of its own.
+
+use std::collections::HashMap;
+
+use nalgebra::Vector3;
+
+use crate::winding::flip_face;
+use crate::{compute_triangle_direction_with, Mesh, NormalBackend};
+
+/// Find the representative of `i` in the union-find forest, compressing the path on the way.
+fn find_root(parents: &mut [usize], mut i: usize) -> usize {
+    while parents[i] != i {
+        parents[i] = parents[parents[i]];
+        i = parents[i];
+    }
+    i
+}
+
+#[test]
+fn test_split_components() {
+    let mesh = crate::parse_input("tests/two_tetrahedra.txt").unwrap();
+    assert_eq!(split_components(&mesh), vec![vec![0, 1, 2, 3], vec![4, 5, 6, 7]]);
+    assert_eq!(split_components(&crate::parse_input("tests/input.txt").unwrap()).len(), 1);
+}
+
+/// Group the triangles into connected components, two triangles being connected when they share a vertex.
+/// Each component lists its triangle indices in increasing order, and the components are ordered by their first triangle.
+pub fn split_components(mesh: &Mesh) -> Vec<Vec<usize>> {
+    let mut parents: Vec<usize> = (0..mesh.vertices.len()).collect();
+    for triangle in &mesh.triangles {
+        let root = find_root(&mut parents, triangle[0]);
+        for &i in &triangle[1..] {
+            let other = find_root(&mut parents, i);
+            parents[other] = root;
+        }
+    }
+
+    let mut component_of_root: HashMap<usize, usize> = HashMap::new();
+    let mut components: Vec<Vec<usize>> = Vec::new();
+    for (t, triangle) in mesh.triangles.iter().enumerate() {
+        let root = find_root(&mut parents, triangle[0]);
+        let component = *component_of_root.entry(root).or_insert_with(|| {
+            components.push(Vec::new());
+            components.len() - 1
+        });
+        components[component].push(t);
+    }
+    components
+}
+
+/// The mesh made of the given triangles only, keeping just the points they use, in their original order.
+pub fn component_mesh(mesh: &Mesh, triangles: &[usize]) -> Mesh {
+    let mut used = vec![false; mesh.vertices.len()];
+    for &t in triangles {
+        for &i in &mesh.triangles[t] {
+            used[i] = true;
+        }
+    }
+    let mut new_index = vec![usize::MAX; mesh.vertices.len()];
+    let mut vertices: Vec<Vector3<f64>> = Vec::new();
+    let mut colors: Vec<[u8; 3]> = Vec::new();
+    for i in (0..mesh.vertices.len()).filter(|&i| used[i]) {
+        new_index[i] = vertices.len();
+        vertices.push(mesh.vertices[i]);
+        if let Some(mesh_colors) = &mesh.colors {
+            colors.push(mesh_colors[i]);
+        }
+    }
+    Mesh {
+        vertices,
+        triangles: triangles.iter().map(|&t| mesh.triangles[t].map(|i| new_index[i])).collect(),
+        colors: mesh.colors.is_some().then_some(colors),
+        groups: mesh.groups.as_ref().map(|groups| triangles.iter().map(|&t| groups[t].clone()).collect()),
+    }
+}
+
+#[test]
+fn test_reorient_components() {
+    let mut mesh = crate::parse_input("tests/two_tetrahedra.txt").unwrap();
+    let outward = vec![[0, 1, 2], [0, 2, 3], [0, 3, 1], [1, 3, 2], [4, 5, 6], [4, 6, 7], [4, 7, 5], [5, 7, 6]];
+
+    // the centroid of all the points lies between the tetrahedra and outside both
+    let mut single = mesh.clone();
+    crate::reorient_mesh(&mut single);
+    assert_ne!(single.triangles, outward);
+
+    assert_eq!(reorient_components(&mut mesh, NormalBackend::CrossProduct, crate::compute_centroid), 2);
+    assert_eq!(mesh.triangles, outward);
+}
+
+/// Indices, in increasing order, of the triangles whose normal, computed with `backend`, does not point away from
+/// the reference point of their component, given by `reference` for the mesh of each component, see [`component_mesh`].
+pub fn find_misoriented_by_component(mesh: &Mesh, backend: NormalBackend, reference: impl Fn(&Mesh) -> Vector3<f64>) -> Vec<usize> {
+    misoriented_by_component(mesh, backend, reference).0
+}
+
+/// Reorient every connected component against its own reference point, see [`find_misoriented_by_component`].
+/// Returns the number of components found.
+pub fn reorient_components(mesh: &mut Mesh, backend: NormalBackend, reference: impl Fn(&Mesh) -> Vector3<f64>) -> usize {
+    let (misoriented, n_components) = misoriented_by_component(mesh, backend, reference);
+    for t in misoriented {
+        flip_face(&mut mesh.triangles[t]);
+    }
+    n_components
+}
+
+/// The misoriented triangles of [`find_misoriented_by_component`] together with the number of components.
+fn misoriented_by_component(mesh: &Mesh, backend: NormalBackend, reference: impl Fn(&Mesh) -> Vector3<f64>) -> (Vec<usize>, usize) {
+    let components = split_components(mesh);
+    let mut misoriented: Vec<usize> = Vec::new();
+    for component in &components {
+        let point = reference(&component_mesh(mesh, component));
+        misoriented.extend(component.iter().filter(|&&t| !compute_triangle_direction_with(&mesh.vertices, &mesh.triangles[t], &point, backend)));
+    }
+    misoriented.sort_unstable();
+    (misoriented, components.len())
+}
@@ -0,0 +1,123 @@
+//! Reading and writing mesh files in the format given by their extension, or picked explicitly.
+//!
+//! These are the readers and writers behind the binary, so that converting between formats needs
+//! nothing else, see [`convert_mesh`].
+
+use nalgebra::Vector3;
+
+use crate::{
+    parse_bin, parse_input_with, parse_json, parse_json_with, parse_obj, parse_obj_polygons, parse_off, parse_off_polygons, parse_ply, parse_ply_polygons,
+    parse_polygon_input, parse_polygon_reader, parse_reader_with, parse_stl, weld_vertices, write_bin, write_json, write_obj, write_obj_polygons,
+    write_obj_with_normals, write_off, write_off_polygons, write_output_with_base, write_ply, write_ply_polygons, write_ply_with_normals,
+    write_polygons, write_stl, Mesh, ParseError, ParseOptions, PolygonMesh, PrecisionSpec,
+};
+
+#[test]
+fn test_convert_mesh() {
+    let out_path = std::env::temp_dir().join("mesh_reorienter_convert_cube.ply");
+    let out_path = out_path.to_str().unwrap();
+
+    assert_eq!(convert_mesh("tests/cube.obj", out_path, None, PrecisionSpec::uniform(17)), Ok(0));
+    let mesh = parse_obj("tests/cube.obj").unwrap();
+    let converted = parse_ply(out_path).unwrap();
+    assert_eq!(converted.vertices, mesh.vertices);
+    assert_eq!(converted.triangles, mesh.triangles);
+}
+
+/// Convert a mesh file to the format of `out_path`, both picked from their extensions like [`read_mesh`] and
+/// [`write_mesh`], without any orientation analysis: the triangles keep their winding. With `weld`, vertices
+/// closer than that distance are merged first, see [`weld_vertices`]. Returns the number of merged vertices.
+pub fn convert_mesh(in_path: &str, out_path: &str, weld: Option<f64>, precision: PrecisionSpec) -> Result<usize, ParseError> {
+    let mut mesh = read_mesh(in_path, None, &ParseOptions::default())?;
+    let merged = weld.map_or(0, |epsilon| weld_vertices(&mut mesh, epsilon));
+    write_mesh(out_path, &mesh, precision, 0, None);
+    Ok(merged)
+}
+
+/// Read a mesh, picking the parser from `format` or, if it is not given, from the extension of the input file.
+/// Files without a recognised extension, and standard input given as `-`, are read in the native text format,
+/// with `options`.
+pub fn read_mesh(in_path: &str, format: Option<&str>, options: &ParseOptions) -> Result<Mesh, ParseError> {
+    if in_path == "-" {
+        return match format {
+            None | Some("txt") => parse_reader_with(std::io::stdin().lock(), options),
+            Some(format) => Err(ParseError::Io(format!("reading {} from standard input is not supported", format))),
+        };
+    }
+    match format.map(str::to_string).or_else(|| extension(in_path)).as_deref() {
+        Some("obj") => parse_obj(in_path),
+        Some("off") => parse_off(in_path),
+        Some("ply") => parse_ply(in_path),
+        Some("stl") => parse_stl(in_path),
+        Some("bin") => parse_bin(in_path),
+        Some("json") => parse_json_with(in_path, options),
+        _ => parse_input_with(in_path, options),
+    }
+}
+
+/// Write a mesh, picking the writer from `format` or, if it is not given, from the extension of the output file.
+/// Files without a recognised extension, and standard output given as `-`, are written in the native text format.
+pub fn write_mesh(out_path: &str, mesh: &Mesh, precision: PrecisionSpec, index_base: usize, format: Option<&str>) {
+    match format.map(str::to_string).or_else(|| extension(out_path)).as_deref() {
+        Some("obj") => write_obj(out_path, mesh, precision),
+        Some("off") => write_off(out_path, mesh, precision),
+        Some("ply") => write_ply(out_path, mesh, precision),
+        Some("stl") => write_stl(out_path, mesh, precision),
+        Some("bin") => write_bin(out_path, mesh),
+        Some("json") => write_json(out_path, mesh, precision),
+        _ => write_output_with_base(out_path, mesh, precision, index_base),
+    }
+}
+
+/// Write a mesh like [`write_mesh`], storing the normal of every point from `normals` the way the format does:
+/// as `vn` lines for OBJ, see [`write_obj_with_normals`], and as vertex properties for PLY, see [`write_ply_with_normals`].
+/// The other formats hold no normals, so they are written by [`write_mesh`] without them.
+pub fn write_mesh_with_normals(out_path: &str, mesh: &Mesh, normals: &[Vector3<f64>], precision: PrecisionSpec, format: Option<&str>) {
+    match format.map(str::to_string).or_else(|| extension(out_path)).as_deref() {
+        Some("obj") => write_obj_with_normals(out_path, mesh, normals, precision),
+        Some("ply") => write_ply_with_normals(out_path, mesh, normals, precision),
+        _ => write_mesh(out_path, mesh, precision, 0, format),
+    }
+}
+
+/// Read a polygon mesh like [`read_mesh`]. STL and binary files only hold triangles, which are read as three-vertex faces.
+pub fn read_polygon_mesh(in_path: &str, format: Option<&str>) -> Result<PolygonMesh, ParseError> {
+    if in_path == "-" {
+        return match format {
+            None | Some("txt") => parse_polygon_reader(std::io::stdin().lock()),
+            Some(format) => Err(ParseError::Io(format!("reading {} from standard input is not supported", format))),
+        };
+    }
+    match format.map(str::to_string).or_else(|| extension(in_path)).as_deref() {
+        Some("obj") => parse_obj_polygons(in_path),
+        Some("off") => parse_off_polygons(in_path),
+        Some("ply") => parse_ply_polygons(in_path),
+        Some("stl") => parse_stl(in_path).map(PolygonMesh::from),
+        Some("bin") => parse_bin(in_path).map(PolygonMesh::from),
+        Some("json") => parse_json(in_path).map(PolygonMesh::from),
+        _ => parse_polygon_input(in_path),
+    }
+}
+
+/// Write a polygon mesh like [`write_mesh`]. STL, binary and JSON files only hold triangles, which the faces are split into.
+pub fn write_polygon_mesh(out_path: &str, mesh: &PolygonMesh, precision: PrecisionSpec, format: Option<&str>) {
+    match format.map(str::to_string).or_else(|| extension(out_path)).as_deref() {
+        Some("obj") => write_obj_polygons(out_path, mesh, precision),
+        Some("off") => write_off_polygons(out_path, mesh, precision),
+        Some("ply") => write_ply_polygons(out_path, mesh, precision),
+        Some("stl") => write_stl(out_path, &mesh.triangulate(), precision),
+        Some("bin") => write_bin(out_path, &mesh.triangulate()),
+        Some("json") => write_json(out_path, &mesh.triangulate(), precision),
+        _ => write_polygons(out_path, mesh, precision),
+    }
+}
+
+/// Lowercased extension of a path, if it has one. For a gzip-compressed file, the extension before `.gz` is returned.
+pub fn extension(path: &str) -> Option<String> {
+    let path = std::path::Path::new(path);
+    let path = match path.extension() {
+        Some(ext) if ext.eq_ignore_ascii_case("gz") => std::path::Path::new(path.file_stem()?),
+        _ => path,
+    };
+    path.extension().map(|ext| ext.to_string_lossy().to_lowercase())
+}
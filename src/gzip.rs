@@ -0,0 +1,547 @@
+//! Gzip support, so that `mesh.txt.gz` can be read and written anywhere `mesh.txt` can.
+//!
+//! This is a small implementation of the gzip container (RFC 1952) and of DEFLATE (RFC 1951).
+//! Decoding covers stored, fixed Huffman and dynamic Huffman blocks. Encoding writes fixed Huffman
+//! blocks with LZ77 matches, which shrinks the repetitive text of mesh files severalfold, though less
+//! than the dynamic Huffman blocks of the reference gzip implementation would.
+
+use std::io::{self, Write};
+
+/// Whether the path names a gzip-compressed file, judging by its `.gz` extension.
+pub fn is_gzip_path(path: &str) -> bool {
+    std::path::Path::new(path)
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("gz"))
+}
+
+fn invalid(reason: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, format!("invalid gzip data: {}", reason))
+}
+
+/// Compute the CRC-32 checksum used by gzip.
+pub(crate) fn crc32(data: &[u8]) -> u32 {
+    crc32_update(0, data)
+}
+
+/// Extend the CRC-32 checksum `crc` of some data with the bytes that follow it.
+fn crc32_update(crc: u32, data: &[u8]) -> u32 {
+    let mut crc = !crc;
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 { (crc >> 1) ^ 0xEDB8_8320 } else { crc >> 1 };
+        }
+    }
+    !crc
+}
+
+#[test]
+fn test_crc32() {
+    assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+    assert_eq!(crc32_update(crc32(b"1234"), b"56789"), 0xCBF4_3926);
+}
+
+#[test]
+fn test_encoder_round_trip() {
+    // large enough to span several blocks, with matches reaching back into the previous block
+    let data: Vec<u8> = (0..200_000u32).map(|i| (i % 251) as u8).collect();
+    let mut encoder = GzEncoder::new(Vec::new());
+    encoder.write_all(&data[..1000]).unwrap();
+    encoder.write_all(&data[1000..]).unwrap();
+    let compressed = encoder.finish().unwrap();
+    assert_eq!(decompress(&compressed).unwrap(), data);
+
+    assert_eq!(decompress(&GzEncoder::new(Vec::new()).finish().unwrap()).unwrap(), Vec::<u8>::new());
+
+    // bytes without repetitions survive too, and the text of a mesh shrinks
+    let noise: Vec<u8> = (0..5000u32).map(|i| (i.wrapping_mul(2_654_435_761) >> 13) as u8).collect();
+    let mut encoder = GzEncoder::new(Vec::new());
+    encoder.write_all(&noise).unwrap();
+    assert_eq!(decompress(&encoder.finish().unwrap()).unwrap(), noise);
+    let torus = std::fs::read("tests/torus.obj").unwrap();
+    let mut encoder = GzEncoder::new(Vec::new());
+    encoder.write_all(&torus).unwrap();
+    let compressed = encoder.finish().unwrap();
+    assert!(compressed.len() < torus.len() / 2, "{} of {} bytes", compressed.len(), torus.len());
+    assert_eq!(decompress(&compressed).unwrap(), torus);
+}
+
+/// Number of input bytes compressed into every block but the last.
+const BLOCK_SIZE: usize = 1 << 16;
+/// Farthest distance a DEFLATE match may reach back, and the history kept between blocks for it.
+const WINDOW_SIZE: usize = 1 << 15;
+/// Longest DEFLATE match.
+const MAX_MATCH: usize = 258;
+/// Shortest DEFLATE match, and the length of the prefixes the match finder hashes.
+const MIN_MATCH: usize = 3;
+/// Number of earlier positions with the same prefix that the match finder tries, trading speed for size.
+const MAX_CHAIN: usize = 64;
+/// Number of buckets of the prefix hash.
+const HASH_SIZE: usize = 1 << 15;
+
+/// Writes gzip-compressed data to an inner writer.
+/// [`GzEncoder::finish`] must be called once all data has been written, to write the last block and the trailer.
+pub struct GzEncoder<W: Write> {
+    inner: W,
+    /// The last [`WINDOW_SIZE`] bytes already compressed, followed by the bytes waiting to be compressed.
+    buffer: Vec<u8>,
+    /// Where the waiting bytes start in `buffer`.
+    pending: usize,
+    bits: BitWriter,
+    header_written: bool,
+    crc: u32,
+    size: u32,
+}
+
+impl<W: Write> GzEncoder<W> {
+    pub fn new(inner: W) -> Self {
+        GzEncoder { inner, buffer: Vec::with_capacity(WINDOW_SIZE + BLOCK_SIZE), pending: 0, bits: BitWriter::default(), header_written: false, crc: 0, size: 0 }
+    }
+
+    /// Write the buffered data as a final block followed by the gzip trailer, and return the inner writer.
+    pub fn finish(mut self) -> io::Result<W> {
+        self.write_block(true)?;
+        self.inner.write_all(&self.crc.to_le_bytes())?;
+        self.inner.write_all(&self.size.to_le_bytes())?;
+        self.inner.flush()?;
+        Ok(self.inner)
+    }
+
+    /// Compress the waiting bytes into one fixed Huffman block, preceded by the gzip header for the first block,
+    /// and keep the last [`WINDOW_SIZE`] bytes for the matches of the next block.
+    fn write_block(&mut self, last: bool) -> io::Result<()> {
+        if !self.header_written {
+            // no flags, no modification time, unknown operating system
+            self.inner.write_all(&[0x1f, 0x8b, 8, 0, 0, 0, 0, 0, 0, 255])?;
+            self.header_written = true;
+        }
+        self.bits.put(last as u32, 1);
+        self.bits.put(1, 2);
+        deflate_fixed(&self.buffer, self.pending, &mut self.bits);
+        put_literal(&mut self.bits, 256);
+        if last {
+            self.bits.align();
+        }
+        self.inner.write_all(&self.bits.out)?;
+        self.bits.out.clear();
+        self.buffer.drain(..self.buffer.len().saturating_sub(WINDOW_SIZE));
+        self.pending = self.buffer.len();
+        Ok(())
+    }
+}
+
+impl<W: Write> Write for GzEncoder<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = buf.len().min(BLOCK_SIZE - (self.buffer.len() - self.pending));
+        self.buffer.extend_from_slice(&buf[..n]);
+        self.crc = crc32_update(self.crc, &buf[..n]);
+        self.size = self.size.wrapping_add(n as u32);
+        if self.buffer.len() - self.pending == BLOCK_SIZE {
+            self.write_block(false)?;
+        }
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Collects the bits of a DEFLATE stream into bytes, least significant bit of every byte first.
+#[derive(Default)]
+struct BitWriter {
+    out: Vec<u8>,
+    bits: u64,
+    n_bits: u32,
+}
+
+impl BitWriter {
+    /// Append the `n` low bits of `value`, lowest first, as DEFLATE stores extra bits and block headers.
+    fn put(&mut self, value: u32, n: u32) {
+        self.bits |= (value as u64) << self.n_bits;
+        self.n_bits += n;
+        while self.n_bits >= 8 {
+            self.out.push(self.bits as u8);
+            self.bits >>= 8;
+            self.n_bits -= 8;
+        }
+    }
+
+    /// Append a Huffman code of `len` bits, which DEFLATE stores highest bit first.
+    fn put_code(&mut self, code: u32, len: u32) {
+        self.put(code.reverse_bits() >> (32 - len), len);
+    }
+
+    /// Pad the last byte with zero bits.
+    fn align(&mut self) {
+        if self.n_bits > 0 {
+            self.put(0, 8 - self.n_bits);
+        }
+    }
+}
+
+/// Write a literal/length symbol with the fixed Huffman code of RFC 1951, section 3.2.6.
+fn put_literal(bits: &mut BitWriter, symbol: u32) {
+    match symbol {
+        0..=143 => bits.put_code(0x30 + symbol, 8),
+        144..=255 => bits.put_code(0x190 + symbol - 144, 9),
+        256..=279 => bits.put_code(symbol - 256, 7),
+        _ => bits.put_code(0xC0 + symbol - 280, 8),
+    }
+}
+
+/// The earlier positions of every prefix of [`MIN_MATCH`] bytes, most recent first: `head` holds the latest
+/// position of every hash bucket and `prev` the position before every position in the same bucket.
+struct HashChain {
+    head: Vec<usize>,
+    prev: Vec<usize>,
+}
+
+impl HashChain {
+    const NONE: usize = usize::MAX;
+
+    fn new(len: usize) -> Self {
+        HashChain { head: vec![Self::NONE; HASH_SIZE], prev: vec![Self::NONE; len] }
+    }
+
+    /// The hash bucket of the prefix starting at `i`.
+    fn bucket(data: &[u8], i: usize) -> usize {
+        ((data[i] as usize) << 10 ^ (data[i + 1] as usize) << 5 ^ data[i + 2] as usize) & (HASH_SIZE - 1)
+    }
+
+    /// Record the prefix starting at `i`, if `data` holds a whole prefix there.
+    fn insert(&mut self, data: &[u8], i: usize) {
+        if i + MIN_MATCH <= data.len() {
+            let h = Self::bucket(data, i);
+            self.prev[i] = self.head[h];
+            self.head[h] = i;
+        }
+    }
+
+    /// The longest match for `data[i..]` among the first [`MAX_CHAIN`] earlier positions of its prefix within
+    /// [`WINDOW_SIZE`], as its length and distance, or a length of 0.
+    fn longest_match(&self, data: &[u8], i: usize) -> (usize, usize) {
+        let (mut best_len, mut best_distance) = (0, 0);
+        if i + MIN_MATCH > data.len() {
+            return (best_len, best_distance);
+        }
+        let max_len = (data.len() - i).min(MAX_MATCH);
+        let mut candidate = self.head[Self::bucket(data, i)];
+        for _ in 0..MAX_CHAIN {
+            if candidate == Self::NONE || i - candidate > WINDOW_SIZE {
+                break;
+            }
+            let len = data[candidate..].iter().zip(&data[i..i + max_len]).take_while(|(a, b)| a == b).count();
+            if len > best_len {
+                (best_len, best_distance) = (len, i - candidate);
+                if len == max_len {
+                    break;
+                }
+            }
+            candidate = self.prev[candidate];
+        }
+        (best_len, best_distance)
+    }
+}
+
+/// Compress `data[start..]` into the symbols of a fixed Huffman block, without its header and end-of-block symbol.
+/// Matches are found with a hash chain over the prefixes of [`MIN_MATCH`] bytes, and may reach back into the
+/// history in `data[..start]`. Every match is the longest among the first [`MAX_CHAIN`] candidates, taken greedily.
+fn deflate_fixed(data: &[u8], start: usize, bits: &mut BitWriter) {
+    let mut chain = HashChain::new(data.len());
+    for i in 0..start {
+        chain.insert(data, i);
+    }
+    let mut i = start;
+    while i < data.len() {
+        let (len, distance) = chain.longest_match(data, i);
+        if len >= MIN_MATCH {
+            let k = LENGTH_BASE.iter().rposition(|&base| base as usize <= len).unwrap();
+            put_literal(bits, 257 + k as u32);
+            bits.put((len - LENGTH_BASE[k] as usize) as u32, LENGTH_EXTRA[k] as u32);
+            let d = DISTANCE_BASE.iter().rposition(|&base| base as usize <= distance).unwrap();
+            bits.put_code(d as u32, 5);
+            bits.put((distance - DISTANCE_BASE[d] as usize) as u32, DISTANCE_EXTRA[d] as u32);
+            for k in i..i + len {
+                chain.insert(data, k);
+            }
+            i += len;
+        } else {
+            put_literal(bits, data[i] as u32);
+            chain.insert(data, i);
+            i += 1;
+        }
+    }
+}
+
+#[test]
+fn test_decompress() {
+    // written by the reference gzip implementation, with a dynamic Huffman block
+    let compressed = std::fs::read("tests/torus.obj.gz").unwrap();
+    assert_eq!(decompress(&compressed).unwrap(), std::fs::read("tests/torus.obj").unwrap());
+
+    let mut corrupted = compressed.clone();
+    let n = corrupted.len();
+    corrupted[n - 5] ^= 1;
+    assert!(decompress(&corrupted).is_err());
+
+    // a second member made of a single match, copying the 3 bytes before it, which belong to the first member
+    let mut encoder = GzEncoder::new(Vec::new());
+    encoder.write_all(b"abc").unwrap();
+    let mut members = encoder.finish().unwrap();
+    let mut bits = BitWriter::default();
+    bits.put(1, 1);
+    bits.put(1, 2);
+    put_literal(&mut bits, 257);
+    bits.put_code(2, 5);
+    put_literal(&mut bits, 256);
+    bits.align();
+    members.extend([0x1f, 0x8b, 8, 0, 0, 0, 0, 0, 0, 255]);
+    members.extend(&bits.out);
+    members.extend(crc32(b"abc").to_le_bytes());
+    members.extend(3u32.to_le_bytes());
+    assert!(decompress(&members).is_err());
+}
+
+/// Decompress a gzip file held in memory. Concatenated gzip members are decompressed one after the other.
+/// The checksum and length stored at the end of every member are verified.
+pub fn decompress(data: &[u8]) -> io::Result<Vec<u8>> {
+    let mut out: Vec<u8> = Vec::new();
+    let mut pos = 0;
+    while pos < data.len() {
+        let start = out.len();
+        pos = skip_header(data, pos)?;
+        let mut bits = BitReader { data, pos, bit: 0 };
+        inflate(&mut bits, &mut out, start)?;
+        pos = bits.byte_aligned_pos();
+
+        let trailer = data.get(pos..pos + 8).ok_or_else(|| invalid("missing trailer"))?;
+        let crc = u32::from_le_bytes([trailer[0], trailer[1], trailer[2], trailer[3]]);
+        let size = u32::from_le_bytes([trailer[4], trailer[5], trailer[6], trailer[7]]);
+        if crc != crc32(&out[start..]) {
+            return Err(invalid("checksum mismatch"));
+        }
+        if size != (out.len() - start) as u32 {
+            return Err(invalid("length mismatch"));
+        }
+        pos += 8;
+    }
+    Ok(out)
+}
+
+/// Skip the header of the gzip member starting at `pos`, returning the position of the compressed data.
+fn skip_header(data: &[u8], pos: usize) -> io::Result<usize> {
+    const FHCRC: u8 = 2;
+    const FEXTRA: u8 = 4;
+    const FNAME: u8 = 8;
+    const FCOMMENT: u8 = 16;
+
+    let header = data.get(pos..pos + 10).ok_or_else(|| invalid("truncated header"))?;
+    if header[0] != 0x1f || header[1] != 0x8b {
+        return Err(invalid("missing magic bytes"));
+    }
+    if header[2] != 8 {
+        return Err(invalid("unsupported compression method"));
+    }
+    let flags = header[3];
+    let mut pos = pos + 10;
+    if flags & FEXTRA != 0 {
+        let len = data.get(pos..pos + 2).ok_or_else(|| invalid("truncated header"))?;
+        pos += 2 + u16::from_le_bytes([len[0], len[1]]) as usize;
+    }
+    for flag in [FNAME, FCOMMENT] {
+        if flags & flag != 0 {
+            let end = data.get(pos..).and_then(|rest| rest.iter().position(|&b| b == 0)).ok_or_else(|| invalid("truncated header"))?;
+            pos += end + 1;
+        }
+    }
+    if flags & FHCRC != 0 {
+        pos += 2;
+    }
+    if pos > data.len() {
+        return Err(invalid("truncated header"));
+    }
+    Ok(pos)
+}
+
+/// Reads the bits of a DEFLATE stream, least significant bit of every byte first.
+struct BitReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+    bit: u32,
+}
+
+impl BitReader<'_> {
+    fn bits(&mut self, n: u32) -> io::Result<u32> {
+        let mut value = 0;
+        for k in 0..n {
+            let byte = *self.data.get(self.pos).ok_or_else(|| invalid("unexpected end of data"))?;
+            value |= ((byte as u32 >> self.bit) & 1) << k;
+            self.bit += 1;
+            if self.bit == 8 {
+                self.bit = 0;
+                self.pos += 1;
+            }
+        }
+        Ok(value)
+    }
+
+    /// Drop the rest of the current byte and return the position of the next one.
+    fn byte_aligned_pos(&mut self) -> usize {
+        if self.bit != 0 {
+            self.bit = 0;
+            self.pos += 1;
+        }
+        self.pos
+    }
+}
+
+/// A canonical Huffman code, stored as the number of codes of every length and the symbols in code order.
+struct Huffman {
+    counts: [u16; 16],
+    symbols: Vec<u16>,
+}
+
+impl Huffman {
+    fn new(lengths: &[u8]) -> Self {
+        let mut counts = [0u16; 16];
+        for &len in lengths {
+            counts[len as usize] += 1;
+        }
+        counts[0] = 0;
+        let mut offsets = [0u16; 16];
+        for len in 1..15 {
+            offsets[len + 1] = offsets[len] + counts[len];
+        }
+        let mut symbols = vec![0; lengths.len()];
+        for (symbol, &len) in lengths.iter().enumerate() {
+            if len != 0 {
+                symbols[offsets[len as usize] as usize] = symbol as u16;
+                offsets[len as usize] += 1;
+            }
+        }
+        Huffman { counts, symbols }
+    }
+
+    fn decode(&self, bits: &mut BitReader) -> io::Result<u16> {
+        // codes of every length follow the last code of the previous length, so walk the lengths one bit at a time
+        let (mut code, mut first, mut index) = (0i32, 0i32, 0i32);
+        for len in 1..16 {
+            code |= bits.bits(1)? as i32;
+            let count = self.counts[len] as i32;
+            if code - first < count {
+                return Ok(self.symbols[(index + code - first) as usize]);
+            }
+            index += count;
+            first = (first + count) << 1;
+            code <<= 1;
+        }
+        Err(invalid("bad Huffman code"))
+    }
+}
+
+const LENGTH_BASE: [u16; 29] = [3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131, 163, 195, 227, 258];
+const LENGTH_EXTRA: [u8; 29] = [0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0];
+const DISTANCE_BASE: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537, 2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577,
+];
+const DISTANCE_EXTRA: [u8; 30] = [0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13, 13];
+
+/// Order in which the code lengths of the code length alphabet are stored in a dynamic block.
+const CODE_LENGTH_ORDER: [usize; 19] = [16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15];
+
+/// Decode a DEFLATE stream up to and including its final block, appending the output to `out`.
+/// The stream starts at `out[start]`: its matches cannot reach the output of earlier gzip members.
+fn inflate(bits: &mut BitReader, out: &mut Vec<u8>, start: usize) -> io::Result<()> {
+    loop {
+        let last = bits.bits(1)? == 1;
+        match bits.bits(2)? {
+            0 => {
+                let pos = bits.byte_aligned_pos();
+                let header = bits.data.get(pos..pos + 4).ok_or_else(|| invalid("truncated stored block"))?;
+                let len = u16::from_le_bytes([header[0], header[1]]);
+                if len != !u16::from_le_bytes([header[2], header[3]]) {
+                    return Err(invalid("stored block length mismatch"));
+                }
+                let block = bits.data.get(pos + 4..pos + 4 + len as usize).ok_or_else(|| invalid("truncated stored block"))?;
+                out.extend_from_slice(block);
+                bits.pos = pos + 4 + len as usize;
+            }
+            1 => {
+                let mut lengths = [0u8; 288];
+                lengths[..144].fill(8);
+                lengths[144..256].fill(9);
+                lengths[256..280].fill(7);
+                lengths[280..].fill(8);
+                inflate_block(bits, out, start, &Huffman::new(&lengths), &Huffman::new(&[5; 30]))?;
+            }
+            2 => {
+                let (literals, distances) = read_dynamic_codes(bits)?;
+                inflate_block(bits, out, start, &literals, &distances)?;
+            }
+            _ => return Err(invalid("reserved block type")),
+        }
+        if last {
+            return Ok(());
+        }
+    }
+}
+
+/// Read the literal/length and distance codes at the start of a dynamic Huffman block.
+fn read_dynamic_codes(bits: &mut BitReader) -> io::Result<(Huffman, Huffman)> {
+    let n_literals = bits.bits(5)? as usize + 257;
+    let n_distances = bits.bits(5)? as usize + 1;
+    let n_code_lengths = bits.bits(4)? as usize + 4;
+
+    let mut code_lengths = [0u8; 19];
+    for &symbol in &CODE_LENGTH_ORDER[..n_code_lengths] {
+        code_lengths[symbol] = bits.bits(3)? as u8;
+    }
+    let code_length_code = Huffman::new(&code_lengths);
+
+    let mut lengths: Vec<u8> = Vec::with_capacity(n_literals + n_distances);
+    while lengths.len() < n_literals + n_distances {
+        let (value, repeat) = match code_length_code.decode(bits)? {
+            symbol @ 0..=15 => (symbol as u8, 1),
+            16 => (*lengths.last().ok_or_else(|| invalid("repeated length without a previous one"))?, 3 + bits.bits(2)?),
+            17 => (0, 3 + bits.bits(3)?),
+            _ => (0, 11 + bits.bits(7)?),
+        };
+        lengths.extend(std::iter::repeat_n(value, repeat as usize));
+    }
+    if lengths.len() > n_literals + n_distances {
+        return Err(invalid("code lengths overflow"));
+    }
+    Ok((Huffman::new(&lengths[..n_literals]), Huffman::new(&lengths[n_literals..])))
+}
+
+/// Decode the symbols of a Huffman block up to its end-of-block symbol, for a stream starting at `out[start]`.
+fn inflate_block(bits: &mut BitReader, out: &mut Vec<u8>, start: usize, literals: &Huffman, distances: &Huffman) -> io::Result<()> {
+    loop {
+        let symbol = literals.decode(bits)? as usize;
+        match symbol {
+            0..=255 => out.push(symbol as u8),
+            256 => return Ok(()),
+            _ => {
+                let k = symbol - 257;
+                if k >= LENGTH_BASE.len() {
+                    return Err(invalid("bad length symbol"));
+                }
+                let len = LENGTH_BASE[k] as usize + bits.bits(LENGTH_EXTRA[k] as u32)? as usize;
+                let d = distances.decode(bits)? as usize;
+                if d >= DISTANCE_BASE.len() {
+                    return Err(invalid("bad distance symbol"));
+                }
+                let distance = DISTANCE_BASE[d] as usize + bits.bits(DISTANCE_EXTRA[d] as u32)? as usize;
+                if distance > out.len() - start {
+                    return Err(invalid("distance reaches before the start of the data"));
+                }
+                // the copy may overlap the bytes it produces, so it goes one byte at a time
+                let from = out.len() - distance;
+                for k in 0..len {
+                    out.push(out[from + k]);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,274 @@
+//! A reference point inside the solid, for meshes whose centroid lies outside it.
+//!
+//! The centroid approach of [`crate::reorient_mesh`] assumes that the centroid is enclosed by the
+//! surface. For crescent or hollow shapes it is not, and a point found by stepping off a face and
+//! checking with a ray cast that it is enclosed, see [`is_inside`], makes a better reference.
+
+use nalgebra::Vector3;
+
+use crate::{analyze_edges, bounding_box, compute_centroid, Mesh};
+
+/// Directions of the rays cast by [`is_inside`], tried in turn. They are chosen away from the coordinate axes and
+/// diagonals and from each other, so that a ray grazing an edge or a vertex is followed by one that does not.
+const RAY_DIRECTIONS: [[f64; 3]; 4] = [[0.5377, 0.6142, 0.5775], [-0.4213, 0.7391, 0.5255], [0.6733, -0.3119, 0.6703], [-0.5861, -0.5484, -0.5965]];
+
+/// Barycentric distance to the border of a triangle within which a crossing ray counts as grazing an edge or a vertex,
+/// where rounding decides whether it crosses one, both or neither of the triangles sharing them.
+const GRAZING_MARGIN: f64 = 1e-9;
+
+/// Distances, relative to the bounding box diagonal, stepped off a face when looking for an interior point.
+const INTERIOR_STEPS: [f64; 3] = [1e-3, 1e-5, 1e-7];
+
+/// Number of triangles, spread evenly over the mesh, that [`pick_interior_point`] steps off before giving up.
+/// Every attempt casts rays against the whole mesh, so trying every triangle of an open surface would take
+/// quadratic time.
+const INTERIOR_ATTEMPTS: usize = 16;
+
+#[test]
+fn test_pick_interior_point_crescent() {
+    let mesh = crate::parse_obj("tests/crescent.obj").unwrap();
+    assert!(!is_inside(&mesh, &compute_centroid(&mesh)));
+    assert!(is_inside(&mesh, &pick_interior_point(&mesh)));
+
+    // an open grid encloses no point, so the search does not even start
+    let n = 100;
+    let mut grid = Mesh { vertices: (0..=n).flat_map(|i| (0..=n).map(move |j| Vector3::new(i as f64, j as f64, 0.0))).collect(), ..Mesh::default() };
+    for i in 0..n {
+        for j in 0..n {
+            let corner = i * (n + 1) + j;
+            grid.triangles.extend([[corner, corner + n + 1, corner + n + 2], [corner, corner + n + 2, corner + 1]]);
+        }
+    }
+    assert_eq!(pick_interior_point(&grid), compute_centroid(&grid));
+}
+
+/// Find a point inside the volume enclosed by the mesh.
+/// A surface with boundary edges, see [`analyze_edges`], encloses no volume, and the parity of a ray cast from
+/// either side of it means nothing, so the vertex centroid is returned for it straight away. Otherwise, for
+/// up to 16 triangles spread over the mesh, points a small distance off the triangle centroid along
+/// both directions of its normal are checked with [`is_inside`], since the side the normal points to is not known yet.
+/// If no such point is inside, the vertex centroid is returned as a fallback too, which the caller can tell apart
+/// with [`is_inside`].
+pub fn pick_interior_point(mesh: &Mesh) -> Vector3<f64> {
+    let Some((min, max)) = bounding_box(mesh) else {
+        return compute_centroid(mesh);
+    };
+    if !analyze_edges(mesh).boundary_edges.is_empty() {
+        return compute_centroid(mesh);
+    }
+    let diagonal = (max - min).norm();
+    let stride = mesh.triangles.len().div_ceil(INTERIOR_ATTEMPTS).max(1);
+    for triangle in mesh.triangles.iter().step_by(stride) {
+        let [v0, v1, v2] = triangle.map(|i| mesh.vertices[i]);
+        let Some(normal) = (v1 - v0).cross(&(v2 - v0)).try_normalize(0.0) else {
+            continue;
+        };
+        let center = (v0 + v1 + v2) / 3.0;
+        for step in INTERIOR_STEPS {
+            for candidate in [center - normal * step * diagonal, center + normal * step * diagonal] {
+                if is_inside(mesh, &candidate) {
+                    return candidate;
+                }
+            }
+        }
+    }
+    compute_centroid(mesh)
+}
+
+#[test]
+fn test_closest_point_on_mesh() {
+    let mesh = crate::parse_input("tests/input.txt").unwrap();
+    // off the slanted face x + y + z = 1, the projection is its center
+    let (point, t) = closest_point_on_mesh(&mesh, &Vector3::new(1.0, 1.0, 1.0)).unwrap();
+    assert!((point - Vector3::new(1.0, 1.0, 1.0) / 3.0).norm() < 1e-12);
+    assert_eq!(t, 3);
+    // beside the x = 0 face the projection drops the x coordinate
+    let (point, t) = closest_point_on_mesh(&mesh, &Vector3::new(-1.0, 0.2, 0.3)).unwrap();
+    assert!((point - Vector3::new(0.0, 0.2, 0.3)).norm() < 1e-12);
+    assert_eq!(t, 0);
+    // beyond a corner the nearest point is the vertex itself
+    let (point, _) = closest_point_on_mesh(&mesh, &Vector3::new(2.0, -1.0, -1.0)).unwrap();
+    assert!((point - Vector3::new(1.0, 0.0, 0.0)).norm() < 1e-12);
+    assert_eq!(closest_point_on_mesh(&Mesh::default(), &Vector3::zeros()), None);
+}
+
+/// The point of the surface nearest to `query`, together with the index of the triangle it lies on, or `None` for a
+/// mesh without triangles. Every triangle is checked in turn; of several triangles at the same distance, as around a
+/// shared edge or vertex, the first one is returned.
+pub fn closest_point_on_mesh(mesh: &Mesh, query: &Vector3<f64>) -> Option<(Vector3<f64>, usize)> {
+    mesh.triangles
+        .iter()
+        .enumerate()
+        .map(|(t, triangle)| (closest_point_on_triangle(query, triangle.map(|i| mesh.vertices[i])), t))
+        .min_by(|(a, _), (b, _)| (a - query).norm_squared().total_cmp(&(b - query).norm_squared()))
+}
+
+/// The point of the triangle nearest to `p`, found from the region of the triangle plane that `p` projects to:
+/// the region of a vertex, of an edge, or the inside of the triangle (Ericson, Real-Time Collision Detection, 5.1.5).
+fn closest_point_on_triangle(p: &Vector3<f64>, [a, b, c]: [Vector3<f64>; 3]) -> Vector3<f64> {
+    let ab = b - a;
+    let ac = c - a;
+    let ap = p - a;
+    let d1 = ab.dot(&ap);
+    let d2 = ac.dot(&ap);
+    if d1 <= 0.0 && d2 <= 0.0 {
+        return a;
+    }
+    let bp = p - b;
+    let d3 = ab.dot(&bp);
+    let d4 = ac.dot(&bp);
+    if d3 >= 0.0 && d4 <= d3 {
+        return b;
+    }
+    let vc = d1 * d4 - d3 * d2;
+    if vc <= 0.0 && d1 >= 0.0 && d3 <= 0.0 {
+        return a + ab * (d1 / (d1 - d3));
+    }
+    let cp = p - c;
+    let d5 = ab.dot(&cp);
+    let d6 = ac.dot(&cp);
+    if d6 >= 0.0 && d5 <= d6 {
+        return c;
+    }
+    let vb = d5 * d2 - d1 * d6;
+    if vb <= 0.0 && d2 >= 0.0 && d6 <= 0.0 {
+        return a + ac * (d2 / (d2 - d6));
+    }
+    let va = d3 * d6 - d5 * d4;
+    if va <= 0.0 && d4 - d3 >= 0.0 && d5 - d6 >= 0.0 {
+        return b + (c - b) * ((d4 - d3) / ((d4 - d3) + (d5 - d6)));
+    }
+    // inside the triangle
+    let denominator = 1.0 / (va + vb + vc);
+    a + ab * (vb * denominator) + ac * (vc * denominator)
+}
+
+#[test]
+fn test_is_inside() {
+    let mesh = crate::parse_input("tests/input.txt").unwrap();
+    assert!(is_inside(&mesh, &Vector3::new(0.1, 0.2, 0.3)));
+    assert!(is_inside(&mesh, &compute_centroid(&mesh)));
+    assert!(!is_inside(&mesh, &Vector3::new(0.5, 0.5, 0.5)));
+    assert!(!is_inside(&mesh, &Vector3::new(-0.1, 0.2, 0.3)));
+
+    // points whose first ray runs through the vertex (1, 0, 0), shared by three triangles, or through the middle
+    // of the edge from (1, 0, 0) to (0, 1, 0), shared by two
+    let direction = Vector3::from(RAY_DIRECTIONS[0]).normalize();
+    for target in [Vector3::new(1.0, 0.0, 0.0), Vector3::new(0.5, 0.5, 0.0)] {
+        assert!(!is_inside(&mesh, &(target - direction * 0.5)));
+    }
+
+    // the parallel test is relative to the size of the triangles, so a tiny mesh is not missed by every ray
+    let scale = 1e-9;
+    let tiny = Mesh { vertices: mesh.vertices.iter().map(|v| v * scale).collect(), ..mesh.clone() };
+    assert!(is_inside(&tiny, &(Vector3::new(0.1, 0.2, 0.3) * scale)));
+    assert!(!is_inside(&tiny, &(Vector3::new(0.5, 0.5, 0.5) * scale)));
+    #[allow(deprecated)]
+    let contained = contains_point(&tiny, &(Vector3::new(0.1, 0.2, 0.3) * scale));
+    assert!(contained);
+}
+
+/// Whether `point` lies inside the volume enclosed by the mesh, by the parity of the number of
+/// triangles a ray cast from the point crosses. The winding of the triangles does not matter.
+/// A ray that grazes an edge or a vertex may be counted as crossing any number of the triangles sharing it,
+/// so the ray is then cast again in the next direction. If every direction grazes, the last count is used.
+pub fn is_inside(mesh: &Mesh, point: &Vector3<f64>) -> bool {
+    let mut crossings = 0;
+    for direction in RAY_DIRECTIONS {
+        let direction = Vector3::from(direction).normalize();
+        crossings = 0;
+        let mut grazing = false;
+        for triangle in &mesh.triangles {
+            match ray_hit(point, &direction, triangle.map(|i| mesh.vertices[i])) {
+                RayHit::Miss => {}
+                RayHit::Crossing => crossings += 1,
+                RayHit::Grazing => {
+                    crossings += 1;
+                    grazing = true;
+                }
+            }
+        }
+        if !grazing {
+            break;
+        }
+    }
+    crossings % 2 == 1
+}
+
+/// Whether `point` lies inside the volume enclosed by the mesh, see [`is_inside`].
+#[deprecated(since = "0.1.0", note = "renamed to `is_inside`")]
+pub fn contains_point(mesh: &Mesh, point: &Vector3<f64>) -> bool {
+    is_inside(mesh, point)
+}
+
+/// How a ray meets a triangle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RayHit {
+    Miss,
+    /// The ray crosses the inside of the triangle.
+    Crossing,
+    /// The ray crosses the triangle within [`GRAZING_MARGIN`] of an edge or a vertex.
+    Grazing,
+}
+
+/// How the ray from `origin` along `direction` meets the triangle, see [`ray_plane_hit`].
+fn ray_hit(origin: &Vector3<f64>, direction: &Vector3<f64>, [v0, v1, v2]: [Vector3<f64>; 3]) -> RayHit {
+    let Some((t, u, v)) = ray_plane_hit(origin, direction, &v0, &v1, &v2) else {
+        return RayHit::Miss;
+    };
+    if u < -GRAZING_MARGIN || v < -GRAZING_MARGIN || u + v > 1.0 + GRAZING_MARGIN || t <= 0.0 {
+        return RayHit::Miss;
+    }
+    if u < GRAZING_MARGIN || v < GRAZING_MARGIN || u + v > 1.0 - GRAZING_MARGIN {
+        RayHit::Grazing
+    } else {
+        RayHit::Crossing
+    }
+}
+
+#[test]
+fn test_ray_triangle_intersect() {
+    let [v0, v1, v2] = [Vector3::new(0.0, 0.0, 0.0), Vector3::new(1.0, 0.0, 0.0), Vector3::new(0.0, 1.0, 0.0)];
+    let down = Vector3::new(0.0, 0.0, -2.0);
+    // a hit, at a parameter measured in lengths of the direction
+    assert_eq!(ray_triangle_intersect(&Vector3::new(0.25, 0.25, 3.0), &down, &v0, &v1, &v2), Some(1.5));
+    // misses beside the triangle and behind the origin
+    assert_eq!(ray_triangle_intersect(&Vector3::new(0.75, 0.75, 3.0), &down, &v0, &v1, &v2), None);
+    assert_eq!(ray_triangle_intersect(&Vector3::new(0.25, 0.25, -3.0), &down, &v0, &v1, &v2), None);
+    // a ray parallel to the triangle, whether in its plane or above it
+    let along = Vector3::new(1.0, 0.0, 0.0);
+    assert_eq!(ray_triangle_intersect(&Vector3::new(-1.0, 0.25, 1.0), &along, &v0, &v1, &v2), None);
+    assert_eq!(ray_triangle_intersect(&Vector3::new(-1.0, 0.25, 0.0), &along, &v0, &v1, &v2), None);
+    // a triangle and a ray scaled down together still meet, at the same parameter
+    let scale = 1e-9;
+    let t = ray_triangle_intersect(&(Vector3::new(0.25, 0.25, 3.0) * scale), &(down * scale), &(v0 * scale), &(v1 * scale), &(v2 * scale));
+    assert!((t.unwrap() - 1.5).abs() < 1e-9);
+}
+
+/// The parameter `t` at which the ray `origin + t * direction` crosses the triangle `v0`, `v1`, `v2`, with the
+/// Möller–Trumbore algorithm, or `None` if it misses it. Only crossings in front of the origin, with `t > 0`, count,
+/// and a ray parallel to the triangle never crosses it. With a unit `direction`, `t` is the distance to the crossing.
+/// The triangle includes its edges and vertices; the winding does not matter.
+pub fn ray_triangle_intersect(origin: &Vector3<f64>, direction: &Vector3<f64>, v0: &Vector3<f64>, v1: &Vector3<f64>, v2: &Vector3<f64>) -> Option<f64> {
+    let (t, u, v) = ray_plane_hit(origin, direction, v0, v1, v2)?;
+    (u >= 0.0 && v >= 0.0 && u + v <= 1.0 && t > 0.0).then_some(t)
+}
+
+/// Where the line `origin + t * direction` meets the plane of the triangle, as the parameter `t` and the barycentric
+/// coordinates `u` and `v` of the point relative to `v1` and `v2`, or `None` if the line is parallel to the plane.
+/// The point lies in the triangle when `u`, `v` and `1 - u - v` are all between 0 and 1. Whether the line is parallel
+/// is decided relative to the lengths of the edges and of `direction`, so that the test holds at any scale.
+fn ray_plane_hit(origin: &Vector3<f64>, direction: &Vector3<f64>, v0: &Vector3<f64>, v1: &Vector3<f64>, v2: &Vector3<f64>) -> Option<(f64, f64, f64)> {
+    let edge1 = v1 - v0;
+    let edge2 = v2 - v0;
+    let p = direction.cross(&edge2);
+    let determinant = edge1.dot(&p);
+    if determinant.abs() <= f64::EPSILON * edge1.norm() * edge2.norm() * direction.norm() {
+        return None;
+    }
+    let to_origin = origin - v0;
+    let u = to_origin.dot(&p) / determinant;
+    let q = to_origin.cross(&edge1);
+    let v = direction.dot(&q) / determinant;
+    Some((edge2.dot(&q) / determinant, u, v))
+}
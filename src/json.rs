@@ -0,0 +1,394 @@
+//! JSON support, for exchanging meshes with Python or JavaScript tools such as Three.js.
+//!
+//! A mesh is read and written as an object with a `vertices` array of `[x, y, z]` coordinate arrays and a
+//! `triangles` array of `[i, j, k]` 0-based index arrays, plus a `colors` array of `[r, g, b]`
+//! arrays when the mesh has vertex colors. Other members are ignored when reading. JSON has no number for
+//! coordinates that are not finite, so they are written as `null`, which is read back as NaN with
+//! [`ParseOptions::allow_nonfinite`].
+
+use nalgebra::Vector3;
+
+use crate::{validate_faces, Mesh, ParseError, ParseOptions, PrecisionSpec};
+
+#[test]
+fn test_parse_json() {
+    let mesh = parse_json("tests/cube.json").unwrap();
+    assert_eq!(mesh.vertices.len(), 8);
+    assert_eq!(mesh.triangles.len(), 12);
+    assert_eq!(mesh.triangles[0], [0, 3, 2]);
+    assert_eq!(mesh.vertices[6], Vector3::new(1.0, 1.0, 1.0));
+}
+
+#[test]
+fn test_json_round_trip() {
+    let out_path = std::env::temp_dir().join("mesh_reorienter_colored.json");
+    let out_path = out_path.to_str().unwrap();
+
+    let mesh = crate::parse_ply_ascii("tests/colored.ply").unwrap();
+    write_json(out_path, &mesh, PrecisionSpec::uniform(17));
+    assert_eq!(parse_json(out_path).unwrap(), mesh);
+
+    // a coordinate that is not finite comes back as NaN, with the option only
+    let out_path = std::env::temp_dir().join("mesh_reorienter_nonfinite.json");
+    let out_path = out_path.to_str().unwrap();
+    let options = ParseOptions { allow_nonfinite: true, ..ParseOptions::default() };
+    let mesh = crate::parse_input_with("tests/nonfinite.txt", &options).unwrap();
+    write_json(out_path, &mesh, PrecisionSpec::uniform(17));
+    let read = parse_json_with(out_path, &options).unwrap();
+    assert_eq!(read.triangles, mesh.triangles);
+    for (read, written) in read.vertices.iter().zip(&mesh.vertices) {
+        assert!(read.iter().zip(written.iter()).all(|(read, written)| read == written || read.is_nan() && !written.is_finite()));
+    }
+    assert!(matches!(parse_json(out_path), Err(ParseError::InvalidNumber { .. })));
+}
+
+#[test]
+fn test_parse_json_errors() {
+    let error = |contents: &str| json_from_str(contents, &ParseOptions::default()).unwrap_err().to_string();
+    assert_eq!(error("{\"vertices\": [[0, 0, 0]],\n \"triangles\": [[0, 0]]}"), "line 2: triangle 0 has 2 values, expected exactly 3");
+    assert_eq!(error("{\"vertices\": [[0, 0, 0]],\n \"triangles\": [[0, 0, -1]]}"), "line 2: invalid point index '-1'");
+    assert_eq!(error("{\"vertices\": [[0, 0, 0],\n"), "line 2: unexpected end of the document");
+    assert_eq!(error("{\"triangles\": []}"), "line 1: missing 'vertices' member");
+    assert!(matches!(json_from_str("{\"vertices\": [], \"triangles\": [[0, 1, 2]]}", &ParseOptions::default()), Err(ParseError::IndexOutOfRange { .. })));
+    // deep nesting is an error rather than a stack overflow
+    assert_eq!(json_from_str(&"[".repeat(200_000), &ParseOptions::default()), Err(ParseError::Syntax { line: 1, reason: String::from("arrays and objects are nested more than 64 levels deep") }));
+}
+
+/// Parse a JSON mesh document, see the [module documentation](self).
+pub fn parse_json(in_path: &str) -> Result<Mesh, ParseError> {
+    parse_json_with(in_path, &ParseOptions::default())
+}
+
+/// Parse a JSON mesh document like [`parse_json`]; with [`ParseOptions::allow_nonfinite`], a `null` coordinate
+/// is read as NaN. The other options only concern the native format.
+pub fn parse_json_with(in_path: &str, options: &ParseOptions) -> Result<Mesh, ParseError> {
+    json_from_str(&crate::read_input_to_string(in_path)?, options)
+}
+
+/// A parsed JSON value, with the 1-based line on which it starts.
+struct JsonValue {
+    line: usize,
+    kind: JsonKind,
+}
+
+enum JsonKind {
+    Null,
+    Bool,
+    Number(f64),
+    String,
+    Array(Vec<JsonValue>),
+    Object(Vec<(String, JsonValue)>),
+}
+
+impl JsonValue {
+    fn error(&self, reason: String) -> ParseError {
+        ParseError::Syntax { line: self.line, reason }
+    }
+
+    fn as_array(&self, what: &str) -> Result<&[JsonValue], ParseError> {
+        match &self.kind {
+            JsonKind::Array(items) => Ok(items),
+            _ => Err(self.error(format!("{} is not an array", what))),
+        }
+    }
+
+    fn as_number(&self, what: &str) -> Result<f64, ParseError> {
+        match self.kind {
+            JsonKind::Number(value) => Ok(value),
+            _ => Err(ParseError::InvalidNumber { line: self.line, reason: format!("{} is not a number", what) }),
+        }
+    }
+
+    /// Read a coordinate: a number, or `null` for NaN with [`ParseOptions::allow_nonfinite`].
+    fn as_coordinate(&self, options: &ParseOptions) -> Result<f64, ParseError> {
+        match self.kind {
+            JsonKind::Null if options.allow_nonfinite => Ok(f64::NAN),
+            _ => self.as_number("coordinate"),
+        }
+    }
+
+    /// Read a non-negative integer that fits in `T`, such as a point index or a color channel.
+    fn as_integer<T: TryFrom<u64>>(&self, what: &str) -> Result<T, ParseError> {
+        let value = self.as_number(what)?;
+        let integer = (value >= 0.0 && value.fract() == 0.0 && value <= u64::MAX as f64)
+            .then(|| T::try_from(value as u64).ok())
+            .flatten();
+        integer.ok_or_else(|| ParseError::InvalidNumber { line: self.line, reason: format!("invalid {} '{}'", what, value) })
+    }
+
+    /// Read an array of exactly three values with `read`; `what` names the array in error messages.
+    fn as_triple<T: Default + Copy>(&self, what: &str, read: impl Fn(&JsonValue) -> Result<T, ParseError>) -> Result<[T; 3], ParseError> {
+        let items = self.as_array(what)?;
+        if items.len() != 3 {
+            return Err(self.error(format!("{} has {} values, expected exactly 3", what, items.len())));
+        }
+        let mut triple = [T::default(); 3];
+        for (value, item) in triple.iter_mut().zip(items) {
+            *value = read(item)?;
+        }
+        Ok(triple)
+    }
+}
+
+/// Parse the text of a JSON mesh document.
+fn json_from_str(contents: &str, options: &ParseOptions) -> Result<Mesh, ParseError> {
+    let mut parser = JsonParser { bytes: contents.as_bytes(), offset: 0, line: 1, depth: 0 };
+    let document = parser.parse_value()?;
+    parser.skip_whitespace();
+    if parser.offset < parser.bytes.len() {
+        return Err(parser.error("unexpected text after the document"));
+    }
+
+    let JsonKind::Object(members) = &document.kind else {
+        return Err(document.error(String::from("the document is not an object")));
+    };
+    let member = |name: &str| members.iter().find(|(key, _)| key == name).map(|(_, value)| value);
+    let required = |name: &str| member(name).ok_or_else(|| document.error(format!("missing '{}' member", name)));
+
+    let vertices = required("vertices")?
+        .as_array("vertices")?
+        .iter()
+        .enumerate()
+        .map(|(i, point)| point.as_triple(&format!("point {}", i), |value| value.as_coordinate(options)).map(Vector3::from))
+        .collect::<Result<Vec<_>, _>>()?;
+    let triangles = required("triangles")?
+        .as_array("triangles")?
+        .iter()
+        .enumerate()
+        .map(|(t, triangle)| triangle.as_triple(&format!("triangle {}", t), |value| value.as_integer("point index")))
+        .collect::<Result<Vec<_>, _>>()?;
+    let colors = match member("colors") {
+        Some(colors) => {
+            let colors = colors
+                .as_array("colors")?
+                .iter()
+                .enumerate()
+                .map(|(i, color)| color.as_triple(&format!("color {}", i), |value| value.as_integer("color channel")))
+                .collect::<Result<Vec<_>, _>>()?;
+            if colors.len() != vertices.len() {
+                return Err(document.error(format!("{} colors given for {} points", colors.len(), vertices.len())));
+            }
+            Some(colors)
+        }
+        None => None,
+    };
+
+    validate_faces(vertices.len(), &triangles)?;
+    Ok(Mesh { vertices, triangles, colors, groups: None })
+}
+
+/// Deepest nesting of arrays and objects accepted, far past the 3 levels of a mesh document, so that a hostile
+/// document cannot exhaust the stack of the recursive parser.
+const MAX_JSON_DEPTH: usize = 64;
+
+/// A recursive descent parser for the JSON subset used by mesh documents: all of JSON except `\u` escapes.
+struct JsonParser<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+    line: usize,
+    /// Number of arrays and objects enclosing the value being parsed.
+    depth: usize,
+}
+
+impl JsonParser<'_> {
+    fn error(&self, reason: &str) -> ParseError {
+        if self.offset >= self.bytes.len() {
+            ParseError::UnexpectedEof { line: self.line, reason: String::from("unexpected end of the document") }
+        } else {
+            ParseError::Syntax { line: self.line, reason: String::from(reason) }
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(&byte) = self.bytes.get(self.offset) {
+            match byte {
+                b'\n' => self.line += 1,
+                b' ' | b'\t' | b'\r' => {}
+                _ => break,
+            }
+            self.offset += 1;
+        }
+    }
+
+    /// Skip whitespace and consume `byte` if it comes next.
+    fn eat(&mut self, byte: u8) -> bool {
+        self.skip_whitespace();
+        let found = self.bytes.get(self.offset) == Some(&byte);
+        if found {
+            self.offset += 1;
+        }
+        found
+    }
+
+    fn expect(&mut self, byte: u8) -> Result<(), ParseError> {
+        if self.eat(byte) {
+            Ok(())
+        } else {
+            Err(self.error(&format!("expected '{}'", byte as char)))
+        }
+    }
+
+    fn parse_value(&mut self) -> Result<JsonValue, ParseError> {
+        self.skip_whitespace();
+        let line = self.line;
+        if matches!(self.bytes.get(self.offset), Some(b'{' | b'[')) {
+            if self.depth == MAX_JSON_DEPTH {
+                return Err(ParseError::Syntax { line, reason: format!("arrays and objects are nested more than {} levels deep", MAX_JSON_DEPTH) });
+            }
+            self.depth += 1;
+        }
+        let kind = match self.bytes.get(self.offset) {
+            Some(b'{') => {
+                self.offset += 1;
+                let mut members = Vec::new();
+                if !self.eat(b'}') {
+                    loop {
+                        self.skip_whitespace();
+                        let key = self.parse_string()?;
+                        self.expect(b':')?;
+                        members.push((key, self.parse_value()?));
+                        if self.eat(b'}') {
+                            break;
+                        }
+                        self.expect(b',')?;
+                    }
+                }
+                self.depth -= 1;
+                JsonKind::Object(members)
+            }
+            Some(b'[') => {
+                self.offset += 1;
+                let mut items = Vec::new();
+                if !self.eat(b']') {
+                    loop {
+                        items.push(self.parse_value()?);
+                        if self.eat(b']') {
+                            break;
+                        }
+                        self.expect(b',')?;
+                    }
+                }
+                self.depth -= 1;
+                JsonKind::Array(items)
+            }
+            Some(b'"') => {
+                self.parse_string()?;
+                JsonKind::String
+            }
+            Some(b't') if self.bytes[self.offset..].starts_with(b"true") => {
+                self.offset += 4;
+                JsonKind::Bool
+            }
+            Some(b'f') if self.bytes[self.offset..].starts_with(b"false") => {
+                self.offset += 5;
+                JsonKind::Bool
+            }
+            Some(b'n') if self.bytes[self.offset..].starts_with(b"null") => {
+                self.offset += 4;
+                JsonKind::Null
+            }
+            Some(b'-' | b'0'..=b'9') => {
+                let start = self.offset;
+                while matches!(self.bytes.get(self.offset), Some(b'-' | b'+' | b'.' | b'e' | b'E' | b'0'..=b'9')) {
+                    self.offset += 1;
+                }
+                let token = std::str::from_utf8(&self.bytes[start..self.offset]).expect("the token is ASCII");
+                let value = token.parse::<f64>().map_err(|_| ParseError::InvalidNumber { line, reason: format!("invalid number '{}'", token) })?;
+                JsonKind::Number(value)
+            }
+            _ => return Err(self.error("expected a value")),
+        };
+        Ok(JsonValue { line, kind })
+    }
+
+    fn parse_string(&mut self) -> Result<String, ParseError> {
+        if self.bytes.get(self.offset) != Some(&b'"') {
+            return Err(self.error("expected a string"));
+        }
+        self.offset += 1;
+        let mut value: Vec<u8> = Vec::new();
+        loop {
+            match self.bytes.get(self.offset) {
+                Some(b'"') => break,
+                Some(b'\\') => {
+                    self.offset += 1;
+                    let escaped = match self.bytes.get(self.offset) {
+                        Some(b'n') => b'\n',
+                        Some(b't') => b'\t',
+                        Some(b'r') => b'\r',
+                        Some(b'b') => 0x08,
+                        Some(b'f') => 0x0c,
+                        Some(&byte @ (b'"' | b'\\' | b'/')) => byte,
+                        _ => return Err(self.error("unsupported escape sequence")),
+                    };
+                    value.push(escaped);
+                }
+                Some(b'\n') | None => return Err(self.error("unterminated string")),
+                Some(&byte) => value.push(byte),
+            }
+            self.offset += 1;
+        }
+        self.offset += 1;
+        Ok(String::from_utf8(value).expect("the input is valid UTF-8 and escapes are ASCII"))
+    }
+}
+
+#[test]
+fn test_write_json() {
+    let out_path = std::env::temp_dir().join("mesh_reorienter_input.json");
+    let out_path = out_path.to_str().unwrap();
+
+    let mesh = crate::parse_input("tests/input.txt").unwrap();
+    write_json(out_path, &mesh, "1,2,1".parse().unwrap());
+    let written = std::fs::read_to_string(out_path).unwrap();
+    assert!(written.starts_with("{\n  \"vertices\": [\n    [0.0, 0.00, 0.0],\n    [0.0, 0.00, 1.0],\n"));
+    assert!(written.ends_with("  \"triangles\": [\n    [0, 1, 2],\n    [0, 3, 2],\n    [0, 3, 1],\n    [1, 2, 3]\n  ]\n}\n"));
+}
+
+/// Write the mesh as a JSON document. `precision` sets the number of decimals or significant figures of the coordinates;
+/// coordinates that are not finite, which JSON cannot represent, are written as `null`, see [`parse_json_with`].
+pub fn write_json(out_path: &str, mesh: &Mesh, precision: PrecisionSpec) {
+    crate::write_file(out_path, |writer| writer.write_all(json_contents(mesh, precision).as_bytes()))
+        .expect("Something went wrong writing the file");
+}
+
+/// Format a coordinate as a JSON number with the given number of decimals.
+fn json_number(value: f64, decimals: usize) -> String {
+    if value.is_finite() {
+        format!("{:.*}", decimals, value)
+    } else {
+        String::from("null")
+    }
+}
+
+/// Format a point as a JSON array of its coordinates, each with the precision of its axis.
+fn json_point(point: &Vector3<f64>, precision: PrecisionSpec) -> String {
+    let [x, y, z] = [(point.x, precision.x), (point.y, precision.y), (point.z, precision.z)].map(|(value, axis)| json_number(value, precision.decimals(value, axis)));
+    format!("[{}, {}, {}]", x, y, z)
+}
+
+/// Format a named array of rows as a JSON object member, one row per line.
+fn json_member(out_contents: &mut String, name: &str, rows: impl Iterator<Item = String>) {
+    out_contents.push_str(&format!("  \"{}\": [", name));
+    let mut empty = true;
+    for row in rows {
+        out_contents.push_str(if empty { "\n    " } else { ",\n    " });
+        out_contents.push_str(&row);
+        empty = false;
+    }
+    out_contents.push_str(if empty { "]" } else { "\n  ]" });
+}
+
+/// Format the mesh as a JSON document.
+fn json_contents(mesh: &Mesh, precision: PrecisionSpec) -> String {
+    let mut out_contents = String::from("{\n");
+    json_member(&mut out_contents, "vertices", mesh.vertices.iter().map(|point| json_point(point, precision)));
+    if let Some(colors) = &mesh.colors {
+        out_contents.push_str(",\n");
+        json_member(&mut out_contents, "colors", colors.iter().map(|[r, g, b]| format!("[{}, {}, {}]", r, g, b)));
+    }
+    out_contents.push_str(",\n");
+    json_member(&mut out_contents, "triangles", mesh.triangles.iter().map(|[a, b, c]| format!("[{}, {}, {}]", a, b, c)));
+    out_contents.push_str("\n}\n");
+    out_contents
+}
@@ -0,0 +1,1798 @@
+//! # Triangle meshgrid vertex reorienter.
+//!
+//! Library behind the `mesh-reorienter` binary. It parses a triangle meshgrid,
+//! reorients its triangles, and writes the result back in the same format.
+//!
+//! Two reorientation approaches are available. [`make_winding_consistent`] propagates the winding
+//! of a seed triangle to its neighbours and works for any surface shape. [`reorient_mesh`] compares
+//! every triangle against the centroid and works as follows:
+//! 1. compute the centroid of the surface described by the meshgrid.
+//! 2. For each triangle,
+//!     1. compute the normal vector of the triangle as the cross product of the two vectors described by indices 0->1 and 0->2.
+//!     2. Compare the normal to the centroid of the meshgrid.
+//!         - If the dot product of the normal and the vector from the centroid to the first point of the triangle is positive, the normal points outwards from the centroid and the triangle is correctly aligned.
+//!         - If the dot product is negative, the normal points inwards from the centroid, and the order of the verteces must be reversed.
+//!
+//! The centroid of the meshgrid is assumed to be contained inside the surface described by the triangle meshgrid.
+
+use std::collections::HashMap;
+
+use nalgebra::Vector3;
+
+pub mod binary;
+pub mod cleanup;
+pub mod components;
+pub mod formats;
+pub mod gzip;
+pub mod interior;
+pub mod json;
+pub mod log;
+pub mod obj;
+pub mod off;
+pub mod planar;
+pub mod ply;
+pub mod polygon;
+pub mod stats;
+pub mod stl;
+pub mod winding;
+
+pub use binary::{parse_bin, write_bin};
+pub use cleanup::{drop_degenerate_triangles, drop_duplicate_triangles, find_degenerate_triangles, find_duplicate_triangles, repair_mesh, weld_vertices, RepairReport};
+pub use components::{component_mesh, find_misoriented_by_component, reorient_components, split_components};
+pub use formats::{convert_mesh, read_mesh, read_polygon_mesh, write_mesh, write_mesh_with_normals, write_polygon_mesh};
+#[allow(deprecated)]
+pub use interior::contains_point;
+pub use interior::{closest_point_on_mesh, is_inside, pick_interior_point, ray_triangle_intersect};
+pub use json::{parse_json, parse_json_with, write_json};
+pub use log::{Logger, Progress, Silent, WriterLogger};
+pub use obj::{parse_obj, parse_obj_polygons, write_obj, write_obj_polygons, write_obj_with_normals, FaceGroup};
+pub use off::{parse_off, parse_off_polygons, write_off, write_off_polygons};
+pub use planar::{find_clockwise_triangles, is_planar, orient_planar};
+pub use ply::{parse_ply, parse_ply_ascii, parse_ply_ascii_polygons, parse_ply_binary, parse_ply_polygons, write_ply, write_ply_polygons, write_ply_with_normals};
+pub use polygon::{
+    apply_polygon_winding, make_polygon_winding_consistent, orient_polygons_outward, parse_polygon_input, parse_polygon_reader,
+    polygon_area_weighted_centroid, reorient_polygons, reorient_polygons_around, write_polygons, write_polygons_to,
+    PolygonMesh,
+};
+pub use stats::{bounding_box, compute_euler_characteristic, compute_obb, total_surface_area, triangle_area, AreaReport, ComponentStats, MeshStats, OrientedBox, Topology};
+pub use stl::{parse_stl, parse_stl_ascii, parse_stl_binary, write_stl};
+pub use winding::{analyze_edges, apply_winding, build_edge_adjacency, copy_reference_winding, count_algorithm_disagreements, find_inconsistent_triangles, flip_all, flip_triangles, is_inside_out, make_winding_consistent, only_winding_changed, orient_outward, Winding};
+
+/// A triangle meshgrid: the point coordinates and the triangles indexing them.
+/// The point and triangle counts of the file format are always `vertices.len()` and `triangles.len()`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Mesh {
+    pub vertices: Vec<Vector3<f64>>,
+    pub triangles: Vec<[usize; 3]>,
+    /// Per-vertex RGB colors, parallel to `vertices`, when the input file carries them.
+    pub colors: Option<Vec<[u8; 3]>>,
+    /// Per-triangle OBJ group and material names, parallel to `triangles`, when the input file carries them.
+    pub groups: Option<Vec<FaceGroup>>,
+}
+
+#[test]
+fn test_mesh_append() {
+    let mut mesh = parse_input("tests/input.txt").unwrap();
+    let second = parse_input("tests/tetra_shifted.txt").unwrap();
+    mesh.append(second.clone());
+    assert_eq!(mesh.vertices.len(), 8);
+    assert_eq!(mesh.triangles.len(), 8);
+    assert_eq!(mesh.vertices[4..], second.vertices[..]);
+    assert_eq!(mesh.triangles[4], [4, 5, 6]);
+    assert_eq!(mesh.triangles[4..].to_vec(), second.triangles.iter().map(|triangle| triangle.map(|i| i + 4)).collect::<Vec<_>>());
+}
+
+#[test]
+fn test_mesh_recenter() {
+    let mut mesh = parse_obj("tests/torus.obj").unwrap();
+    for triangle in mesh.triangles.iter_mut().step_by(3) {
+        triangle.swap(1, 2);
+    }
+    let offset = Vector3::new(1e8, -1e8, 1e8);
+    let mut shifted = mesh.clone();
+    shifted.vertices.iter_mut().for_each(|point| *point += offset);
+
+    let center = shifted.recenter();
+    let (min, max) = bounding_box(&shifted).unwrap();
+    assert!((min + max).norm() < 1e-6);
+    let (min, max) = bounding_box(&mesh).unwrap();
+    assert!((center - offset - (min + max) / 2.0).norm() < 1e-6);
+    assert_eq!(
+        find_misoriented_triangles(&shifted, &compute_centroid(&shifted), NormalBackend::CrossProduct),
+        find_misoriented_triangles(&mesh, &compute_centroid(&mesh), NormalBackend::CrossProduct)
+    );
+}
+
+#[test]
+fn test_mesh_scale() {
+    let mut mesh = parse_input("tests/input.txt").unwrap();
+    let original = mesh.clone();
+    mesh.scale(Vector3::new(2.0, 0.5, 1.0));
+    assert_eq!(mesh.triangles, original.triangles);
+    for (point, original) in mesh.vertices.iter().zip(&original.vertices) {
+        assert_eq!(*point, Vector3::new(2.0 * original.x, 0.5 * original.y, original.z));
+    }
+}
+
+#[test]
+fn test_mesh_make_double_sided() {
+    let mut mesh = parse_input("tests/reordered.txt").unwrap();
+    let original = mesh.clone();
+    mesh.make_double_sided();
+    let n = original.triangles.len();
+    assert_eq!(mesh.vertices, original.vertices);
+    assert_eq!(mesh.triangles.len(), 2 * n);
+    assert_eq!(mesh.triangles[..n], original.triangles[..]);
+    for (&[a, b, c], twin) in original.triangles.iter().zip(&mesh.triangles[n..]) {
+        assert_eq!(*twin, [a, c, b]);
+    }
+}
+
+#[test]
+fn test_mesh_sort_triangles() {
+    let mut mesh = parse_input("tests/reordered.txt").unwrap();
+    let mut shuffled = mesh.clone();
+    shuffled.triangles = vec![[3, 2, 1], [2, 3, 0], [1, 0, 3], [0, 1, 2]];
+    mesh.sort_triangles();
+    shuffled.sort_triangles();
+    assert_eq!(mesh.triangles, vec![[0, 1, 2], [0, 2, 3], [0, 3, 1], [1, 3, 2]]);
+    assert_eq!(shuffled, mesh);
+}
+
+#[test]
+fn test_mesh_translate() {
+    let mut mesh = parse_input("tests/input.txt").unwrap();
+    let original = mesh.clone();
+    let offset = Vector3::new(1.0, -2.0, 0.5);
+    mesh.translate(offset);
+    assert_eq!(mesh.triangles, original.triangles);
+    assert_eq!(mesh.vertices, original.vertices.iter().map(|point| point + offset).collect::<Vec<_>>());
+}
+
+impl Mesh {
+    /// Translate the points so that the center of their bounding box lies at the origin, and return that center.
+    /// Far from the origin, the coordinates keep few significant digits for the small differences that
+    /// cross and dot products work on; centered coordinates keep all of them. A mesh without points is left as is.
+    pub fn recenter(&mut self) -> Vector3<f64> {
+        let Some((min, max)) = bounding_box(self) else {
+            return Vector3::new(0.0, 0.0, 0.0);
+        };
+        let center = (min + max) / 2.0;
+        for point in &mut self.vertices {
+            *point -= center;
+        }
+        center
+    }
+
+    /// Multiply the coordinates of every point by `factors`, axis by axis. Positive factors keep the winding
+    /// of every triangle outward-facing, while a negative one mirrors the mesh and turns it inside out.
+    pub fn scale(&mut self, factors: Vector3<f64>) {
+        for point in &mut self.vertices {
+            point.component_mul_assign(&factors);
+        }
+    }
+
+    /// Add `offset` to every point. Translating changes no normal, so it keeps the winding of every triangle.
+    pub fn translate(&mut self, offset: Vector3<f64>) {
+        for point in &mut self.vertices {
+            *point += offset;
+        }
+    }
+
+    /// Sort the triangles by their indices, each first rotated so that it starts at its smallest index, which keeps
+    /// its winding. The order then only depends on the set of triangles, not on the order they came in.
+    /// The groups, if any, follow their triangles.
+    pub fn sort_triangles(&mut self) {
+        for triangle in &mut self.triangles {
+            *triangle = rotate_to_smallest(*triangle);
+        }
+        let mut order: Vec<usize> = (0..self.triangles.len()).collect();
+        order.sort_by_key(|&t| self.triangles[t]);
+        self.triangles = order.iter().map(|&t| self.triangles[t]).collect();
+        if let Some(groups) = &mut self.groups {
+            *groups = order.iter().map(|&t| groups[t].clone()).collect();
+        }
+    }
+
+    /// Append a copy of every triangle with its winding reversed, so that the surface faces both ways.
+    /// The copies share the points of their originals and take their groups, if any.
+    pub fn make_double_sided(&mut self) {
+        let reversed: Vec<[usize; 3]> = self.triangles.iter().map(|&[a, b, c]| [a, c, b]).collect();
+        self.triangles.extend(reversed);
+        if let Some(groups) = &mut self.groups {
+            groups.extend_from_within(..);
+        }
+    }
+
+    /// Append the points and triangles of `other`, offsetting its triangle indices past the points already in the mesh.
+    /// The colors are kept only if both meshes have them, or if this mesh was empty, and likewise the groups.
+    pub fn append(&mut self, other: Mesh) {
+        let offset = self.vertices.len();
+        self.groups = match (self.groups.take(), other.groups) {
+            (Some(mut groups), Some(other_groups)) => {
+                groups.extend(other_groups);
+                Some(groups)
+            }
+            (None, other_groups) if self.triangles.is_empty() => other_groups,
+            _ => None,
+        };
+        self.colors = match (self.colors.take(), other.colors) {
+            (Some(mut colors), Some(other_colors)) => {
+                colors.extend(other_colors);
+                Some(colors)
+            }
+            (None, other_colors) if offset == 0 => other_colors,
+            _ => None,
+        };
+        self.vertices.extend(other.vertices);
+        self.triangles.extend(other.triangles.iter().map(|triangle| triangle.map(|i| i + offset)));
+    }
+}
+
+#[test]
+fn test_reorient_mesh() {
+    let mut mesh = parse_input("tests/input.txt").unwrap();
+    reorient_mesh(&mut mesh);
+    assert_eq!(mesh.triangles, vec![[0, 1, 2], [0, 2, 3], [0, 3, 1], [1, 3, 2]]);
+}
+
+#[test]
+fn test_reorient_mesh_parallel_matches_sequential() {
+    // a large fan of triangles around the origin, with every other one wound the wrong way
+    let n = 100_000;
+    let mut vertices = vec![Vector3::new(0.0, 0.0, 1.0)];
+    for i in 0..n {
+        let angle = 2.0 * std::f64::consts::PI * i as f64 / n as f64;
+        vertices.push(Vector3::new(angle.cos(), angle.sin(), 0.0));
+    }
+    let triangles = (0..n)
+        .map(|i| if i % 2 == 0 { [0, i + 1, (i + 1) % n + 1] } else { [0, (i + 1) % n + 1, i + 1] })
+        .collect();
+    let mut mesh = Mesh { vertices, triangles, colors: None, groups: None };
+
+    let mut sequential = mesh.triangles.clone();
+    reorient_triangles(&mesh.vertices, &mut sequential, &compute_centroid(&mesh), NormalBackend::CrossProduct, 0.0, true, &Silent);
+    reorient_mesh(&mut mesh);
+    assert_eq!(mesh.triangles, sequential);
+}
+
+/// Reorient every triangle of the mesh so that its normal points away from the centroid of its vertices.
+/// A mesh without triangles is left as it is, without computing the centroid.
+pub fn reorient_mesh(mesh: &mut Mesh) {
+    if mesh.triangles.is_empty() {
+        return;
+    }
+    let centroid = compute_centroid(mesh);
+    reorient_mesh_around(mesh, &centroid);
+}
+
+/// Reorient every triangle of the mesh so that its normal points away from `centroid`.
+pub fn reorient_mesh_around(mesh: &mut Mesh, centroid: &Vector3<f64>) {
+    reorient_mesh_with(mesh, centroid, NormalBackend::CrossProduct);
+}
+
+/// Reorient every triangle of the mesh so that its normal, computed with `backend`, points away from `centroid`.
+/// The triangles are split into one chunk per available thread and processed in parallel.
+/// Triangles whose normal is exactly perpendicular to the centroid ray are flipped, see
+/// [`reorient_mesh_with_epsilon`] to leave them to the caller instead.
+pub fn reorient_mesh_with(mesh: &mut Mesh, centroid: &Vector3<f64>, backend: NormalBackend) {
+    reorient_in_parallel(mesh, centroid, backend, 0.0, true, &Silent);
+}
+
+#[test]
+fn test_reorient_mesh_with_progress() {
+    struct Counter(std::sync::atomic::AtomicUsize);
+    impl Progress for Counter {
+        fn advance(&self, done: usize) {
+            self.0.fetch_add(done, std::sync::atomic::Ordering::Relaxed);
+        }
+    }
+
+    let mut mesh = parse_obj("tests/torus.obj").unwrap();
+    let mut expected = mesh.clone();
+    let counter = Counter(std::sync::atomic::AtomicUsize::new(0));
+    reorient_mesh_with_progress(&mut mesh, &compute_centroid(&expected), NormalBackend::CrossProduct, &counter);
+    reorient_mesh(&mut expected);
+    assert_eq!(mesh, expected);
+    assert_eq!(counter.0.into_inner(), mesh.triangles.len());
+}
+
+/// Reorient the triangles of the mesh like [`reorient_mesh_with`], reporting the number of processed triangles
+/// to `progress` as the work goes on, in batches, from every thread. The batches add up to the number of triangles.
+pub fn reorient_mesh_with_progress(mesh: &mut Mesh, centroid: &Vector3<f64>, backend: NormalBackend, progress: &dyn Progress) {
+    reorient_in_parallel(mesh, centroid, backend, 0.0, true, progress);
+}
+
+#[test]
+fn test_reorient_mesh_with_epsilon() {
+    // the first triangle lies in the plane of the centroid, the second faces it
+    let mut mesh = Mesh {
+        vertices: vec![
+            Vector3::new(0.0, 0.0, 0.0),
+            Vector3::new(1.0, 0.0, 0.0),
+            Vector3::new(0.0, 1.0, 0.0),
+            Vector3::new(0.0, 0.0, 1.0),
+        ],
+        triangles: vec![[0, 1, 2], [0, 1, 3]],
+        colors: None,
+        groups: None,
+    };
+    let centroid = Vector3::new(-1.0, -1.0, 0.0);
+    assert_eq!(classify_triangle(&mesh.vertices, &mesh.triangles[0], &centroid, NormalBackend::CrossProduct, 1e-9), Orientation::Ambiguous);
+
+    let ambiguous = reorient_mesh_with_epsilon(&mut mesh, &centroid, NormalBackend::CrossProduct, 1e-9);
+    assert_eq!(ambiguous, vec![0]);
+    assert_eq!(mesh.triangles, vec![[0, 1, 2], [0, 3, 1]]);
+}
+
+/// Reorient the triangles of the mesh like [`reorient_mesh_with`], except that triangles classified as
+/// [`Orientation::Ambiguous`] with the given `epsilon` are left untouched. Returns the indices of those
+/// triangles, in increasing order, so that the caller can decide how to treat them.
+pub fn reorient_mesh_with_epsilon(mesh: &mut Mesh, centroid: &Vector3<f64>, backend: NormalBackend, epsilon: f64) -> Vec<usize> {
+    reorient_in_parallel(mesh, centroid, backend, epsilon, false, &Silent)
+        .into_iter()
+        .filter(|decision| decision.orientation == Orientation::Ambiguous)
+        .map(|decision| decision.index)
+        .collect()
+}
+
+#[test]
+fn test_reorient_mesh_logged() {
+    let mut mesh = parse_input("tests/input.txt").unwrap();
+    let centroid = compute_centroid(&mesh);
+    let mut logger = WriterLogger(Vec::new());
+    reorient_mesh_logged(&mut mesh, &centroid, NormalBackend::CrossProduct, None, &mut logger);
+
+    let log = String::from_utf8(logger.0).unwrap();
+    let lines: Vec<&str> = log.lines().collect();
+    assert_eq!(lines.len(), 4);
+    assert_eq!(lines[0], "triangle 0: normal -1 0 0, dot 0.25, kept");
+    assert!(lines[1].ends_with(", flipped"));
+}
+
+/// Reorient the triangles of the mesh like [`reorient_mesh_with`], or like [`reorient_mesh_with_epsilon`] if an
+/// `epsilon` is given, and log the [`TriangleDecision`] made for every triangle, in index order, to `logger`.
+/// Returns the indices of the ambiguous triangles left untouched, which is always empty without an `epsilon`.
+pub fn reorient_mesh_logged(mesh: &mut Mesh, centroid: &Vector3<f64>, backend: NormalBackend, epsilon: Option<f64>, logger: &mut dyn Logger) -> Vec<usize> {
+    let decisions = reorient_in_parallel(mesh, centroid, backend, epsilon.unwrap_or(0.0), epsilon.is_none(), &Silent);
+    let mut ambiguous: Vec<usize> = Vec::new();
+    for decision in decisions {
+        logger.log(&decision.to_string());
+        if decision.orientation == Orientation::Ambiguous && !decision.flipped {
+            ambiguous.push(decision.index);
+        }
+    }
+    ambiguous
+}
+
+/// Decision made for one triangle when reorienting it against a centroid.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TriangleDecision {
+    /// Index of the triangle in the mesh.
+    pub index: usize,
+    /// Normal of the triangle before reorientation, unnormalized.
+    pub normal: Vector3<f64>,
+    /// Dot product of the normal and the vector from the centroid to the first point of the triangle.
+    pub dot_product: f64,
+    pub orientation: Orientation,
+    /// Whether the vertex order of the triangle was reversed.
+    pub flipped: bool,
+}
+
+impl std::fmt::Display for TriangleDecision {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "triangle {}: normal {} {} {}, dot {}, {}", self.index, self.normal.x, self.normal.y, self.normal.z, self.dot_product,
+            if self.flipped { "flipped" } else { "kept" })?;
+        if self.orientation == Orientation::Ambiguous {
+            write!(f, " (ambiguous)")?;
+        }
+        Ok(())
+    }
+}
+
+/// Split the triangles into one chunk per available thread and reorient the chunks in parallel,
+/// returning the decision made for every triangle in index order.
+fn reorient_in_parallel(mesh: &mut Mesh, centroid: &Vector3<f64>, backend: NormalBackend, epsilon: f64, flip_ambiguous: bool, progress: &dyn Progress) -> Vec<TriangleDecision> {
+    let n_threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+    let chunk_size = mesh.triangles.len().div_ceil(n_threads).max(1);
+
+    let vertices = &mesh.vertices;
+    std::thread::scope(|scope| {
+        let handles: Vec<_> = mesh.triangles.chunks_mut(chunk_size)
+            .map(|chunk| scope.spawn(move || reorient_triangles(vertices, chunk, centroid, backend, epsilon, flip_ambiguous, progress)))
+            .collect();
+        handles.into_iter()
+            .enumerate()
+            .flat_map(|(k, handle)| {
+                let decisions = handle.join().expect("reorientation thread panicked");
+                decisions.into_iter().map(move |decision| TriangleDecision { index: k * chunk_size + decision.index, ..decision })
+            })
+            .collect()
+    })
+}
+
+#[test]
+fn test_find_misoriented_triangles() {
+    let mesh = parse_input("tests/input.txt").unwrap();
+    let centroid = compute_centroid(&mesh);
+    assert_eq!(find_misoriented_triangles(&mesh, &centroid, NormalBackend::CrossProduct), vec![1, 3]);
+}
+
+/// Indices, in increasing order, of the triangles whose normal, computed with `backend`, does not point away from `centroid`.
+/// These are the triangles [`reorient_mesh_with`] would flip; the mesh itself is left untouched.
+pub fn find_misoriented_triangles(mesh: &Mesh, centroid: &Vector3<f64>, backend: NormalBackend) -> Vec<usize> {
+    (0..mesh.triangles.len())
+        .filter(|&t| !compute_triangle_direction_with(&mesh.vertices, &mesh.triangles[t], centroid, backend))
+        .collect()
+}
+
+#[test]
+fn test_orientation_flags() {
+    let mesh = parse_input("tests/input.txt").unwrap();
+    let centroid = compute_centroid(&mesh);
+    assert_eq!(orientation_flags(&mesh, &centroid), vec![true, false, true, false]);
+}
+
+/// Whether the normal of every triangle points away from `reference`, in the order of the triangle list,
+/// see [`compute_triangle_norm_vec_direction`]. The mesh is left untouched.
+pub fn orientation_flags(mesh: &Mesh, reference: &Vector3<f64>) -> Vec<bool> {
+    mesh.triangles.iter().map(|triangle| compute_triangle_norm_vec_direction(&mesh.vertices, triangle, reference)).collect()
+}
+
+#[test]
+fn test_oriented_triangles() {
+    let mesh = parse_obj("tests/torus.obj").unwrap();
+    let centroid = compute_centroid(&mesh);
+    let iter = oriented_triangles(&mesh, &centroid, NormalBackend::CrossProduct);
+    assert_eq!(iter.len(), mesh.triangles.len());
+    let oriented: Vec<[usize; 3]> = iter.collect();
+
+    let mut reoriented = mesh.clone();
+    reorient_mesh(&mut reoriented);
+    assert_eq!(oriented, reoriented.triangles);
+}
+
+/// Iterator over the triangles of a mesh in the winding [`reorient_mesh_with`] would give them, without mutating
+/// or cloning the mesh, see [`oriented_triangles`].
+#[derive(Debug, Clone)]
+pub struct OrientedTriangles<'a> {
+    vertices: &'a [Vector3<f64>],
+    triangles: std::slice::Iter<'a, [usize; 3]>,
+    centroid: &'a Vector3<f64>,
+    backend: NormalBackend,
+}
+
+impl Iterator for OrientedTriangles<'_> {
+    type Item = [usize; 3];
+
+    fn next(&mut self) -> Option<[usize; 3]> {
+        let &[a, b, c] = self.triangles.next()?;
+        if compute_triangle_direction_with(self.vertices, &[a, b, c], self.centroid, self.backend) {
+            Some([a, b, c])
+        } else {
+            Some([a, c, b])
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.triangles.size_hint()
+    }
+}
+
+impl ExactSizeIterator for OrientedTriangles<'_> {}
+
+/// Iterate over the triangles of the mesh one at a time, in order, each wound so that its normal, computed with
+/// `backend`, points away from `centroid`. Each triangle is only analysed when it is reached.
+pub fn oriented_triangles<'a>(mesh: &'a Mesh, centroid: &'a Vector3<f64>, backend: NormalBackend) -> OrientedTriangles<'a> {
+    OrientedTriangles { vertices: &mesh.vertices, triangles: mesh.triangles.iter(), centroid, backend }
+}
+
+/// Number of triangles reoriented between two progress reports of a thread.
+const PROGRESS_BATCH: usize = 4096;
+
+/// Reorient a slice of triangles so that their normals point away from `centroid`.
+/// Ambiguous triangles are flipped only if `flip_ambiguous` is set. Returns the decision made for every
+/// triangle, indexed within the slice. Progress is reported every [`PROGRESS_BATCH`] triangles and at the end.
+fn reorient_triangles(vertices: &[Vector3<f64>], triangles: &mut [[usize; 3]], centroid: &Vector3<f64>, backend: NormalBackend, epsilon: f64, flip_ambiguous: bool, progress: &dyn Progress) -> Vec<TriangleDecision> {
+    let mut decisions: Vec<TriangleDecision> = Vec::with_capacity(triangles.len());
+    for (t, triangle) in triangles.iter_mut().enumerate() {
+        if t > 0 && t % PROGRESS_BATCH == 0 {
+            progress.advance(PROGRESS_BATCH);
+        }
+        let (normal, dot_product) = centroid_dot_product(vertices, triangle, centroid, backend);
+        let orientation = orientation_from_dot_product(dot_product, epsilon);
+        let flipped = match orientation {
+            Orientation::Outward => false,
+            Orientation::Inward => true,
+            Orientation::Ambiguous => flip_ambiguous,
+        };
+        if flipped {
+            triangle.swap(1, 2);
+        }
+        decisions.push(TriangleDecision { index: t, normal, dot_product, orientation, flipped });
+    }
+    if !triangles.is_empty() {
+        progress.advance((triangles.len() - 1) % PROGRESS_BATCH + 1);
+    }
+    decisions
+}
+
+#[test]
+fn test_parse_input() {
+    use nalgebra::Vector3;
+
+    let in_path = "tests/input.txt";
+    let mesh = parse_input(in_path).unwrap();
+    assert_eq!(mesh.vertices, vec![
+        Vector3::new(0.0, 0.0, 0.0), 
+        Vector3::new(0.0, 0.0, 1.0), 
+        Vector3::new(0.0, 1.0, 0.0), 
+        Vector3::new(1.0, 0.0, 0.0)
+    ]);
+    assert_eq!(mesh.triangles, vec![[0, 1, 2], [0, 3, 2], [0, 3, 1], [1, 2, 3]]);
+}
+
+/// Error produced while parsing an input mesh file.
+/// Text formats report the 1-based line number where parsing failed, binary formats the byte offset.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParseError {
+    /// The file could not be read.
+    Io(String),
+    /// The file ended before an expected line was found.
+    UnexpectedEof { line: usize, reason: String },
+    /// A line ended before all of its expected values were read.
+    MissingValue { line: usize, reason: String },
+    /// A value could not be parsed as a number.
+    InvalidNumber { line: usize, reason: String },
+    /// A JSON document is malformed or does not describe a mesh, or a native file holds more rows than it declares.
+    Syntax { line: usize, reason: String },
+    /// A binary file is malformed; `offset` is the byte offset where parsing failed.
+    Binary { offset: usize, reason: String },
+    /// A face row holds the wrong number of point indices: exactly three for a triangle, at least three for a polygon.
+    WrongIndexCount { line: usize, count: usize, polygon: bool },
+    /// A triangle references a point past the end of the point list.
+    /// `triangle` is the 0-based position of the triangle in the triangle list.
+    IndexOutOfRange { triangle: usize, index: usize, n_points: usize },
+}
+
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            ParseError::Io(reason) => write!(f, "could not read file: {}", reason),
+            ParseError::UnexpectedEof { line, reason }
+            | ParseError::MissingValue { line, reason }
+            | ParseError::InvalidNumber { line, reason }
+            | ParseError::Syntax { line, reason } => write!(f, "line {}: {}", line, reason),
+            ParseError::Binary { offset, reason } => write!(f, "byte {}: {}", offset, reason),
+            ParseError::WrongIndexCount { line, count, polygon } => write!(
+                f,
+                "line {}: {} row has {} point indices, expected {}",
+                line,
+                if *polygon { "face" } else { "triangle" },
+                count,
+                if *polygon { "at least 3" } else { "exactly 3" }
+            ),
+            ParseError::IndexOutOfRange { triangle, index, n_points } => write!(
+                f,
+                "triangle {} references point {}, but there are only {} points",
+                triangle, index, n_points
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Parse a single whitespace-separated token found on the 1-based line `line`.
+/// `what` describes the expected value and is used in the error message.
+/// Surrounding whitespace is trimmed first, so a `\r` left over from a Windows line ending is never part of the number.
+/// Numbers follow the syntax of [`str::parse`], so coordinates such as `1.5e-3`, `+2.0` and `.5` are accepted.
+pub(crate) fn parse_token<T: std::str::FromStr>(token: Option<&str>, line: usize, what: &str) -> Result<T, ParseError> {
+    let token = token.ok_or_else(|| ParseError::MissingValue { line, reason: format!("missing {}", what) })?.trim();
+    token.parse::<T>().map_err(|_| {
+        let reason = if token.starts_with('-') {
+            format!("negative {} '{}'", what, token)
+        } else {
+            format!("invalid {} '{}'", what, token)
+        };
+        ParseError::InvalidNumber { line, reason }
+    })
+}
+
+#[test]
+fn test_validate_mesh() {
+    let err = parse_input("tests/index_out_of_range.txt").unwrap_err();
+    assert_eq!(err, ParseError::IndexOutOfRange { triangle: 1, index: 3, n_points: 3 });
+    assert!(err.to_string().contains("triangle 1"));
+
+    let err = parse_input("tests/negative_index.txt").unwrap_err();
+    assert_eq!(err, ParseError::InvalidNumber { line: 6, reason: String::from("negative point index '-1'") });
+}
+
+/// Check that every triangle index refers to an existing point.
+pub fn validate_mesh(mesh: &Mesh) -> Result<(), ParseError> {
+    validate_faces(mesh.vertices.len(), &mesh.triangles)
+}
+
+/// Check that every index of every face is below `n_points`.
+pub(crate) fn validate_faces<F: AsRef<[usize]>>(n_points: usize, faces: &[F]) -> Result<(), ParseError> {
+    for (triangle, indices) in faces.iter().enumerate() {
+        if let Some(&index) = indices.as_ref().iter().find(|&&index| index >= n_points) {
+            return Err(ParseError::IndexOutOfRange { triangle, index, n_points });
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn test_parse_input_short_triangle_row() {
+    let err = parse_input("tests/short_triangle.txt").unwrap_err();
+    assert_eq!(err, ParseError::WrongIndexCount { line: 6, count: 2, polygon: false });
+    assert_eq!(err.to_string(), "line 6: triangle row has 2 point indices, expected exactly 3");
+}
+
+#[test]
+fn test_parse_input_missing_coordinate() {
+    let err = parse_input("tests/missing_coordinate.txt").unwrap_err();
+    assert_eq!(err, ParseError::MissingValue { line: 3, reason: String::from("missing z coordinate") });
+}
+
+#[test]
+fn test_parse_input_invalid_point_count() {
+    let err = parse_input("tests/invalid_point_count.txt").unwrap_err();
+    assert!(matches!(err, ParseError::InvalidNumber { line: 1, .. }));
+}
+
+#[test]
+fn test_parse_input_planar() {
+    let mesh = parse_input("tests/planar.txt").unwrap();
+    assert_eq!(mesh.vertices.len(), 5);
+    assert_eq!(mesh.vertices[4], Vector3::new(2.0, 0.5, 0.0));
+    assert!(mesh.vertices.iter().all(|point| point.z == 0.0));
+
+    // with the option, a three-column file must keep every point in the z = 0 plane
+    let options = ParseOptions { planar: true, ..ParseOptions::default() };
+    let err = parse_input_with("tests/input.txt", &options).unwrap_err();
+    assert_eq!(err, ParseError::Syntax { line: 3, reason: String::from("planar point has a z coordinate of '1.0'") });
+}
+
+#[test]
+fn test_parse_input_empty() {
+    let mut mesh = parse_input("tests/empty.txt").unwrap();
+    assert_eq!(mesh, Mesh::default());
+    reorient_mesh(&mut mesh);
+    assert_eq!(mesh, Mesh::default());
+    assert_eq!(native_contents(&mesh.vertices, &mesh.triangles, PrecisionSpec::uniform(1), 0), "0\n0\n");
+
+    let mut mesh = parse_input("tests/no_triangles.txt").unwrap();
+    assert_eq!((mesh.vertices.len(), mesh.triangles.len()), (3, 0));
+    let expected = mesh.clone();
+    reorient_mesh(&mut mesh);
+    assert_eq!(mesh, expected);
+}
+
+#[test]
+fn test_parse_input_extra_rows() {
+    let err = parse_input("tests/extra_triangles.txt").unwrap_err();
+    assert_eq!(err, ParseError::Syntax { line: 9, reason: String::from("unexpected row after the 2 declared triangles") });
+}
+
+#[test]
+fn test_parse_input_nonfinite_coordinate() {
+    let err = parse_input("tests/nonfinite.txt").unwrap_err();
+    assert_eq!(err, ParseError::InvalidNumber { line: 4, reason: String::from("non-finite y coordinate 'inf'") });
+
+    let options = ParseOptions { allow_nonfinite: true, ..ParseOptions::default() };
+    let mesh = parse_input_with("tests/nonfinite.txt", &options).unwrap();
+    assert_eq!(mesh.vertices[2].y, f64::INFINITY);
+}
+
+/// Options of the native format parser.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ParseOptions {
+    /// Accept `nan`, `inf` and other coordinates that are not finite numbers, which are rejected by default
+    /// since they poison the centroid and the normals.
+    pub allow_nonfinite: bool,
+    /// Index of the first point in the triangle rows, 0 unless the file was written with another base.
+    pub index_base: usize,
+    /// Read a file without the point and triangle count lines: the points run up to the first blank line
+    /// or a line holding just `faces`, and every row after it is a triangle.
+    pub no_counts: bool,
+    /// Read points made of two coordinates, x and y, and place them in the z = 0 plane. A third coordinate is
+    /// accepted only when it is 0. Without this option, a file whose first point row holds exactly two values is
+    /// read as planar too.
+    pub planar: bool,
+    /// Read point rows that start with the index of the point, `i x y z`, as some exporters write them. The
+    /// triangle rows refer to the points by these indices, which may come in any order and skip values.
+    pub indexed_points: bool,
+}
+
+/// Parse the input file and return the mesh it describes.
+/// The file is streamed line by line rather than read into memory at once, see [`parse_reader`].
+/// A gzip-compressed file, whose path ends in `.gz`, is decompressed in memory first.
+pub fn parse_input(in_path: &str) -> Result<Mesh, ParseError> {
+    parse_input_with(in_path, &ParseOptions::default())
+}
+
+/// Parse the input file like [`parse_input`], with the given options.
+pub fn parse_input_with(in_path: &str, options: &ParseOptions) -> Result<Mesh, ParseError> {
+    parse_reader_with(open_input(in_path)?, options)
+}
+
+#[test]
+fn test_parse_input_gzip() {
+    assert_eq!(parse_input("tests/input.txt.gz").unwrap(), parse_input("tests/input.txt").unwrap());
+    assert_eq!(obj::parse_obj("tests/torus.obj.gz").unwrap(), obj::parse_obj("tests/torus.obj").unwrap());
+}
+
+/// Open an input file for reading. Files whose path ends in `.gz` are decompressed in memory,
+/// so every parser accepts gzip-compressed input.
+pub(crate) fn open_input(in_path: &str) -> Result<Box<dyn std::io::Read>, ParseError> {
+    if gzip::is_gzip_path(in_path) {
+        let bytes = std::fs::read(in_path)
+            .map_err(|e| ParseError::Io(e.to_string()))?;
+        let contents = gzip::decompress(&bytes)
+            .map_err(|e| ParseError::Io(e.to_string()))?;
+        return Ok(Box::new(std::io::Cursor::new(contents)));
+    }
+    let file = std::fs::File::open(in_path)
+        .map_err(|e| ParseError::Io(e.to_string()))?;
+    Ok(Box::new(file))
+}
+
+/// Read a whole input file, decompressing it if needed, see [`open_input`].
+pub(crate) fn read_input(in_path: &str) -> Result<Vec<u8>, ParseError> {
+    let mut contents: Vec<u8> = Vec::new();
+    open_input(in_path)?.read_to_end(&mut contents)
+        .map_err(|e| ParseError::Io(e.to_string()))?;
+    Ok(contents)
+}
+
+/// Read a whole text input file, decompressing it if needed, see [`open_input`].
+pub(crate) fn read_input_to_string(in_path: &str) -> Result<String, ParseError> {
+    String::from_utf8(read_input(in_path)?)
+        .map_err(|e| ParseError::Io(e.to_string()))
+}
+
+#[test]
+fn test_parse_reader() {
+    let contents = std::fs::read("tests/input.txt").unwrap();
+    let mesh = parse_reader(std::io::Cursor::new(contents)).unwrap();
+    assert_eq!(mesh, parse_input("tests/input.txt").unwrap());
+}
+
+/// Parse a mesh in the native text format from any reader, such as a file or standard input.
+/// The reader is buffered and parsed one line at a time, so memory use grows with the mesh rather than with the size of the text.
+pub fn parse_reader(reader: impl std::io::Read) -> Result<Mesh, ParseError> {
+    parse_reader_with(reader, &ParseOptions::default())
+}
+
+/// Parse a mesh in the native text format from any reader like [`parse_reader`], with the given options.
+pub fn parse_reader_with(reader: impl std::io::Read, options: &ParseOptions) -> Result<Mesh, ParseError> {
+    let (vertices, triangles) = parse_native(reader, false, |face| [face[0], face[1], face[2]], options, None)?;
+    Ok(Mesh { vertices, triangles, colors: None, groups: None })
+}
+
+#[test]
+fn test_preserve_coordinate_text() {
+    let out_path = std::env::temp_dir().join("mesh_reorienter_preserved.txt");
+    let out_path = out_path.to_str().unwrap();
+
+    let (mut mesh, coordinate_text) = parse_input_preserving("tests/high_precision.txt", &ParseOptions::default()).unwrap();
+    assert_eq!(coordinate_text[1], "0.10000000000000000555 0.00000000000000001 1.0000000000000002");
+    reorient_mesh(&mut mesh);
+    write_output_preserving(out_path, &mesh, &coordinate_text, 0);
+
+    let input = std::fs::read_to_string("tests/high_precision.txt").unwrap();
+    let written = std::fs::read_to_string(out_path).unwrap();
+    let n_point_lines = mesh.vertices.len() + 2;
+    assert_eq!(written.lines().take(n_point_lines).collect::<Vec<_>>(), input.lines().take(n_point_lines).collect::<Vec<_>>());
+    assert_eq!(parse_input(out_path).unwrap(), mesh);
+}
+
+/// Parse the input file like [`parse_input`], also returning the original text of the coordinates of every point,
+/// as its three tokens separated by single spaces. Writing the mesh with [`write_output_preserving`] then
+/// leaves the coordinates exactly as they were, whatever their precision.
+pub fn parse_input_preserving(in_path: &str, options: &ParseOptions) -> Result<(Mesh, Vec<String>), ParseError> {
+    let mut coordinate_text: Vec<String> = Vec::new();
+    let (vertices, triangles) = parse_native(open_input(in_path)?, false, |face| [face[0], face[1], face[2]], options, Some(&mut coordinate_text))?;
+    Ok((Mesh { vertices, triangles, colors: None, groups: None }, coordinate_text))
+}
+
+/// Reads a text source one line at a time, keeping track of the 1-based line number for error reporting.
+/// Blank lines and comment lines, whose first non-whitespace character is `#`, are skipped.
+pub(crate) struct LineReader<R: std::io::BufRead> {
+    reader: R,
+    line: String,
+    line_no: usize,
+}
+
+impl<R: std::io::BufRead> LineReader<R> {
+    pub(crate) fn new(reader: R) -> Self {
+        LineReader { reader, line: String::new(), line_no: 0 }
+    }
+
+    /// Read the next line into the buffer, blank or not. Returns `false` at the end of the input.
+    fn advance(&mut self) -> Result<bool, ParseError> {
+        self.line.clear();
+        let n_bytes = self.reader.read_line(&mut self.line)
+            .map_err(|e| ParseError::Io(e.to_string()))?;
+        self.line_no += 1;
+        Ok(n_bytes > 0)
+    }
+
+    /// Read lines into the buffer until one is neither blank nor a comment. Returns `false` at the end of the input.
+    fn advance_to_content(&mut self) -> Result<bool, ParseError> {
+        while self.advance()? {
+            if !is_skipped_line(&self.line) {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    /// Read the next line, blank or not, returning its line number and contents, or `None` at the end of the input.
+    fn next_raw_line(&mut self) -> Result<Option<(usize, &str)>, ParseError> {
+        Ok(self.advance()?.then_some((self.line_no, self.line.as_str())))
+    }
+
+    /// Read the next line that is neither blank nor a comment, or `None` at the end of the input.
+    fn next_content_line(&mut self) -> Result<Option<(usize, &str)>, ParseError> {
+        Ok(self.advance_to_content()?.then_some((self.line_no, self.line.as_str())))
+    }
+
+    /// Read the next line that is neither blank nor a comment, returning its line number and contents.
+    /// `what` describes the expected contents and is used in the error message at the end of the input.
+    pub(crate) fn next_line(&mut self, what: &str) -> Result<(usize, &str), ParseError> {
+        if !self.advance_to_content()? {
+            return Err(ParseError::UnexpectedEof { line: self.line_no, reason: format!("expected {}", what) });
+        }
+        Ok((self.line_no, &self.line))
+    }
+}
+
+/// Whether a line of the native format carries nothing: it is blank or a `#` comment.
+fn is_skipped_line(line: &str) -> bool {
+    let content = line.trim_start();
+    content.is_empty() || content.starts_with('#')
+}
+
+#[test]
+fn test_parse_input_no_counts() {
+    let options = ParseOptions { no_counts: true, ..ParseOptions::default() };
+    let expected = parse_input("tests/input.txt").unwrap();
+    assert_eq!(parse_input_with("tests/no_counts.txt", &options).unwrap(), expected);
+    assert_eq!(parse_input_with("tests/no_counts_marker.txt", &options).unwrap(), expected);
+}
+
+#[test]
+fn test_parse_input_comma_separated() {
+    let mesh = parse_input("tests/comma_separated.txt").unwrap();
+    assert_eq!(mesh, parse_input("tests/input.txt").unwrap());
+}
+
+/// Split a row of the native format into its fields, separated by any run of whitespace and commas,
+/// so that rows such as `0,1,2`, `0, 1, 2` and tab-separated exports are all accepted.
+pub(crate) fn split_fields(line: &str) -> impl Iterator<Item = &str> {
+    line.split(|c: char| c.is_whitespace() || c == ',').filter(|field| !field.is_empty())
+}
+
+#[test]
+fn test_parse_input_crlf_line_endings() {
+    assert!(std::fs::read_to_string("tests/input_crlf.txt").unwrap().contains("\r\n"));
+    assert_eq!(parse_input("tests/input_crlf.txt").unwrap(), parse_input("tests/input.txt").unwrap());
+    assert_eq!(parse_token::<usize>(Some("4\r"), 1, "number of points"), Ok(4));
+}
+
+#[test]
+fn test_parse_input_comments_and_blank_lines() {
+    let mesh = parse_input("tests/commented.txt").unwrap();
+    assert_eq!(mesh, parse_input("tests/input.txt").unwrap());
+}
+
+#[test]
+fn test_parse_reader_large_file() {
+    let in_path = std::env::temp_dir().join("mesh_reorienter_large.txt");
+    let n = 150_000;
+    let mut contents = format!("{}\n", n + 2);
+    for i in 0..n + 2 {
+        contents.push_str(&format!("{} {} 0.0\n", i, i % 7));
+    }
+    contents.push_str(&format!("{}\n", n));
+    for i in 0..n {
+        contents.push_str(&format!("{} {} {}\n", i, i + 1, i + 2));
+    }
+    std::fs::write(&in_path, contents).unwrap();
+
+    let mesh = parse_input(in_path.to_str().unwrap()).unwrap();
+    assert_eq!(mesh.vertices.len(), n + 2);
+    assert_eq!(mesh.triangles.len(), n);
+    assert_eq!(mesh.triangles[n - 1], [n - 1, n, n + 1]);
+}
+
+/// Most rows reserved up front from a declared count, which a corrupt file may set arbitrarily high: the rows
+/// beyond it are still read, only without a reservation, while a huge count fails on the missing rows instead of
+/// on allocating room for them.
+const MAX_RESERVED_ROWS: usize = 1 << 16;
+
+/// Parse the native text format. Every face row holds exactly three indices, or with `polygons`
+/// any number of indices from three upwards; `make_face` turns the indices of a row into a face.
+/// If `coordinate_text` is given, the coordinate tokens of every point are pushed to it, separated by single spaces.
+/// With [`ParseOptions::no_counts`], the point and face counts are inferred instead of read, see [`ParseOptions`].
+///
+/// The input is read through a buffer one line at a time and parsed as it is read, so the whole
+/// text is never held in memory: peak memory stays close to the size of the parsed points and faces.
+pub(crate) fn parse_native<F: AsRef<[usize]>>(
+    reader: impl std::io::Read,
+    polygons: bool,
+    make_face: impl Fn(&[usize]) -> F,
+    options: &ParseOptions,
+    mut coordinate_text: Option<&mut Vec<String>>,
+) -> Result<(Vec<Vector3<f64>>, Vec<F>), ParseError> {
+    let mut lines = LineReader::new(std::io::BufReader::new(reader));
+    let mut point_rows = PointRows::default();
+    let mut parse_point = |line_no: usize, line: &str| -> Result<Vector3<f64>, ParseError> {
+        let point = point_rows.parse(line_no, line, options)?;
+        if let (Some(text), Some(layout)) = (coordinate_text.as_deref_mut(), point_rows.layout) {
+            let n_coords = if layout.planar { 2 } else { 3 };
+            text.push(split_fields(line).skip(usize::from(layout.indexed)).take(n_coords).collect::<Vec<_>>().join(" "));
+        }
+        Ok(point)
+    };
+    let mut face: Vec<usize> = Vec::with_capacity(3);
+    let mut parse_face = |line_no: usize, line: &str, point_rows: &PointRows| -> Result<F, ParseError> {
+        parse_face_row(line_no, line, polygons, options, &mut face)?;
+        point_rows.resolve(line_no, &mut face, options)?;
+        Ok(make_face(&face))
+    };
+
+    let mut vertices: Vec<Vector3<f64>> = Vec::new();
+    let mut faces: Vec<F> = Vec::new();
+    if options.no_counts {
+        // points run up to the first blank line or `faces` marker line, every later row is a face
+        while let Some((line_no, line)) = lines.next_raw_line()? {
+            let content = line.trim();
+            if content.eq_ignore_ascii_case("faces") || (content.is_empty() && !vertices.is_empty()) {
+                break;
+            }
+            if !is_skipped_line(line) {
+                vertices.push(parse_point(line_no, line)?);
+            }
+        }
+        while let Some((line_no, line)) = lines.next_content_line()? {
+            faces.push(parse_face(line_no, line, &point_rows)?);
+        }
+    } else {
+        let (line_no, line) = lines.next_line("number of points")?;
+        let n_points = parse_token::<usize>(Some(line.trim()), line_no, "number of points")?;
+        vertices.reserve(n_points.min(MAX_RESERVED_ROWS));
+        for _ in 0..n_points {
+            let (line_no, line) = lines.next_line("point coordinates")?;
+            vertices.push(parse_point(line_no, line)?);
+        }
+
+        let (face_count, face_row) = if polygons { ("number of faces", "face indices") } else { ("number of triangles", "triangle indices") };
+        let (line_no, line) = lines.next_line(face_count)?;
+        let n_faces = parse_token::<usize>(Some(line.trim()), line_no, face_count)?;
+        faces.reserve(n_faces.min(MAX_RESERVED_ROWS));
+        for _ in 0..n_faces {
+            let (line_no, line) = lines.next_line(face_row)?;
+            faces.push(parse_face(line_no, line, &point_rows)?);
+        }
+        // rows beyond the declared counts mean the counts and the data disagree
+        if let Some((line_no, _)) = lines.next_content_line()? {
+            let what = if polygons { "faces" } else { "triangles" };
+            return Err(ParseError::Syntax { line: line_no, reason: format!("unexpected row after the {} declared {}", n_faces, what) });
+        }
+    }
+
+    validate_faces(vertices.len(), &faces)?;
+    Ok((vertices, faces))
+}
+
+#[test]
+fn test_parse_input_number_forms() {
+    let mesh = parse_input("tests/number_forms.txt").unwrap();
+    assert_eq!(mesh.vertices[0], Vector3::new(1.5e-3, 2.0, 0.5));
+    assert_eq!(mesh.vertices[1], Vector3::new(1000.0, -0.25, 5.0));
+    assert_eq!(mesh.vertices[2], Vector3::new(0.75, -25.0, 0.0));
+    assert_eq!(mesh.vertices[3], Vector3::new(0.0, 1.0, 0.0));
+
+    let options = ParseOptions::default();
+    let err = PointRows::default().parse(2, "1.2.3 0 0", &options).unwrap_err();
+    assert_eq!(err, ParseError::InvalidNumber { line: 2, reason: String::from("invalid x coordinate '1.2.3'") });
+    for token in ["1e", "e3", "++1", "1..5", "0x10", "."] {
+        assert!(matches!(PointRows::default().parse(2, &format!("0 {} 0", token), &options), Err(ParseError::InvalidNumber { line: 2, .. })), "{}", token);
+    }
+}
+
+#[test]
+fn test_parse_input_indexed_points() {
+    let options = ParseOptions { indexed_points: true, ..ParseOptions::default() };
+    let mesh = parse_input_with("tests/indexed_points.txt", &options).unwrap();
+    assert_eq!(mesh.vertices, parse_input("tests/reordered.txt").unwrap().vertices.into_iter().rev().collect::<Vec<_>>());
+    // the triangle rows refer to the points of reordered.txt through their indices
+    assert_eq!(mesh.triangles, vec![[3, 2, 1], [3, 1, 0], [3, 0, 2], [2, 0, 1]]);
+
+    // without the option, the rows hold one value too many
+    assert!(matches!(parse_input("tests/indexed_points.txt"), Err(ParseError::Syntax { line: 3, .. })));
+
+    let mut point_rows = PointRows::default();
+    point_rows.parse(2, "7 0 0 0", &options).unwrap();
+    assert_eq!(point_rows.parse(3, "7 1 0 0", &options), Err(ParseError::Syntax { line: 3, reason: String::from("point index 7 is given twice") }));
+    assert_eq!(point_rows.resolve(9, &mut [7, 7, 8], &options), Err(ParseError::InvalidNumber { line: 9, reason: String::from("no point has index 8") }));
+
+    // the messages give the indices as written in the file
+    let based = ParseOptions { index_base: 1, ..options };
+    let mut point_rows = PointRows::default();
+    point_rows.parse(2, "7 0 0 0", &based).unwrap();
+    assert_eq!(point_rows.resolve(9, &mut [6, 6, 7], &based), Err(ParseError::InvalidNumber { line: 9, reason: String::from("no point has index 8") }));
+}
+
+#[test]
+fn test_parse_point_rows_layout() {
+    let options = ParseOptions::default();
+    let mut point_rows = PointRows::default();
+    assert_eq!(point_rows.parse(2, "1 2", &options), Ok(Vector3::new(1.0, 2.0, 0.0)));
+    assert_eq!(point_rows.parse(3, "1 2 3", &options), Err(ParseError::Syntax { line: 3, reason: String::from("point row has 3 values, but the first point row has 2") }));
+    let mut point_rows = PointRows::default();
+    point_rows.parse(2, "1 2 3", &options).unwrap();
+    assert!(matches!(point_rows.parse(3, "1 2", &options), Err(ParseError::MissingValue { line: 3, .. })));
+    assert!(matches!(point_rows.parse(4, "1 2 3 4", &options), Err(ParseError::Syntax { line: 4, .. })));
+
+    // an indexed file read without the option is rejected rather than read with the index as x
+    let err = PointRows::default().parse(2, "0 0 0 0", &options).unwrap_err();
+    assert_eq!(err, ParseError::Syntax { line: 2, reason: String::from("point row has 4 values, expected at most 3; rows that start with the index of the point need --indexed-points") });
+    let indexed = ParseOptions { indexed_points: true, ..ParseOptions::default() };
+    assert!(matches!(PointRows::default().parse(2, "0 0 0 0 0", &indexed), Err(ParseError::Syntax { line: 2, .. })));
+
+    let planar = ParseOptions { planar: true, ..ParseOptions::default() };
+    let mut point_rows = PointRows::default();
+    assert_eq!(point_rows.parse(2, "1 2 0", &planar), Ok(Vector3::new(1.0, 2.0, 0.0)));
+    assert_eq!(point_rows.parse(3, "1 2 0.5", &planar), Err(ParseError::Syntax { line: 3, reason: String::from("planar point has a z coordinate of '0.5'") }));
+}
+
+/// How the point rows of a native file are laid out, decided by the first row.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct PointLayout {
+    /// Two coordinates per point, see [`ParseOptions::planar`].
+    planar: bool,
+    /// A leading point index, see [`ParseOptions::indexed_points`].
+    indexed: bool,
+    /// The number of values on every point row.
+    fields: usize,
+}
+
+/// The state of the point rows read so far: their layout and, for indexed rows, the position of every point by index.
+#[derive(Debug, Default)]
+struct PointRows {
+    layout: Option<PointLayout>,
+    positions: HashMap<usize, usize>,
+    count: usize,
+}
+
+impl PointRows {
+    /// Parse a point row of the native format, made of three coordinates, or of two in a planar file, whose points
+    /// get a z coordinate of 0, after the index of the point in an indexed file. The first point row decides
+    /// whether the file is planar and indexed, see [`ParseOptions::planar`] and [`ParseOptions::indexed_points`],
+    /// and every later point row must hold as many values as it does. A planar point may carry a z coordinate of 0,
+    /// and a row holding more values than a point has is rejected.
+    fn parse(&mut self, line_no: usize, line: &str, options: &ParseOptions) -> Result<Vector3<f64>, ParseError> {
+        let n_fields = split_fields(line).count();
+        let layout = *self.layout.get_or_insert_with(|| {
+            let indexed = options.indexed_points;
+            PointLayout { planar: options.planar || n_fields == 2 + usize::from(indexed), indexed, fields: n_fields }
+        });
+        // a planar point may still carry its zero z coordinate
+        let max_fields = 3 + usize::from(layout.indexed);
+        if n_fields > max_fields {
+            let hint = if layout.indexed { "" } else { "; rows that start with the index of the point need --indexed-points" };
+            return Err(ParseError::Syntax { line: line_no, reason: format!("point row has {} values, expected at most {}{}", n_fields, max_fields, hint) });
+        }
+        let mut coords = split_fields(line);
+        if layout.indexed {
+            let index = parse_token::<usize>(coords.next(), line_no, "point index")?;
+            let index = index.checked_sub(options.index_base).ok_or_else(|| ParseError::InvalidNumber {
+                line: line_no,
+                reason: format!("point index {} is below the index base {}", index, options.index_base),
+            })?;
+            if self.positions.insert(index, self.count).is_some() {
+                return Err(ParseError::Syntax { line: line_no, reason: format!("point index {} is given twice", index + options.index_base) });
+            }
+        }
+        let n_coords = if layout.planar { 2 } else { 3 };
+        let mut point = Vector3::new(0.0, 0.0, 0.0);
+        for (k, what) in ["x coordinate", "y coordinate", "z coordinate"].into_iter().enumerate().take(n_coords) {
+            let token = coords.next();
+            point[k] = parse_token::<f64>(token, line_no, what)?;
+            if !point[k].is_finite() && !options.allow_nonfinite {
+                return Err(ParseError::InvalidNumber { line: line_no, reason: format!("non-finite {} '{}'", what, token.unwrap_or_default()) });
+            }
+        }
+        if options.planar {
+            if let Some(token) = coords.next() {
+                if parse_token::<f64>(Some(token), line_no, "z coordinate")? != 0.0 {
+                    return Err(ParseError::Syntax { line: line_no, reason: format!("planar point has a z coordinate of '{}'", token) });
+                }
+            }
+        }
+        if n_fields != layout.fields {
+            return Err(ParseError::Syntax { line: line_no, reason: format!("point row has {} values, but the first point row has {}", n_fields, layout.fields) });
+        }
+        self.count += 1;
+        Ok(point)
+    }
+
+    /// Replace the point indices of a face row of an indexed file by the positions of the points.
+    /// Faces of a file without point indices are left as they are. The face holds the indices already converted from
+    /// [`ParseOptions::index_base`], which the error message converts back.
+    fn resolve(&self, line_no: usize, face: &mut [usize], options: &ParseOptions) -> Result<(), ParseError> {
+        if !self.layout.is_some_and(|layout| layout.indexed) {
+            return Ok(());
+        }
+        for index in face {
+            *index = *self.positions.get(index).ok_or_else(|| ParseError::InvalidNumber { line: line_no, reason: format!("no point has index {}", *index + options.index_base) })?;
+        }
+        Ok(())
+    }
+}
+
+/// Parse a face row of the native format into `face`, converting every index from [`ParseOptions::index_base`] to 0-based.
+/// A triangle row holds exactly three indices, a polygon row at least three.
+fn parse_face_row(line_no: usize, line: &str, polygons: bool, options: &ParseOptions, face: &mut Vec<usize>) -> Result<(), ParseError> {
+    face.clear();
+    for token in split_fields(line) {
+        let index = parse_token::<usize>(Some(token), line_no, "point index")?;
+        face.push(index.checked_sub(options.index_base).ok_or_else(|| ParseError::InvalidNumber {
+            line: line_no,
+            reason: format!("point index {} is below the index base {}", index, options.index_base),
+        })?);
+    }
+    if face.len() < 3 || (!polygons && face.len() > 3) {
+        return Err(ParseError::WrongIndexCount { line: line_no, count: face.len(), polygon: polygons });
+    }
+    Ok(())
+}
+
+#[test]
+fn test_parse_split_input() {
+    let options = ParseOptions::default();
+    let expected = parse_input("tests/input.txt").unwrap();
+    assert_eq!(parse_split_input("tests/split_vertices.txt", "tests/split_faces.txt", &options).unwrap(), expected);
+    // the count lines are optional in both files
+    assert_eq!(parse_split_input("tests/split_vertices_counted.txt", "tests/split_faces_counted.txt", &options).unwrap(), expected);
+}
+
+/// Parse a mesh whose points and triangles are kept in separate files, for pipelines sharing one vertex
+/// file between many face files. The vertex file holds the point rows of the native format and the
+/// face file its triangle rows, each optionally preceded by a line holding just the number of rows.
+/// Options apply as in [`parse_input_with`], except [`ParseOptions::no_counts`], since the counts are always optional.
+pub fn parse_split_input(vertices_path: &str, faces_path: &str, options: &ParseOptions) -> Result<Mesh, ParseError> {
+    let mut point_rows = PointRows::default();
+    let vertices = parse_rows(open_input(vertices_path)?, ("number of points", "point coordinates"), |line_no, line| {
+        point_rows.parse(line_no, line, options)
+    })?;
+    let mut face: Vec<usize> = Vec::with_capacity(3);
+    let triangles = parse_rows(open_input(faces_path)?, ("number of triangles", "triangle indices"), |line_no, line| {
+        parse_face_row(line_no, line, false, options, &mut face)?;
+        point_rows.resolve(line_no, &mut face, options)?;
+        Ok([face[0], face[1], face[2]])
+    })?;
+    validate_faces(vertices.len(), &triangles)?;
+    Ok(Mesh { vertices, triangles, colors: None, groups: None })
+}
+
+/// Parse every row of a file with `parse_row`. A first line holding a single field is the number of rows,
+/// which are then read up to that count; otherwise every line that is neither blank nor a comment is a row.
+/// `what` describes the count and the rows for the error messages.
+fn parse_rows<T>(reader: impl std::io::Read, what: (&str, &str), mut parse_row: impl FnMut(usize, &str) -> Result<T, ParseError>) -> Result<Vec<T>, ParseError> {
+    let (count_what, row_what) = what;
+    let mut lines = LineReader::new(std::io::BufReader::new(reader));
+    let mut rows: Vec<T> = Vec::new();
+    let Some((line_no, line)) = lines.next_content_line()? else {
+        return Ok(rows);
+    };
+    if split_fields(line).count() == 1 {
+        let n_rows = parse_token::<usize>(Some(line), line_no, count_what)?;
+        rows.reserve(n_rows.min(MAX_RESERVED_ROWS));
+        for _ in 0..n_rows {
+            let (line_no, line) = lines.next_line(row_what)?;
+            rows.push(parse_row(line_no, line)?);
+        }
+    } else {
+        rows.push(parse_row(line_no, line)?);
+        while let Some((line_no, line)) = lines.next_content_line()? {
+            rows.push(parse_row(line_no, line)?);
+        }
+    }
+    Ok(rows)
+}
+
+#[test]
+fn test_compute_centroid() {
+    let mesh = Mesh {
+        vertices: vec![
+            Vector3::new(0.0, 0.0, 0.0), 
+            Vector3::new(0.0, 0.0, 1.0), 
+            Vector3::new(0.0, 1.0, 0.0), 
+            Vector3::new(1.0, 0.0, 0.0)
+        ],
+        triangles: vec![],
+        colors: None,
+        groups: None,
+    };
+    let centroid = compute_centroid(&mesh);
+    assert_eq!(centroid, Vector3::new(0.25, 0.25, 0.25));
+}
+
+#[test]
+fn test_compute_centroid_far_from_origin() {
+    // summing the points first would reach 1e12, where the offset of 0.123 is off by about 1e-5 after dividing
+    let point = Vector3::new(1e7 + 0.123, -1e7 + 0.123, 0.123);
+    let mesh = Mesh { vertices: vec![point; 100_000], triangles: vec![], colors: None, groups: None };
+    let centroid = compute_centroid(&mesh);
+    assert!((centroid - point).norm() < 1e-9);
+}
+
+/// Compute the centroid of the vertices of a mesh.
+/// The mean is updated one point at a time, see [`mean_point`], so that it stays accurate for meshes far from the origin.
+pub fn compute_centroid(mesh: &Mesh) -> Vector3<f64> {
+    mean_point(&mesh.vertices)
+}
+
+/// Mean of a list of points, computed as a running mean: every point moves the mean by its difference to the
+/// mean so far, divided by the number of points seen. Unlike summing the points and dividing at the end,
+/// the accumulator stays near the magnitude of the points, so large coordinates do not swamp small offsets.
+pub(crate) fn mean_point(points: &[Vector3<f64>]) -> Vector3<f64> {
+    if points.is_empty() {
+        return Vector3::repeat(f64::NAN);
+    }
+    let mut mean = Vector3::new(0.0, 0.0, 0.0);
+    for (i, point) in points.iter().enumerate() {
+        mean += (point - mean) / (i + 1) as f64;
+    }
+    mean
+}
+
+#[test]
+fn test_compute_area_weighted_centroid() {
+    // a unit cube whose top face is subdivided into a 10 x 10 grid of quads
+    let mut mesh = parse_obj("tests/cube.obj").unwrap();
+    mesh.triangles.retain(|triangle| triangle.iter().any(|&i| mesh.vertices[i].z < 1.0));
+    let n = 10;
+    let first = mesh.vertices.len();
+    for i in 0..=n {
+        for j in 0..=n {
+            mesh.vertices.push(Vector3::new(i as f64 / n as f64, j as f64 / n as f64, 1.0));
+        }
+    }
+    let grid = |i: usize, j: usize| first + i * (n + 1) + j;
+    for i in 0..n {
+        for j in 0..n {
+            mesh.triangles.push([grid(i, j), grid(i + 1, j), grid(i + 1, j + 1)]);
+            mesh.triangles.push([grid(i, j), grid(i + 1, j + 1), grid(i, j + 1)]);
+        }
+    }
+
+    let area_centroid = compute_area_weighted_centroid(&mesh);
+    assert!((area_centroid - Vector3::new(0.5, 0.5, 0.5)).norm() < 1e-12);
+    assert!(compute_centroid(&mesh).z > 0.9);
+
+    // a surface without area, or without triangles, falls back to the vertex centroid
+    let flat = Mesh { vertices: vec![Vector3::new(0.0, 0.0, 0.0), Vector3::new(1.0, 0.0, 0.0), Vector3::new(2.0, 0.0, 0.0)], triangles: vec![[0, 1, 2]], ..Mesh::default() };
+    assert_eq!(compute_area_weighted_centroid(&flat), Vector3::new(1.0, 0.0, 0.0));
+    let points = Mesh { triangles: Vec::new(), ..flat };
+    assert_eq!(compute_area_weighted_centroid(&points), Vector3::new(1.0, 0.0, 0.0));
+}
+
+/// Compute the centroid of the surface described by the mesh.
+/// The centroid of every triangle is weighted by the triangle area, so that the result does not
+/// depend on how densely each region of the surface is sampled by vertices. A mesh whose triangles
+/// have no area falls back to [`compute_centroid`].
+pub fn compute_area_weighted_centroid(mesh: &Mesh) -> Vector3<f64> {
+    let mut weighted_sum = Vector3::new(0.0, 0.0, 0.0);
+    let mut total_area = 0.0;
+    for triangle in &mesh.triangles {
+        let [v0, v1, v2] = triangle.map(|i| mesh.vertices[i]);
+        let area = (v1 - v0).cross(&(v2 - v0)).norm() / 2.0;
+        weighted_sum += (v0 + v1 + v2) / 3.0 * area;
+        total_area += area;
+    }
+    if total_area == 0.0 {
+        return compute_centroid(mesh);
+    }
+    weighted_sum / total_area
+}
+
+#[test]
+fn test_compute_triangle_norm_vec_direction() {
+    let points = vec![
+        Vector3::new(0.0, 0.0, 0.0), 
+        Vector3::new(0.0, 0.0, 1.0), 
+        Vector3::new(0.0, 1.0, 0.0), 
+        Vector3::new(1.0, 0.0, 0.0)
+    ];
+    let triangle = [0, 1, 2];
+    let centroid = Vector3::new(0.25, 0.25, 0.25);
+    let outwards : bool = compute_triangle_norm_vec_direction(&points, &triangle, &centroid);
+    assert!(outwards);
+}
+
+/// Compute the direction of the normal vector of a triangle with respect to the centroid of the meshgrid.
+/// The normal vector is computed as the cross product of the vectors described by the indices 0->1 and 0->2.
+/// The direction of the normal vector is determined by the dot product of the normal vector and the vector from the centroid to the first point of the triangle.
+pub fn compute_triangle_norm_vec_direction(points: &[Vector3<f64>], triangle: &[usize; 3], centroid: &Vector3<f64>) -> bool {
+    compute_triangle_direction_with(points, triangle, centroid, NormalBackend::CrossProduct)
+}
+
+/// Same as [`compute_triangle_norm_vec_direction`], with the normal vector computed by `backend`.
+pub fn compute_triangle_direction_with(points: &[Vector3<f64>], triangle: &[usize; 3], centroid: &Vector3<f64>, backend: NormalBackend) -> bool {
+    let norm_vec = backend.normal(&triangle.map(|i| points[i]));
+    let centroid_to_triangle = points[triangle[0]] - *centroid;
+    let dot_prod = norm_vec.dot(&centroid_to_triangle);
+    dot_prod > 0.0
+}
+
+/// Orientation of a triangle with respect to a centroid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Orientation {
+    /// The normal points away from the centroid.
+    Outward,
+    /// The normal points towards the centroid.
+    Inward,
+    /// The normal is perpendicular to the centroid ray, within the tolerance, or undefined.
+    Ambiguous,
+}
+
+/// Classify a triangle by the dot product of its normal, computed with `backend`, and the vector from the centroid
+/// to its first point, as in [`compute_triangle_direction_with`]. Dot products within `[-epsilon, epsilon]`,
+/// such as those of triangles whose plane contains the centroid, are [`Orientation::Ambiguous`].
+pub fn classify_triangle(points: &[Vector3<f64>], triangle: &[usize; 3], centroid: &Vector3<f64>, backend: NormalBackend, epsilon: f64) -> Orientation {
+    let (_, dot_product) = centroid_dot_product(points, triangle, centroid, backend);
+    orientation_from_dot_product(dot_product, epsilon)
+}
+
+/// The normal of the triangle, computed with `backend`, and its dot product with the vector from the centroid to the first point.
+fn centroid_dot_product(points: &[Vector3<f64>], triangle: &[usize; 3], centroid: &Vector3<f64>, backend: NormalBackend) -> (Vector3<f64>, f64) {
+    let norm_vec = backend.normal(&triangle.map(|i| points[i]));
+    let dot_prod = norm_vec.dot(&(points[triangle[0]] - *centroid));
+    (norm_vec, dot_prod)
+}
+
+/// Classify a dot product computed by [`centroid_dot_product`], see [`classify_triangle`].
+fn orientation_from_dot_product(dot_prod: f64, epsilon: f64) -> Orientation {
+    if dot_prod > epsilon {
+        Orientation::Outward
+    } else if dot_prod < -epsilon {
+        Orientation::Inward
+    } else {
+        Orientation::Ambiguous
+    }
+}
+
+/// Method used to compute the (unnormalized) normal vector of a face.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum NormalBackend {
+    /// The cross product of the vectors described by the indices 0->1 and 0->2.
+    #[default]
+    CrossProduct,
+    /// Newell's method, see [`newell_normal`].
+    Newell,
+}
+
+impl NormalBackend {
+    /// Compute the normal vector of the face through `points`.
+    /// The cross product only looks at the first three points.
+    pub fn normal(&self, points: &[Vector3<f64>]) -> Vector3<f64> {
+        match self {
+            NormalBackend::CrossProduct => (points[1] - points[0]).cross(&(points[2] - points[0])),
+            NormalBackend::Newell => newell_normal(points),
+        }
+    }
+}
+
+#[test]
+fn test_newell_normal() {
+    // on a well-conditioned triangle both methods agree
+    let points = [Vector3::new(0.0, 0.0, 0.0), Vector3::new(1.0, 0.0, 0.0), Vector3::new(0.0, 1.0, 0.0)];
+    let cross = NormalBackend::CrossProduct.normal(&points);
+    assert!((newell_normal(&points).normalize() - cross.normalize()).norm() < 1e-15);
+
+    // on a long sliver the cross product depends on which vertex comes first, Newell's method barely does
+    let sliver = [
+        Vector3::new(0.1, 0.2, 0.3),
+        Vector3::new(1000.1, 1000.2 + 1e-7, 0.3),
+        Vector3::new(2000.1, 2000.2 + 3e-7, 0.3 + 1e-9),
+    ];
+    let spread = |backend: NormalBackend| {
+        let normals: Vec<Vector3<f64>> = (0..3)
+            .map(|r| backend.normal(&[sliver[r], sliver[(r + 1) % 3], sliver[(r + 2) % 3]]).normalize())
+            .collect();
+        (0..3).map(|r| (normals[r] - normals[(r + 1) % 3]).norm()).fold(0.0, f64::max)
+    };
+    assert!(spread(NormalBackend::Newell) < spread(NormalBackend::CrossProduct) / 10.0);
+    assert!(newell_normal(&sliver).dot(&cross) > 0.0);
+}
+
+/// Compute the (unnormalized) normal vector of a polygon with Newell's method.
+/// The normal is accumulated over every edge of the vertex loop, taken relative to the mean of the points,
+/// so all vertices contribute. This is robust for near-degenerate and non-planar faces.
+pub fn newell_normal(points: &[Vector3<f64>]) -> Vector3<f64> {
+    let center = points.iter().sum::<Vector3<f64>>() / points.len() as f64;
+    let mut normal = Vector3::new(0.0, 0.0, 0.0);
+    for k in 0..points.len() {
+        let current = points[k] - center;
+        let next = points[(k + 1) % points.len()] - center;
+        normal.x += (current.y - next.y) * (current.z + next.z);
+        normal.y += (current.z - next.z) * (current.x + next.x);
+        normal.z += (current.x - next.x) * (current.y + next.y);
+    }
+    normal
+}
+
+#[test]
+fn test_triangle_normal() {
+    let mesh = parse_input("tests/input.txt").unwrap();
+    assert_eq!(triangle_normal(&mesh, &[0, 3, 2]), Vector3::new(0.0, 0.0, 1.0));
+    assert_eq!(triangle_normal(&mesh, &[0, 2, 3]), Vector3::new(0.0, 0.0, -1.0));
+}
+
+/// Compute the unit normal of a triangle as the normalized cross product of the vectors described by the indices 0->1 and 0->2.
+pub fn triangle_normal(mesh: &Mesh, triangle: &[usize; 3]) -> Vector3<f64> {
+    let [v0, v1, v2] = triangle.map(|i| mesh.vertices[i]);
+    (v1 - v0).cross(&(v2 - v0)).normalize()
+}
+
+#[test]
+fn test_compute_vertex_normals() {
+    // point 0 is the right-angled corner of a triangle facing +z and the sharp tip of a sliver facing -x
+    let mesh = Mesh {
+        vertices: vec![
+            Vector3::new(0.0, 0.0, 0.0),
+            Vector3::new(1.0, 0.0, 0.0),
+            Vector3::new(0.0, 1.0, 0.0),
+            Vector3::new(0.0, 0.0, 1.0),
+            Vector3::new(0.0, 0.1, 1.0),
+        ],
+        triangles: vec![[0, 1, 2], [0, 3, 4]],
+        colors: None,
+        groups: None,
+    };
+    let normals = compute_vertex_normals(&mesh);
+    let expected = (Vector3::new(0.0, 0.0, 1.0) * std::f64::consts::FRAC_PI_2 + Vector3::new(-1.0, 0.0, 0.0) * 0.1f64.atan()).normalize();
+    assert!((normals[0] - expected).norm() < 1e-12);
+    // uniform accumulation would give the sliver as much say as the corner, tilting the normal by 45 degrees
+    assert!(normals[0].z > 0.99);
+    assert_eq!(normals[1], Vector3::new(0.0, 0.0, 1.0));
+    assert_eq!(normals[3], Vector3::new(-1.0, 0.0, 0.0));
+}
+
+/// Compute the unit normal of every point, the sum of the unit normals of its triangles, each weighted
+/// by the angle of the triangle at the point. Unlike uniform or area weighting, the result does not
+/// depend on how the surface around the point is split into triangles.
+/// Degenerate triangles are ignored, and points of no other triangle get a zero normal.
+pub fn compute_vertex_normals(mesh: &Mesh) -> Vec<Vector3<f64>> {
+    let mut normals = vec![Vector3::new(0.0, 0.0, 0.0); mesh.vertices.len()];
+    for triangle in &mesh.triangles {
+        let points = triangle.map(|i| mesh.vertices[i]);
+        let Some(normal) = (points[1] - points[0]).cross(&(points[2] - points[0])).try_normalize(0.0) else {
+            continue;
+        };
+        for k in 0..3 {
+            let angle = (points[(k + 1) % 3] - points[k]).angle(&(points[(k + 2) % 3] - points[k]));
+            normals[triangle[k]] += normal * angle;
+        }
+    }
+    normals.into_iter().map(|normal| normal.try_normalize(0.0).unwrap_or_default()).collect()
+}
+
+#[test]
+fn test_write_normals_degenerate() {
+    let out_path = std::env::temp_dir().join("mesh_reorienter_degenerate_normals.txt");
+    let out_path = out_path.to_str().unwrap();
+
+    let mut mesh = parse_input("tests/reordered.txt").unwrap();
+    mesh.triangles.push([0, 1, 1]);
+    assert_eq!(write_normals(out_path, &mesh, PrecisionSpec::uniform(2)), vec![4]);
+    let contents = std::fs::read_to_string(out_path).unwrap();
+    assert!(!contents.contains("NaN"));
+    assert_eq!(contents.lines().last(), Some("0.00 0.00 0.00"));
+}
+
+/// Write the unit normal of every triangle, in the order of the triangle list.
+/// The file starts with the number of normals, followed by one `<nx> <ny> <nz>` line per triangle.
+/// Degenerate triangles, see [`find_degenerate_triangles`], have no normal and are written as `0 0 0` rather than NaN.
+/// Returns their indices, in increasing order.
+pub fn write_normals(out_path: &str, mesh: &Mesh, precision: PrecisionSpec) -> Vec<usize> {
+    let degenerate = find_degenerate_triangles(mesh, cleanup::DEFAULT_DEGENERATE_EPSILON);
+    let normals: Vec<Vector3<f64>> = mesh.triangles
+        .iter()
+        .enumerate()
+        .map(|(t, triangle)| if degenerate.binary_search(&t).is_ok() { Vector3::zeros() } else { triangle_normal(mesh, triangle) })
+        .collect();
+    write_vectors(out_path, &normals, precision);
+    degenerate
+}
+
+/// Write the vertex normals of [`compute_vertex_normals`] in the layout of [`write_normals`], one line per point.
+pub fn write_vertex_normals(out_path: &str, mesh: &Mesh, precision: PrecisionSpec) {
+    write_vectors(out_path, &compute_vertex_normals(mesh), precision);
+}
+
+/// Write one line per triangle holding `1` if `flipped` marks it as flipped and `0` otherwise.
+pub fn write_flip_mask(out_path: &str, flipped: &[bool]) {
+    let out_contents: String = flipped.iter().map(|&flipped| if flipped { "1\n" } else { "0\n" }).collect();
+    write_file(out_path, |writer| writer.write_all(out_contents.as_bytes()))
+        .expect("Something went wrong writing the file");
+}
+
+/// Write a single `<x> <y> <z>` line with the shortest coordinates that parse back to exactly `point`.
+pub fn write_point(out_path: &str, point: &Vector3<f64>) {
+    write_file(out_path, |writer| writeln!(writer, "{} {} {}", point.x, point.y, point.z))
+        .expect("Something went wrong writing the file");
+}
+
+/// Write the number of vectors followed by one `<x> <y> <z>` line per vector.
+fn write_vectors(out_path: &str, vectors: &[Vector3<f64>], precision: PrecisionSpec) {
+    let mut out_contents = String::new();
+    out_contents.push_str(&vectors.len().to_string());
+    out_contents.push('\n');
+    for vector in vectors {
+        out_contents.push_str(&precision.format_point(vector));
+        out_contents.push('\n');
+    }
+    write_file(out_path, |writer| writer.write_all(out_contents.as_bytes()))
+        .expect("Something went wrong writing the file");
+}
+
+#[test]
+fn test_signed_volume() {
+    let mut mesh = parse_input("tests/input.txt").unwrap();
+    make_winding_consistent(&mut mesh);
+    assert!((signed_volume(&mesh).abs() - 1.0 / 6.0).abs() < 1e-15);
+
+    orient_outward(&mut mesh);
+    assert!((signed_volume(&mesh) - 1.0 / 6.0).abs() < 1e-15);
+}
+
+/// Compute the signed volume enclosed by the mesh using the divergence theorem,
+/// summing `dot(v0, cross(v1, v2)) / 6` over all triangles.
+/// The volume is positive when the triangles are wound counter-clockwise as seen from outside, that is when
+/// their normals point outwards, and negative when they point inwards. The mesh should be closed and
+/// consistently wound, otherwise the result is not a volume; a positive value then doubles as a quick check
+/// that the mesh is oriented outwards.
+pub fn signed_volume(mesh: &Mesh) -> f64 {
+    mesh.triangles
+        .iter()
+        .map(|triangle| {
+            let [v0, v1, v2] = triangle.map(|i| mesh.vertices[i]);
+            v0.dot(&v1.cross(&v2)) / 6.0
+        })
+        .sum()
+}
+
+/// The triangle rotated so that it starts at its smallest index, which keeps its winding.
+fn rotate_to_smallest(mut triangle: [usize; 3]) -> [usize; 3] {
+    let first = (0..3).min_by_key(|&k| triangle[k]).unwrap_or_default();
+    triangle.rotate_left(first);
+    triangle
+}
+
+#[test]
+fn test_meshes_approx_equal() {
+    let mesh = parse_input("tests/reordered.txt").unwrap();
+    let mut rounded = mesh.clone();
+    rounded.vertices[1].z += 4e-7;
+    rounded.vertices[3].x -= 4e-7;
+    // the same triangles in another order, some starting from another corner
+    rounded.triangles = vec![[2, 0, 1], [1, 3, 2], [0, 2, 3], [3, 1, 0]];
+    assert!(meshes_approx_equal(&mesh, &rounded, 1e-6));
+    assert!(!meshes_approx_equal(&mesh, &rounded, 1e-7));
+
+    // reversing a triangle changes its winding, which no rotation undoes
+    let mut flipped = mesh.clone();
+    flipped.triangles[0] = [0, 2, 1];
+    assert!(!meshes_approx_equal(&mesh, &flipped, 1e-6));
+    let mut fewer_points = mesh.clone();
+    fewer_points.vertices.pop();
+    assert!(!meshes_approx_equal(&mesh, &fewer_points, 1e-6));
+}
+
+/// Whether two meshes are equal up to rounding: their points match one for one within `eps` on every axis, and
+/// they have the same triangles, in any order and each starting from any of its corners, but with the same winding.
+/// Colors and groups are not compared. This suits checking meshes written with a limited precision and read back.
+pub fn meshes_approx_equal(a: &Mesh, b: &Mesh, eps: f64) -> bool {
+    let close = a.vertices.len() == b.vertices.len() && a.vertices.iter().zip(&b.vertices).all(|(p, q)| (p - q).amax() <= eps);
+    let sorted_triangles = |mesh: &Mesh| {
+        let mut triangles: Vec<[usize; 3]> = mesh.triangles.iter().map(|&triangle| rotate_to_smallest(triangle)).collect();
+        triangles.sort_unstable();
+        triangles
+    };
+    close && sorted_triangles(a) == sorted_triangles(b)
+}
+
+/// Number of decimals written for each coordinate axis.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PrecisionSpec {
+    pub x: usize,
+    pub y: usize,
+    pub z: usize,
+    /// Count significant figures rather than decimals, choosing the decimals of each coordinate from its
+    /// magnitude so that the relative error stays bounded whatever the scale of the mesh.
+    pub significant: bool,
+}
+
+#[test]
+fn test_precision_significant_figures() {
+    let precision = PrecisionSpec::significant(5);
+    assert_eq!(precision.format_point(&Vector3::new(12345.678, 0.00012345, -1.5)), "12346 0.00012345 -1.5000");
+    assert_eq!(PrecisionSpec::significant(8).format_point(&Vector3::new(12345.678, 0.000123456789, 0.0)), "12345.678 0.00012345679 0");
+    // the integer part is always written in full, even when it has more digits than asked for
+    assert_eq!(PrecisionSpec::significant(2).format_point(&Vector3::new(12345.678, 0.99, f64::NAN)), "12346 0.99 NaN");
+}
+
+impl PrecisionSpec {
+    /// The same number of decimals on every axis.
+    pub fn uniform(precision: usize) -> Self {
+        PrecisionSpec { x: precision, y: precision, z: precision, significant: false }
+    }
+
+    /// The same number of significant figures on every axis.
+    pub fn significant(figures: usize) -> Self {
+        PrecisionSpec { significant: true, ..PrecisionSpec::uniform(figures) }
+    }
+
+    /// Number of decimals to write `value` with, for an axis of the given precision.
+    /// With significant figures, a zero or non-finite value has no magnitude and gets no decimals.
+    pub fn decimals(&self, value: f64, precision: usize) -> usize {
+        if !self.significant {
+            return precision;
+        }
+        if value == 0.0 || !value.is_finite() {
+            return 0;
+        }
+        let exponent = value.abs().log10().floor() as i64;
+        (precision as i64 - 1 - exponent).max(0) as usize
+    }
+
+    /// Format a point as space-separated coordinates, each with the precision of its axis.
+    pub fn format_point(&self, point: &Vector3<f64>) -> String {
+        let [x, y, z] = [(point.x, self.x), (point.y, self.y), (point.z, self.z)].map(|(value, precision)| format!("{:.*}", self.decimals(value, precision), value));
+        format!("{} {} {}", x, y, z)
+    }
+}
+
+impl std::str::FromStr for PrecisionSpec {
+    type Err = std::num::ParseIntError;
+
+    /// Parse either a single number applied to all axes, such as `3`, or a comma-separated triple such as `2,2,5`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split(',').map(|part| part.trim().parse::<usize>()).collect::<Result<Vec<usize>, _>>()?[..] {
+            [x, y, z] => Ok(PrecisionSpec { x, y, z, significant: false }),
+            // fall back to parsing the whole string, so that anything but one or three numbers is an error
+            _ => s.trim().parse::<usize>().map(PrecisionSpec::uniform),
+        }
+    }
+}
+
+#[test]
+fn test_write_output_per_axis_precision() {
+    let out_path = std::env::temp_dir().join("mesh_reorienter_precision.txt");
+    let out_path = out_path.to_str().unwrap();
+    let mesh = Mesh { vertices: vec![Vector3::new(1.0, 2.0, 3.0)], triangles: vec![], colors: None, groups: None };
+
+    let precision: PrecisionSpec = "1,2,5".parse().unwrap();
+    write_output(out_path, &mesh, precision);
+    let written = std::fs::read_to_string(out_path).unwrap();
+    assert_eq!(written.lines().nth(1), Some("1.0 2.00 3.00000"));
+
+    assert_eq!("3".parse::<PrecisionSpec>(), Ok(PrecisionSpec::uniform(3)));
+    assert!("1,2".parse::<PrecisionSpec>().is_err());
+}
+
+#[test]
+fn test_write_output_gzip() {
+    let out_path = std::env::temp_dir().join("mesh_reorienter_output.txt.gz");
+    let out_path = out_path.to_str().unwrap();
+
+    let mesh = parse_input("tests/input.txt").unwrap();
+    write_output(out_path, &mesh, PrecisionSpec::uniform(3));
+    let mut expected: Vec<u8> = Vec::new();
+    write_output_to(&mut expected, &mesh, PrecisionSpec::uniform(3)).unwrap();
+    assert_eq!(gzip::decompress(&std::fs::read(out_path).unwrap()).unwrap(), expected);
+}
+
+/// Write the output file with the same format as the input file.
+/// The point and triangle counts are taken from the lengths of the mesh vectors.
+/// A path ending in `.gz` is written gzip-compressed.
+pub fn write_output(out_path: &str, mesh: &Mesh, precision: PrecisionSpec) {
+    write_output_with_base(out_path, mesh, precision, 0);
+}
+
+#[test]
+fn test_write_output_index_base_round_trip() {
+    let out_path = std::env::temp_dir().join("mesh_reorienter_index_base.txt");
+    let out_path = out_path.to_str().unwrap();
+
+    let mesh = parse_input("tests/input.txt").unwrap();
+    write_output_with_base(out_path, &mesh, PrecisionSpec::uniform(1), 1);
+    let written = std::fs::read_to_string(out_path).unwrap();
+    assert_eq!(written.lines().nth(6), Some("1 2 3"));
+
+    let options = ParseOptions { index_base: 1, ..ParseOptions::default() };
+    assert_eq!(parse_input_with(out_path, &options).unwrap().triangles, mesh.triangles);
+    assert!(matches!(parse_input_with("tests/input.txt", &options), Err(ParseError::InvalidNumber { line: 7, .. })));
+}
+
+/// Write the mesh in the native text format like [`write_output`], numbering the points from `index_base`
+/// in the triangle rows. Read such a file back with the same base in [`ParseOptions::index_base`].
+pub fn write_output_with_base(out_path: &str, mesh: &Mesh, precision: PrecisionSpec, index_base: usize) {
+    write_file(out_path, |writer| write_output_to_with_base(writer, mesh, precision, index_base))
+        .expect("Something went wrong writing the file");
+}
+
+/// Create an output file and let `write` fill it. Files whose path ends in `.gz` are gzip-compressed,
+/// so every writer can produce compressed output.
+pub(crate) fn write_file(out_path: &str, write: impl FnOnce(&mut dyn std::io::Write) -> std::io::Result<()>) -> std::io::Result<()> {
+    if out_path == "-" {
+        let mut stdout = std::io::stdout().lock();
+        write(&mut stdout)?;
+        return std::io::Write::flush(&mut stdout);
+    }
+    let mut file = std::io::BufWriter::new(std::fs::File::create(out_path)?);
+    if gzip::is_gzip_path(out_path) {
+        let mut encoder = gzip::GzEncoder::new(file);
+        write(&mut encoder)?;
+        encoder.finish()?;
+        Ok(())
+    } else {
+        write(&mut file)?;
+        std::io::Write::flush(&mut file)
+    }
+}
+
+/// Write the mesh in the native text format to any writer, such as a file or standard output.
+pub fn write_output_to<W: std::io::Write + ?Sized>(writer: &mut W, mesh: &Mesh, precision: PrecisionSpec) -> std::io::Result<()> {
+    write_output_to_with_base(writer, mesh, precision, 0)
+}
+
+/// Write the mesh in the native text format to any writer, numbering the points from `index_base`, see [`write_output_with_base`].
+pub fn write_output_to_with_base<W: std::io::Write + ?Sized>(writer: &mut W, mesh: &Mesh, precision: PrecisionSpec, index_base: usize) -> std::io::Result<()> {
+    writer.write_all(native_contents(&mesh.vertices, &mesh.triangles, precision, index_base).as_bytes())
+}
+
+/// Write the mesh in the native text format, writing every point with its original coordinate text
+/// as returned by [`parse_input_preserving`] instead of reformatting it. A path ending in `.gz` is written gzip-compressed.
+/// The mesh must have the same points as when it was parsed; only the triangles may have changed.
+/// The points are numbered from `index_base` in the triangle rows, see [`write_output_with_base`].
+pub fn write_output_preserving(out_path: &str, mesh: &Mesh, coordinate_text: &[String], index_base: usize) {
+    assert_eq!(coordinate_text.len(), mesh.vertices.len(), "the coordinate text does not match the points of the mesh");
+    let out_contents = native_contents_with(coordinate_text, &mesh.triangles, index_base);
+    write_file(out_path, |writer| writer.write_all(out_contents.as_bytes()))
+        .expect("Something went wrong writing the file");
+}
+
+/// Format points and faces in the native text format, one row of indices numbered from `index_base` per face.
+pub(crate) fn native_contents<F: AsRef<[usize]>>(vertices: &[Vector3<f64>], faces: &[F], precision: PrecisionSpec, index_base: usize) -> String {
+    let point_rows: Vec<String> = vertices.iter().map(|point| precision.format_point(point)).collect();
+    native_contents_with(&point_rows, faces, index_base)
+}
+
+/// Format already formatted point rows and faces in the native text format.
+fn native_contents_with<F: AsRef<[usize]>>(point_rows: &[String], faces: &[F], index_base: usize) -> String {
+    let mut out_contents = String::new();
+    out_contents.push_str(&point_rows.len().to_string());
+    out_contents.push('\n');
+    for row in point_rows {
+        out_contents.push_str(row);
+        out_contents.push('\n');
+    }
+    out_contents.push_str(&faces.len().to_string());
+    out_contents.push('\n');
+    for face in faces {
+        let indices: Vec<String> = face.as_ref().iter().map(|index| (index + index_base).to_string()).collect();
+        out_contents.push_str(&indices.join(" "));
+        out_contents.push('\n');
+    }
+    out_contents
+}
@@ -0,0 +1,42 @@
+//! Diagnostic messages, such as the per-triangle decisions of [`crate::reorient_mesh_logged`],
+//! and progress reports of long operations, such as [`crate::reorient_mesh_with_progress`].
+//!
+//! The library never prints on its own; callers pass a [`Logger`] or a [`Progress`] and choose where
+//! the messages go. [`Silent`] discards them, which is what a run without `--verbose` uses.
+
+use std::io::Write;
+
+/// Destination of diagnostic messages.
+pub trait Logger {
+    /// Record one message, given without a trailing newline.
+    fn log(&mut self, message: &str);
+}
+
+/// A logger that discards every message.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Silent;
+
+impl Logger for Silent {
+    fn log(&mut self, _message: &str) {}
+}
+
+impl Progress for Silent {
+    fn advance(&self, _done: usize) {}
+}
+
+/// Receiver of progress reports. Work running on several threads reports to it concurrently.
+pub trait Progress: Sync {
+    /// Record that `done` more items were processed.
+    fn advance(&self, done: usize);
+}
+
+/// A logger that writes every message as one line to the wrapped writer, such as standard error.
+/// Write errors are ignored, since losing a diagnostic line should not abort the run.
+#[derive(Debug)]
+pub struct WriterLogger<W: Write>(pub W);
+
+impl<W: Write> Logger for WriterLogger<W> {
+    fn log(&mut self, message: &str) {
+        let _ = writeln!(self.0, "{}", message);
+    }
+}
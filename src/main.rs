@@ -1,188 +1,637 @@
 use std::env;
+use std::io::IsTerminal;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Instant;
+
+mod cli;
+
+use cli::{Centroid, CliError, Command};
 use nalgebra::Vector3;
+use mesh_reorienter::cleanup::{DEFAULT_DEGENERATE_EPSILON, DEFAULT_WELD_EPSILON};
+use mesh_reorienter::{
+    analyze_edges, apply_polygon_winding, apply_winding, classify_triangle, compute_area_weighted_centroid, component_mesh, compute_centroid, compute_obb, compute_vertex_normals, copy_reference_winding,
+    count_algorithm_disagreements, drop_degenerate_triangles, drop_duplicate_triangles, find_clockwise_triangles, find_inconsistent_triangles, find_misoriented_by_component, find_misoriented_triangles, flip_all,
+    flip_triangles, formats, is_inside, is_inside_out, is_planar, make_polygon_winding_consistent, make_winding_consistent, only_winding_changed, orient_planar, orient_polygons_outward,
+    parse_input_preserving, parse_split_input, pick_interior_point, polygon_area_weighted_centroid, read_mesh, read_polygon_mesh, reorient_components, reorient_mesh_logged, reorient_mesh_with_epsilon,
+    reorient_mesh_with_progress, reorient_polygons, reorient_polygons_around, repair_mesh, split_components, weld_vertices, write_flip_mask, write_mesh, write_mesh_with_normals, write_normals,
+    write_output_preserving, write_polygon_mesh, write_point, write_vertex_normals, AreaReport, Mesh, MeshStats, Orientation, OrientedBox, ParseOptions, Progress, RepairReport, Topology, Winding, WriterLogger,
+};
 
 /// # Triangle meshgrid vertex reorienter.
-/// 
-/// Run the script as follows:
-/// ```shell
-/// cargo run --release input.txt output.txt
-/// ```
-/// where input.txt is the input file with the following format:
-/// ```
-/// <number of points>
-/// <x0> <y0> <z0>
-/// ...
-/// <number of triangles>
-/// <point_index0> <point_index1> <point_index2>
-/// ...
-/// ```
-/// and output.txt is the output file with the same format.
-/// The first line of the input file is the number of points in the meshgrid as an integer.
-/// the next n lines are the x, y, z coordinates of the points in the meshgrid.
-/// The next line is the number of triangles in the meshgrid as an integer.
-/// The next n lines are the indices of the points that form the triangles in the meshgrid. 
-/// The indices index the point list above.
-/// 
-/// the program works as follows:
-/// 1. compute the centroid of the surface described by the meshgrid. 
-/// 2. For each triangle, 
-///     1. compute the normal vector of the triangle as the cross product of the two vectors described by indices 0->1 and 0->2.
-///     2. Compare the normal to the centroid of the meshgrid. 
-///         - If the dot product of the normal and the vector from the centroid to the first point of the triangle is positive, the normal points outwards from the centroid and the triangle is correctly aligned.
-///         - If the dot product is negative, the normal points inwards from the centroid, and the order of the verteces must be reversed.
-/// 3. Once all triangles have been checked and rearranged, write the output file with the same format as the input file.
-/// 
-/// in this program we assume that the centroid of the meshgrid is contained inside the surface described by the triangle meshgrid.
-/// 
+///
+/// Reads a mesh, makes the winding of its triangles consistent and writes it back. The commands, options and file
+/// formats are described in [`cli::USAGE`], which `--help` prints.
 fn main() {
-    
-    let args: Vec<String> = env::args().collect();
-
-    let in_path = &args[1];
-    let out_path = &args[2];
-    // optional argument 3 should be desired precision in number of decimal numbers
-    let precision = args.get(3).unwrap_or(&String::from("1")).parse::<usize>().unwrap();
-
-    let (n_points, point_coords, n_triangles, mut triangle_specs) = parse_input(&in_path);
-
-    let centroid = compute_centroid(&point_coords, n_points);
-
-    for triangle in &mut triangle_specs {
-        let outwards = compute_triangle_norm_vec_direction(&point_coords, triangle, &centroid);
-        if !outwards {
-            triangle.swap(1, 2);
-        }
-    }
-    write_output(out_path, n_points, &point_coords, n_triangles, &triangle_specs, precision);
-}
-
-
-#[test]
-fn test_parse_input() {
-    use nalgebra::Vector3;
-
-    let in_path = "tests/input.txt";
-    let ( n_points, point_coords, n_triangles, triangle_specs)  = parse_input(in_path);
-    assert_eq!(n_points, 4);
-    assert_eq!(point_coords, vec![
-        Vector3::new(0.0, 0.0, 0.0), 
-        Vector3::new(0.0, 0.0, 1.0), 
-        Vector3::new(0.0, 1.0, 0.0), 
-        Vector3::new(1.0, 0.0, 0.0)
-    ]);
-    assert_eq!(n_triangles, 4);
-    assert_eq!(triangle_specs, vec![vec![0, 1, 2], vec![0, 3, 2], vec![0, 3, 1], vec![1, 2, 3]]);
-}
-
-/// Parse the input file and return the number of points, the point coordinates, the number of triangles and the triangle specifications.
-fn parse_input(in_path: &str) -> (usize,  Vec<Vector3<f64>>, usize, Vec<Vec<usize>>) { 
-    // read file and divide it in lines to be parsed later
-    let contents = std::fs::read_to_string(in_path)
-        .expect("Something went wrong reading the file");
-
-    let mut lines = contents.lines();
-    let n_points = lines.next().unwrap().parse::<usize>().unwrap();
-
-    let point_coords: Vec<Vector3<f64>> = lines
-        .by_ref()
-        .take(n_points)
-        .map(|line| {
-            let mut coords = line.split_whitespace();
-            let x = coords.next().unwrap().parse::<f64>().unwrap();
-            let y = coords.next().unwrap().parse::<f64>().unwrap();
-            let z = coords.next().unwrap().parse::<f64>().unwrap();
-            Vector3::new(x, y, z)
-        })
-        .collect();
-
-    let n_triangles = lines.next().unwrap().parse::<usize>().unwrap();
-
-    let triangle_specs: Vec<Vec<usize>> = lines
-    .map(|line| {
-        let indices = line.split_whitespace()
-            .map(|s| s.parse::<usize>().unwrap())
-            .collect();
-        indices
-    })
-    .collect();
-
-    return (n_points, point_coords, n_triangles, triangle_specs);
-    }
-
-
-#[test]
-fn test_compute_centroid() {
-    let points = vec![
-        Vector3::new(0.0, 0.0, 0.0), 
-        Vector3::new(0.0, 0.0, 1.0), 
-        Vector3::new(0.0, 1.0, 0.0), 
-        Vector3::new(1.0, 0.0, 0.0)
-    ];
-    let npoints : usize = 4;
-    let centroid = compute_centroid(&points, npoints);
-    assert_eq!(centroid, Vector3::new(0.25, 0.25, 0.25));
-}
-/// Compute the centroid of a set of points.
-/// This is done by summing all the points and dividing the result by the number of points.
-fn compute_centroid(points: &Vec<Vector3<f64>>, npoints: usize) -> Vector3<f64> {
-    let mut centroid = Vector3::new(0.0, 0.0, 0.0);
-    for i in 0..npoints {
-        centroid += points[i];
-    }
-    centroid /= npoints as f64;
-    return centroid;
-}
-
-#[test]
-fn test_compute_triangle_norm_vec_direction() {
-    let points = vec![
-        Vector3::new(0.0, 0.0, 0.0), 
-        Vector3::new(0.0, 0.0, 1.0), 
-        Vector3::new(0.0, 1.0, 0.0), 
-        Vector3::new(1.0, 0.0, 0.0)
-    ];
-    let triangle = vec![0, 1, 2];
-    let centroid = Vector3::new(0.25, 0.25, 0.25);
-    let outwards : bool = compute_triangle_norm_vec_direction(&points, &triangle, &centroid);
-    assert_eq!(outwards, true);
-}
-
-/// Compute the direction of the normal vector of a triangle with respect to the centroid of the meshgrid.
-/// The normal vector is computed as the cross product of the vectors described by the indices 0->1 and 0->2.
-/// The direction of the normal vector is determined by the dot product of the normal vector and the vector from the centroid to the first point of the triangle.
-fn compute_triangle_norm_vec_direction(points: &Vec<Vector3<f64>>, triangle: &Vec<usize>, centroid: &Vector3<f64>) -> bool {
-    let v1 = points[triangle[1]] - points[triangle[0]];
-    let v2 = points[triangle[2]] - points[triangle[0]];
-    let norm_vec = v1.cross(&v2);
-    let centroid_to_triangle = points[triangle[0]] - *centroid;
-    let dot_prod = norm_vec.dot(&centroid_to_triangle);
-    return dot_prod > 0.0;
-
-}
-
-/// Write the output file with the same format as the input file.
-fn write_output(out_path: &str, n_points: usize, point_coords: &Vec<Vector3<f64>>, n_triangles: usize, triangle_specs: &Vec<Vec<usize>>, precision: usize) {
-    let mut out_contents = String::new();
-    out_contents.push_str(&n_points.to_string());
-    out_contents.push_str("\n");
-    for i in 0..n_points {
-        out_contents.push_str(&format!("{:.*}", precision, point_coords[i].x));
-        out_contents.push_str(" ");
-        out_contents.push_str(&format!("{:.*}", precision, point_coords[i].x));
-        out_contents.push_str(" ");
-        out_contents.push_str(&format!("{:.*}", precision, point_coords[i].x));
-        out_contents.push_str("\n");
-    }
-    out_contents.push_str(&n_triangles.to_string());
-    out_contents.push_str("\n");
-    for i in 0..n_triangles {
-        out_contents.push_str(&triangle_specs[i][0].to_string());
-        out_contents.push_str(" ");
-        out_contents.push_str(&triangle_specs[i][1].to_string());
-        out_contents.push_str(" ");
-        out_contents.push_str(&triangle_specs[i][2].to_string());
-        out_contents.push_str("\n");
-    }
-    std::fs::write(out_path, out_contents)
-        .expect("Something went wrong writing the file");
-}
\ No newline at end of file
+    let args = match cli::parse_args(env::args().skip(1)) {
+        Ok(args) => args,
+        Err(CliError::Help) => {
+            println!("{}", cli::USAGE);
+            return;
+        }
+        Err(CliError::Usage(message)) => {
+            eprintln!("error: {}\n\n{}", message, cli::USAGE);
+            std::process::exit(2);
+        }
+    };
+
+    if args.polygons {
+        run_polygons(&args);
+        return;
+    }
+
+    let preserve_coords = args.preserve_coords && args.inputs.iter().all(|input| is_native_file(input, args.format.as_deref()))
+        && args.output.as_deref().is_none_or(|output| is_native_file(output, args.output_format.as_deref()));
+    if args.preserve_coords && !preserve_coords {
+        eprintln!("Warning: --preserve-coords only applies to native text files, the coordinates are reformatted");
+    }
+    if args.embed_normals && !args.output.as_deref().is_some_and(|output| holds_normals(output, args.output_format.as_deref())) {
+        eprintln!("Error: --embed-normals needs an OBJ or PLY output, the only formats holding normals;");
+        eprintln!("       use --emit-vertex-normals to write the normals to a separate file instead");
+        std::process::exit(1);
+    }
+    let timer = Timer { enabled: args.time };
+    let (mut mesh, coordinate_text) = timer.time("parsing", || read_inputs(&args, preserve_coords));
+    let mut reference_mesh = args.reference_mesh.as_deref().map(|reference_path| match read_mesh(reference_path, None, &ParseOptions::default()) {
+        Ok(reference_mesh) => reference_mesh,
+        Err(err) => {
+            eprintln!("Error parsing {}: {}", reference_path, err);
+            std::process::exit(1);
+        }
+    });
+
+    // the repair flips triangles before the triangles are kept for --stats, which then counts its flips from the report
+    let repair_report = (args.command == Command::Repair).then(|| repair_mesh(&mut mesh, args.weld.unwrap_or(DEFAULT_WELD_EPSILON)));
+    if let Some(report) = &repair_report {
+        print_repair_report(report);
+    } else if let Some(epsilon) = args.weld {
+        let removed = weld_vertices(&mut mesh, epsilon);
+        if removed > 0 {
+            eprintln!("Merged {} duplicate vertices", removed);
+        }
+    }
+    if args.drop_degenerate {
+        let dropped = drop_degenerate_triangles(&mut mesh, DEFAULT_DEGENERATE_EPSILON);
+        if dropped > 0 {
+            eprintln!("Warning: dropped {} degenerate triangles", dropped);
+        }
+    }
+    if args.dedup_triangles {
+        let dropped = drop_duplicate_triangles(&mut mesh, args.dedup_reversed);
+        if dropped > 0 {
+            eprintln!("Warning: dropped {} duplicate triangles", dropped);
+        }
+    }
+
+    if args.check_manifold {
+        let report = analyze_edges(&mesh);
+        if !report.boundary_edges.is_empty() {
+            eprintln!("Warning: {} boundary edges, the surface is not closed", report.boundary_edges.len());
+        }
+        if !report.non_manifold_edges.is_empty() {
+            eprintln!("Warning: {} non-manifold edges shared by three or more triangles:", report.non_manifold_edges.len());
+            for (a, b) in &report.non_manifold_edges {
+                eprintln!("edge {} {}", a, b);
+            }
+        }
+    }
+
+    if args.area_report {
+        print_area_report(AreaReport::compute(&mesh));
+    }
+
+    if args.obb {
+        print_obb(compute_obb(&mesh));
+    }
+
+    if args.topology {
+        let report = analyze_edges(&mesh);
+        if !report.boundary_edges.is_empty() || !report.non_manifold_edges.is_empty() {
+            eprintln!("Warning: the surface is not a closed manifold, so its genus is meaningless");
+        }
+        print_topology(Topology::compute(&mesh));
+    }
+
+    // the point given by --reference, or the one resolved for --dump-reference, kept apart from the parsed arguments,
+    // which tell what the user asked for
+    let mut reference = args.reference;
+    // resolving the reference point once, before any recentering, spares computing it again, or warning about it
+    // twice, in the stages below; it is written in the coordinates of the output, after any --scale and --translate
+    if let Some(reference_path) = &args.dump_reference {
+        if mesh.triangles.is_empty() {
+            eprintln!("Warning: the mesh has no triangles and no reference point, so {} was not written", reference_path);
+        } else if is_planar(&mesh) {
+            eprintln!("Warning: a planar mesh is oriented in its plane without a reference point, so {} was not written", reference_path);
+        } else if let Some(resolved) = selected_centroid(&mesh, &args, reference) {
+            let written = resolved.component_mul(&args.scale.unwrap_or_else(|| Vector3::repeat(1.0))) + args.translate.unwrap_or_else(Vector3::zeros);
+            write_point(reference_path, &written);
+            reference = Some(resolved);
+        }
+    }
+
+    if args.compare_algorithms && !mesh.triangles.is_empty() {
+        let centroid = selected_centroid(&mesh, &args, reference).unwrap_or_else(|| compute_centroid(&mesh));
+        let disagreements = count_algorithm_disagreements(&mesh, &centroid, args.normal_method);
+        eprintln!("the centroid approach and the flood fill disagree on {} of {} triangles", disagreements, mesh.triangles.len());
+    }
+
+    if args.command == Command::Stats {
+        print_stats(&MeshStats::compute(&mesh));
+        eprintln!("triangles that would be flipped: {}", planned_flips(&mesh, &args, reference, &timer).len());
+        return;
+    }
+
+    // only the analysis runs on the recentered points, the original ones are written back afterwards
+    let original_vertices = args.recenter.then(|| mesh.vertices.clone());
+    if args.recenter {
+        let center = mesh.recenter();
+        reference = reference.map(|reference| reference - center);
+        // the same subtraction keeps the points of the reference mesh bitwise equal to the matching points
+        for point in reference_mesh.iter_mut().flat_map(|reference_mesh| &mut reference_mesh.vertices) {
+            *point -= center;
+        }
+    }
+
+    if args.dry_run {
+        let flips = planned_flips(&mesh, &args, reference, &timer);
+        println!("{} of {} triangles would be flipped", flips.len(), mesh.triangles.len());
+        if !flips.is_empty() {
+            println!("{}", flips.iter().map(|t| t.to_string()).collect::<Vec<_>>().join(" "));
+        }
+        return;
+    }
+    if args.check {
+        let flips = planned_flips(&mesh, &args, reference, &timer);
+        if flips.is_empty() {
+            return;
+        }
+        eprintln!("{} of {} triangles are not consistently oriented:", flips.len(), mesh.triangles.len());
+        for t in flips {
+            let [a, b, c] = mesh.triangles[t];
+            eprintln!("triangle {}: {} {} {}", t, a, b, c);
+        }
+        std::process::exit(1);
+    }
+
+    if let Some(range) = &args.range {
+        if range.end > mesh.triangles.len() {
+            eprintln!("Error: range {}:{} exceeds the {} triangles of {}", range.start, range.end, mesh.triangles.len(), input_names(&args));
+            std::process::exit(1);
+        }
+    }
+
+    let original_triangles = (args.stats || args.stable_order || args.flip_mask.is_some() || args.max_flip_ratio.is_some()).then(|| mesh.triangles.clone());
+    // the number of triangles the reorientation flips on its own decision, leaving out the reversal of the whole mesh
+    // by --outward or --winding cw, which --max-flip-ratio does not hold against it
+    let n_decided = timer.time("reorientation", || {
+        if let Some(reference_mesh) = &reference_mesh {
+            let matched = copy_reference_winding(&mut mesh, reference_mesh);
+            let n_matched = matched.iter().filter(|&&matched| matched).count();
+            eprintln!("Kept the winding of {} of {} triangles found in the reference mesh", n_matched, mesh.triangles.len());
+            let (decided, reversed) = decided_flips(&mesh, &args, reference, &timer);
+            let n_decided = decided.iter().filter(|&&t| !matched[t]).count();
+            let flips: Vec<usize> = reversal_flips(decided, reversed, &mesh, &args).into_iter().filter(|&t| !matched[t]).collect();
+            flip_triangles(&mut mesh, &flips);
+            n_decided
+        } else if let Some(range) = &args.range {
+            let (decided, reversed) = decided_flips(&mesh, &args, reference, &timer);
+            let n_decided = decided.iter().filter(|t| range.contains(t)).count();
+            let flips = reversal_flips(decided, reversed, &mesh, &args);
+            flip_triangles(&mut mesh, &flips);
+            n_decided
+        } else if args.flip_all {
+            flip_all(&mut mesh);
+            mesh.triangles.len()
+        } else if !args.no_reorient {
+            let reversed = reorient(&mut mesh, &args, reference, &timer);
+            original_triangles.as_ref().map_or(0, |original| original.iter().zip(&mesh.triangles).filter(|(before, after)| (before != after) != reversed).count())
+        } else {
+            0
+        }
+    });
+    if let Some(original_vertices) = original_vertices {
+        mesh.vertices = original_vertices;
+    }
+    if let Some(factors) = args.scale {
+        mesh.scale(factors);
+    }
+    if let Some(offset) = args.translate {
+        mesh.translate(offset);
+    }
+
+    if args.stable_order && !original_triangles.as_deref().is_some_and(|original| only_winding_changed(original, &mesh.triangles)) {
+        eprintln!("Error: the triangles were reordered or changed beyond their winding, which --stable-order forbids");
+        std::process::exit(1);
+    }
+    // reorienting only ever changes the winding in place, so a triangle was flipped exactly when its row changed
+    let flipped: Option<Vec<bool>> = original_triangles.map(|original| original.iter().zip(&mesh.triangles).map(|(before, after)| before != after).collect());
+    if let Some(max_flip_ratio) = args.max_flip_ratio {
+        if n_decided as f64 > max_flip_ratio * mesh.triangles.len() as f64 {
+            eprintln!("Error: {} of {} triangles would be flipped, more than the --max-flip-ratio of {}; nothing was written", n_decided, mesh.triangles.len(), max_flip_ratio);
+            let mut hints = Vec::new();
+            // --outward already reverses an inside-out mesh, and only a reference point can lie outside the solid
+            if !args.outward {
+                hints.push("if the mesh is inside-out, use --flip-all");
+            }
+            if (args.centroid.is_some() || args.reference.is_some()) && !args.robust_reference {
+                hints.push("if the reference point lies outside the solid, use --robust-reference");
+            }
+            if !hints.is_empty() {
+                eprintln!("       {}", hints.join("; "));
+            }
+            std::process::exit(1);
+        }
+    }
+    if let Some(flipped) = flipped.as_ref().filter(|_| args.stats) {
+        print_stats(&MeshStats::compute(&mesh));
+        let n_flipped = flipped.iter().filter(|&&flipped| flipped).count();
+        eprintln!("flipped triangles: {}", n_flipped + repair_report.map_or(0, |report| report.flipped_triangles()));
+    }
+
+    if args.double_sided {
+        mesh.make_double_sided();
+    }
+    if args.sort_triangles {
+        mesh.sort_triangles();
+    }
+
+    timer.time("writing", || {
+        match &coordinate_text {
+            Some(coordinate_text) => write_output_preserving(output_path(&args), &mesh, coordinate_text, args.index_base),
+            None if args.split_output => {
+                for (k, component) in split_components(&mesh).iter().enumerate() {
+                    let part = component_mesh(&mesh, component);
+                    let part_path = numbered_path(output_path(&args), k);
+                    if args.embed_normals {
+                        write_mesh_with_normals(&part_path, &part, &compute_vertex_normals(&part), args.precision, args.output_format.as_deref());
+                    } else {
+                        write_mesh(&part_path, &part, args.precision, args.index_base, args.output_format.as_deref());
+                    }
+                }
+            }
+            None if args.embed_normals => write_mesh_with_normals(output_path(&args), &mesh, &compute_vertex_normals(&mesh), args.precision, args.output_format.as_deref()),
+            None => write_mesh(output_path(&args), &mesh, args.precision, args.index_base, args.output_format.as_deref()),
+        }
+        if let Some(normals_path) = &args.emit_normals {
+            let degenerate = write_normals(normals_path, &mesh, args.precision);
+            if !degenerate.is_empty() {
+                eprintln!("Warning: {} degenerate triangles have no normal and were written as 0 0 0", degenerate.len());
+            }
+        }
+        if let Some(normals_path) = &args.emit_vertex_normals {
+            write_vertex_normals(normals_path, &mesh, args.precision);
+        }
+        if let (Some(mask_path), Some(flipped)) = (&args.flip_mask, &flipped) {
+            write_flip_mask(mask_path, flipped);
+        }
+    });
+}
+
+/// Wall-clock timing of the stages of a run, printed to standard error by `--time`.
+/// When disabled, [`Timer::time`] only runs the stage, without reading the clock.
+struct Timer {
+    enabled: bool,
+}
+
+impl Timer {
+    /// Run one stage of the processing, printing how long it took if the timer is enabled.
+    fn time<T>(&self, stage: &str, run: impl FnOnce() -> T) -> T {
+        if !self.enabled {
+            return run();
+        }
+        let start = Instant::now();
+        let result = run();
+        eprintln!("{}: {:.3} ms", stage, start.elapsed().as_secs_f64() * 1e3);
+        result
+    }
+}
+
+/// Progress bar of the reorientation, drawn on standard error by `--progress`.
+/// It is only enabled when standard error is a terminal, so that redirected logs hold no redrawn lines.
+struct ProgressBar {
+    enabled: bool,
+    total: usize,
+    done: AtomicUsize,
+    /// Percentage shown by the last redraw, so that the bar is only redrawn when it changes.
+    shown_percent: AtomicUsize,
+    start: Instant,
+}
+
+impl ProgressBar {
+    /// Width of the bar in characters, without the counts and the estimated time left.
+    const WIDTH: usize = 30;
+
+    fn new(enabled: bool, total: usize) -> Self {
+        ProgressBar { enabled, total, done: AtomicUsize::new(0), shown_percent: AtomicUsize::new(0), start: Instant::now() }
+    }
+
+    /// End the line of the bar once the work is done.
+    fn finish(&self) {
+        if self.enabled && self.shown_percent.load(Ordering::Relaxed) > 0 {
+            eprintln!();
+        }
+    }
+}
+
+impl Progress for ProgressBar {
+    fn advance(&self, done: usize) {
+        if !self.enabled {
+            return;
+        }
+        let done = self.done.fetch_add(done, Ordering::Relaxed) + done;
+        let percent = done * 100 / self.total.max(1);
+        if self.shown_percent.fetch_max(percent, Ordering::Relaxed) >= percent {
+            return;
+        }
+        let remaining = self.start.elapsed().as_secs_f64() * (self.total - done) as f64 / done as f64;
+        let filled = Self::WIDTH * percent / 100;
+        eprint!("\rreorienting [{}{}] {:3}% {}/{} triangles, {:.0} s left", "#".repeat(filled), " ".repeat(Self::WIDTH - filled), percent, done, self.total, remaining);
+    }
+}
+
+/// Read and merge every input mesh, together with the original coordinate text of its points if `preserve_coords` is set.
+fn read_inputs(args: &cli::Args, preserve_coords: bool) -> (Mesh, Option<Vec<String>>) {
+    let options = ParseOptions { allow_nonfinite: args.allow_nonfinite, index_base: args.input_index_base, no_counts: args.no_counts, planar: args.planar, indexed_points: args.indexed_points };
+    if let Some((vertices_path, faces_path)) = &args.split_input {
+        return match parse_split_input(vertices_path, faces_path, &options) {
+            Ok(mesh) => (mesh, None),
+            Err(err) => {
+                eprintln!("Error parsing {}: {}", input_names(args), err);
+                std::process::exit(1);
+            }
+        };
+    }
+    let mut mesh = Mesh::default();
+    let mut coordinate_text = preserve_coords.then(Vec::new);
+    for input in &args.inputs {
+        let parsed = if preserve_coords {
+            parse_input_preserving(input, &options).map(|(mesh, coordinate_text)| (mesh, Some(coordinate_text)))
+        } else {
+            read_mesh(input, args.format.as_deref(), &options).map(|mesh| (mesh, None))
+        };
+        match parsed {
+            Ok((part, part_text)) => {
+                mesh.append(part);
+                coordinate_text.iter_mut().zip(part_text).for_each(|(text, part_text)| text.extend(part_text));
+            }
+            Err(err) => {
+                eprintln!("Error parsing {}: {}", input, err);
+                std::process::exit(1);
+            }
+        }
+    }
+    (mesh, coordinate_text)
+}
+
+/// The input paths, for messages about the whole input.
+fn input_names(args: &cli::Args) -> String {
+    match &args.split_input {
+        Some((vertices_path, faces_path)) => format!("{} and {}", vertices_path, faces_path),
+        None => args.inputs.join(", "),
+    }
+}
+
+/// Reorient the whole mesh with the options in `args` and the `reference` point, see [`selected_centroid`].
+/// Returns whether `--outward` or `--winding cw` reversed the whole mesh once its triangles were oriented.
+fn reorient(mesh: &mut Mesh, args: &cli::Args, reference: Option<Vector3<f64>>, timer: &Timer) -> bool {
+    // without triangles there is nothing to orient, and no centroid to compute
+    if mesh.triangles.is_empty() {
+        return false;
+    }
+    if is_planar(mesh) {
+        orient_planar(mesh);
+    } else if args.components || args.split_output {
+        let n_components = reorient_components(mesh, args.normal_method, |part| component_reference(part, args, reference));
+        eprintln!("Found {} components", n_components);
+    } else {
+        reorient_whole(mesh, args, reference, timer);
+    }
+    let inside_out = args.outward && is_inside_out(mesh);
+    if inside_out {
+        flip_all(mesh);
+    }
+    apply_winding(mesh, args.winding);
+    inside_out != (args.winding == Winding::Clockwise)
+}
+
+/// Reorient the mesh as a single surface, against one reference point or by flood fill.
+fn reorient_whole(mesh: &mut Mesh, args: &cli::Args, reference: Option<Vector3<f64>>, timer: &Timer) {
+    match (timer.time("centroid", || selected_centroid(mesh, args, reference)), args.ambiguous_epsilon) {
+        (Some(centroid), epsilon) if args.verbose => {
+            let ambiguous = reorient_mesh_logged(mesh, &centroid, args.normal_method, epsilon, &mut WriterLogger(std::io::stderr()));
+            if !ambiguous.is_empty() {
+                eprintln!("Warning: left {} ambiguous triangles unchanged", ambiguous.len());
+            }
+        }
+        (Some(centroid), Some(epsilon)) => {
+            let ambiguous = reorient_mesh_with_epsilon(mesh, &centroid, args.normal_method, epsilon);
+            if !ambiguous.is_empty() {
+                eprintln!("Warning: left {} ambiguous triangles unchanged", ambiguous.len());
+            }
+        }
+        (Some(centroid), None) => {
+            let progress = ProgressBar::new(args.progress && std::io::stderr().is_terminal(), mesh.triangles.len());
+            reorient_mesh_with_progress(mesh, &centroid, args.normal_method, &progress);
+            progress.finish();
+        }
+        (None, _) => make_winding_consistent(mesh),
+    }
+}
+
+/// Reorient a polygon mesh, keeping faces with more than three vertices intact.
+fn run_polygons(args: &cli::Args) {
+    let input = &args.inputs[0];
+    let mut mesh = match read_polygon_mesh(input, args.format.as_deref()) {
+        Ok(mesh) => mesh,
+        Err(err) => {
+            eprintln!("Error parsing {}: {}", input, err);
+            std::process::exit(1);
+        }
+    };
+    let degenerate = mesh.collapse_repeated_vertices();
+    if !degenerate.is_empty() {
+        eprintln!("Warning: {} faces have fewer than three distinct vertices and no normal:", degenerate.len());
+        for f in degenerate {
+            eprintln!("face {}: {}", f, mesh.faces[f].iter().map(|i| i.to_string()).collect::<Vec<_>>().join(" "));
+        }
+    }
+
+    if !args.no_reorient && !mesh.faces.is_empty() {
+        match args.centroid {
+            Some(Centroid::Vertex) => reorient_polygons(&mut mesh),
+            Some(Centroid::Area) => {
+                let centroid = polygon_area_weighted_centroid(&mesh);
+                reorient_polygons_around(&mut mesh, &centroid);
+            }
+            None => make_polygon_winding_consistent(&mut mesh),
+        }
+        if args.outward {
+            orient_polygons_outward(&mut mesh);
+        }
+        apply_polygon_winding(&mut mesh, args.winding);
+    }
+
+    write_polygon_mesh(output_path(args), &mesh, args.precision, args.output_format.as_deref());
+}
+
+/// Print the statistics of a mesh to standard error.
+fn print_stats(stats: &MeshStats) {
+    eprintln!("vertices: {}", stats.vertex_count);
+    eprintln!("triangles: {}", stats.triangle_count);
+    eprintln!("surface area: {}", stats.surface_area);
+    eprintln!("signed volume: {}", stats.signed_volume);
+    match stats.bounding_box {
+        Some((min, max)) => eprintln!("bounding box: ({}, {}, {}) to ({}, {}, {})", min.x, min.y, min.z, max.x, max.y, max.z),
+        None => eprintln!("bounding box: empty"),
+    }
+    if stats.components.len() > 1 {
+        for (k, component) in stats.components.iter().enumerate() {
+            eprintln!("component {}: {} triangles, surface area {}, signed volume {}", k, component.triangle_count, component.surface_area, component.signed_volume);
+        }
+    }
+}
+
+/// Print how many fixes each step of the repair command applied to standard error.
+fn print_repair_report(report: &RepairReport) {
+    eprintln!("merged vertices: {}", report.merged_vertices);
+    eprintln!("dropped degenerate triangles: {}", report.degenerate_triangles);
+    eprintln!("flipped inconsistent triangles: {}", report.inconsistent_triangles);
+    eprintln!("flipped inside-out triangles: {}", report.inside_out_triangles);
+}
+
+/// Print the triangle area distribution requested by `--area-report` to standard error.
+fn print_area_report(report: Option<AreaReport>) {
+    let Some(report) = report else {
+        eprintln!("triangle areas: no triangles");
+        return;
+    };
+    eprintln!("smallest triangle area: {} (triangle {})", report.min, report.smallest);
+    eprintln!("largest triangle area: {} (triangle {})", report.max, report.largest);
+    eprintln!("mean triangle area: {}", report.mean);
+    eprintln!("triangle area standard deviation: {}", report.std_dev);
+}
+
+/// Print the oriented bounding box requested by `--obb` to standard error.
+fn print_obb(obb: Option<OrientedBox>) {
+    let Some((center, axes, extents)) = obb else {
+        eprintln!("oriented bounding box: empty");
+        return;
+    };
+    eprintln!("oriented bounding box center: ({}, {}, {})", center.x, center.y, center.z);
+    for (k, axis) in axes.iter().enumerate() {
+        eprintln!("oriented bounding box axis {}: ({}, {}, {}), side length {}", k + 1, axis.x, axis.y, axis.z, extents[k]);
+    }
+}
+
+/// Print the counts, Euler characteristic and genus requested by `--topology` to standard error.
+fn print_topology(topology: Topology) {
+    eprintln!("vertices: {}", topology.vertex_count);
+    eprintln!("edges: {}", topology.edge_count);
+    eprintln!("faces: {}", topology.face_count);
+    eprintln!("Euler characteristic: {}", topology.euler_characteristic);
+    match topology.genus() {
+        Some(genus) => eprintln!("genus: {}", genus),
+        None => eprintln!("genus: none, no closed orientable surface has this Euler characteristic"),
+    }
+}
+
+/// The reference point selected by `--reference`, `--robust-reference` or `--centroid`, if any. `reference` is the
+/// point of `--reference`, or the one already resolved for `--dump-reference`, in the coordinates of `mesh`.
+fn selected_centroid(mesh: &Mesh, args: &cli::Args, reference: Option<Vector3<f64>>) -> Option<Vector3<f64>> {
+    if let Some(reference) = reference {
+        return Some(reference);
+    }
+    if args.robust_reference {
+        return Some(pick_interior_point(mesh));
+    }
+    let centroid = match args.centroid {
+        Some(Centroid::Vertex) => compute_centroid(mesh),
+        Some(Centroid::Area) => compute_area_weighted_centroid(mesh),
+        None => return None,
+    };
+    // the centroid approach assumes the centroid is enclosed, a parity ray tells when it is not
+    if !mesh.triangles.is_empty() && !is_inside(mesh, &centroid) {
+        eprintln!("Warning: the centroid lies outside the surface, so comparing against it orients triangles wrongly;");
+        eprintln!("         use --robust-reference to reorient against a point found inside the solid instead");
+    }
+    Some(centroid)
+}
+
+/// The reference point of a connected component for `--components` and `--split-output`, the vertex centroid unless another one is selected.
+fn component_reference(part: &Mesh, args: &cli::Args, reference: Option<Vector3<f64>>) -> Vector3<f64> {
+    selected_centroid(part, args, reference).unwrap_or_else(|| compute_centroid(part))
+}
+
+/// Indices of the triangles that reorienting with the options in `args` would flip, leaving the mesh untouched.
+fn planned_flips(mesh: &Mesh, args: &cli::Args, reference: Option<Vector3<f64>>, timer: &Timer) -> Vec<usize> {
+    let (decided, reversed) = decided_flips(mesh, args, reference, timer);
+    reversal_flips(decided, reversed, mesh, args)
+}
+
+/// Indices of the triangles that the reorientation with the options in `args` flips on its own decision, and whether
+/// `--outward` or `--winding cw` then reverses the whole mesh. `--range` is not applied.
+fn decided_flips(mesh: &Mesh, args: &cli::Args, reference: Option<Vector3<f64>>, timer: &Timer) -> (Vec<usize>, bool) {
+    if mesh.triangles.is_empty() {
+        return (Vec::new(), false);
+    }
+    let flips: Vec<usize> = if is_planar(mesh) {
+        find_clockwise_triangles(mesh)
+    } else if args.components || args.split_output {
+        find_misoriented_by_component(mesh, args.normal_method, |part| component_reference(part, args, reference))
+    } else {
+        match (timer.time("centroid", || selected_centroid(mesh, args, reference)), args.ambiguous_epsilon) {
+            (Some(centroid), Some(epsilon)) => (0..mesh.triangles.len())
+                .filter(|&t| classify_triangle(&mesh.vertices, &mesh.triangles[t], &centroid, args.normal_method, epsilon) == Orientation::Inward)
+                .collect(),
+            (Some(centroid), None) => find_misoriented_triangles(mesh, &centroid, args.normal_method),
+            (None, _) => find_inconsistent_triangles(mesh),
+        }
+    };
+    let mut reversed = false;
+    if args.outward {
+        let mut reoriented = mesh.clone();
+        flip_triangles(&mut reoriented, &flips);
+        reversed = is_inside_out(&reoriented);
+    }
+    if args.winding == Winding::Clockwise {
+        reversed = !reversed;
+    }
+    (flips, reversed)
+}
+
+/// The triangles flipped once the whole mesh is `reversed` after flipping the `decided` ones, within `--range`.
+fn reversal_flips(mut flips: Vec<usize>, reversed: bool, mesh: &Mesh, args: &cli::Args) -> Vec<usize> {
+    if reversed {
+        // every triangle gets flipped once more, so exactly the untouched ones end up flipped
+        flips = (0..mesh.triangles.len()).filter(|t| flips.binary_search(t).is_err()).collect();
+    }
+    if let Some(range) = &args.range {
+        flips.retain(|t| range.contains(t));
+    }
+    flips
+}
+
+/// The output path, which the command line only leaves out for a dry run or a check.
+fn output_path(args: &cli::Args) -> &str {
+    args.output.as_deref().expect("the output path is only optional with --dry-run or --check")
+}
+
+/// The path of the `k`-th file written by `--split-output`: `path` with `_k` inserted before its extension,
+/// or before the extension preceding `.gz`, or appended if it has none.
+fn numbered_path(path: &str, k: usize) -> String {
+    let (path, gz) = path.strip_suffix(".gz").map_or((path, ""), |path| (path, ".gz"));
+    let file_start = path.rfind('/').map_or(0, |slash| slash + 1);
+    match path[file_start..].rfind('.').filter(|&dot| dot > 0) {
+        Some(dot) => format!("{}_{}{}{}", &path[..file_start + dot], k, &path[file_start + dot..], gz),
+        None => format!("{}_{}{}", path, k, gz),
+    }
+}
+
+/// Whether a mesh written to `path` can hold a normal per point, which OBJ and PLY files do.
+fn holds_normals(path: &str, format: Option<&str>) -> bool {
+    matches!(format.map(str::to_string).or_else(|| formats::extension(path)).as_deref(), Some("obj" | "ply"))
+}
+
+/// Whether a path, other than standard input or output, is read and written in the native text format.
+fn is_native_file(path: &str, format: Option<&str>) -> bool {
+    path != "-" && !matches!(format.map(str::to_string).or_else(|| formats::extension(path)).as_deref(), Some("obj" | "off" | "ply" | "stl" | "bin" | "json"))
+}
@@ -0,0 +1,213 @@
+//! Wavefront OBJ support.
+
+use nalgebra::Vector3;
+
+use crate::{parse_token, validate_faces, Mesh, ParseError, PolygonMesh, PrecisionSpec};
+
+/// The OBJ group and material a face was declared under, by the latest `g` and `usemtl` statements before it.
+/// `None` stands for no statement yet, or one without a name.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FaceGroup {
+    pub group: Option<String>,
+    pub material: Option<String>,
+}
+
+#[test]
+fn test_parse_obj() {
+    let mesh = parse_obj("tests/cube.obj").unwrap();
+    assert_eq!(mesh.vertices.len(), 8);
+    assert_eq!(mesh.triangles.len(), 12);
+    assert_eq!(mesh.triangles[0], [0, 3, 2]);
+    assert_eq!(mesh.triangles[1], [0, 2, 1]);
+}
+
+/// Parse a Wavefront OBJ file.
+/// `v x y z` lines are read as vertices and `f i j k ...` lines as faces; every other line is ignored.
+/// Face indices are 1-based and may carry texture/normal references (`f 1/1/1 2/2/2 3/3/3`), which are dropped.
+/// Negative face indices count back from the latest vertex read, `-1` being that vertex.
+/// Faces with more than three vertices are triangulated as a fan around their first vertex.
+/// If the file has `g` or `usemtl` statements, the group and material of every triangle are kept in [`Mesh::groups`].
+pub fn parse_obj(in_path: &str) -> Result<Mesh, ParseError> {
+    Ok(parse_obj_polygons(in_path)?.triangulate())
+}
+
+/// Parse a Wavefront OBJ file like [`parse_obj`], keeping faces with more than three vertices intact.
+pub fn parse_obj_polygons(in_path: &str) -> Result<PolygonMesh, ParseError> {
+    let contents = crate::read_input_to_string(in_path)?;
+
+    let mut vertices: Vec<Vector3<f64>> = Vec::new();
+    let mut faces: Vec<Vec<usize>> = Vec::new();
+    let mut groups: Vec<FaceGroup> = Vec::new();
+    let mut current = FaceGroup::default();
+    let mut has_groups = false;
+    for (i, line) in contents.lines().enumerate() {
+        let line_no = i + 1;
+        let mut tokens = line.split_whitespace();
+        match tokens.next() {
+            Some("v") => {
+                let x = parse_token::<f64>(tokens.next(), line_no, "x coordinate")?;
+                let y = parse_token::<f64>(tokens.next(), line_no, "y coordinate")?;
+                let z = parse_token::<f64>(tokens.next(), line_no, "z coordinate")?;
+                vertices.push(Vector3::new(x, y, z));
+            }
+            Some("f") => {
+                let face = tokens
+                    .map(|token| parse_face_index(token, vertices.len(), line_no))
+                    .collect::<Result<Vec<usize>, ParseError>>()?;
+                if face.len() < 3 {
+                    return Err(ParseError::MissingValue { line: line_no, reason: String::from("face has fewer than three vertices") });
+                }
+                faces.push(face);
+                groups.push(current.clone());
+            }
+            Some(keyword @ ("g" | "usemtl")) => {
+                let name = tokens.collect::<Vec<&str>>().join(" ");
+                let name = (!name.is_empty()).then_some(name);
+                if keyword == "g" {
+                    current.group = name;
+                } else {
+                    current.material = name;
+                }
+                has_groups = true;
+            }
+            _ => {}
+        }
+    }
+
+    validate_faces(vertices.len(), &faces)?;
+    Ok(PolygonMesh { vertices, faces, colors: None, groups: has_groups.then_some(groups) })
+}
+
+#[test]
+fn test_parse_obj_negative_indices() {
+    let mesh = parse_obj("tests/negative_indices.obj").unwrap();
+    let expected = crate::parse_input("tests/reordered.txt").unwrap();
+    assert_eq!(mesh.vertices, expected.vertices);
+    assert_eq!(mesh.triangles, expected.triangles);
+
+    assert_eq!(parse_face_index("-2/5", 3, 1), Ok(1));
+    assert!(matches!(parse_face_index("-4", 3, 7), Err(ParseError::InvalidNumber { line: 7, .. })));
+}
+
+/// Parse a single OBJ face vertex reference such as `3`, `3/1` or `3/1/2` into a 0-based vertex index.
+/// A negative index counts back from the last of the `n_vertices` vertices read so far.
+fn parse_face_index(token: &str, n_vertices: usize, line_no: usize) -> Result<usize, ParseError> {
+    let vertex = token.split('/').next();
+    let index = parse_token::<i64>(vertex, line_no, "vertex index")?;
+    match index {
+        0 => Err(ParseError::InvalidNumber { line: line_no, reason: String::from("vertex index 0 is not valid in OBJ, indices are 1-based") }),
+        1.. => Ok(index as usize - 1),
+        _ => n_vertices.checked_sub(index.unsigned_abs() as usize).ok_or_else(|| ParseError::InvalidNumber {
+            line: line_no,
+            reason: format!("vertex index {} counts back past the first of the {} vertices read so far", index, n_vertices),
+        }),
+    }
+}
+
+#[test]
+fn test_write_obj_round_trip() {
+    let out_path = std::env::temp_dir().join("mesh_reorienter_cube.obj");
+    let out_path = out_path.to_str().unwrap();
+
+    let mesh = parse_obj("tests/cube.obj").unwrap();
+    write_obj(out_path, &mesh, PrecisionSpec::uniform(6));
+    let written = parse_obj(out_path).unwrap();
+    assert_eq!(written.vertices, mesh.vertices);
+    assert_eq!(written.triangles, mesh.triangles);
+}
+
+#[test]
+fn test_obj_groups_round_trip() {
+    let out_path = std::env::temp_dir().join("mesh_reorienter_materials.obj");
+    let out_path = out_path.to_str().unwrap();
+
+    let mut mesh = parse_obj("tests/materials.obj").unwrap();
+    let steel = FaceGroup { group: Some("caps".into()), material: Some("steel".into()) };
+    let rubber = FaceGroup { group: Some("sides".into()), material: Some("rubber".into()) };
+    let expected: Vec<FaceGroup> = [vec![steel; 4], vec![rubber; 8]].concat();
+    assert_eq!(mesh.groups.as_ref(), Some(&expected));
+
+    crate::reorient_mesh(&mut mesh);
+    write_obj(out_path, &mesh, PrecisionSpec::uniform(6));
+    let written = parse_obj(out_path).unwrap();
+    assert_eq!(written.triangles, mesh.triangles);
+    assert_eq!(written.groups, Some(expected));
+    assert!(parse_obj("tests/cube.obj").unwrap().groups.is_none());
+}
+
+/// Write the mesh as a Wavefront OBJ file with one `v` line per vertex and one 1-based `f` line per triangle.
+/// With [`Mesh::groups`], `g` and `usemtl` statements are written wherever the group or material changes.
+pub fn write_obj(out_path: &str, mesh: &Mesh, precision: PrecisionSpec) {
+    crate::write_file(out_path, |writer| writer.write_all(obj_contents(&mesh.vertices, &mesh.triangles, mesh.groups.as_deref(), None, precision).as_bytes()))
+        .expect("Something went wrong writing the file");
+}
+
+#[test]
+fn test_write_obj_with_normals() {
+    let out_path = std::env::temp_dir().join("mesh_reorienter_normals.obj");
+    let out_path = out_path.to_str().unwrap();
+
+    let mesh = crate::parse_input("tests/reordered.txt").unwrap();
+    let normals = crate::compute_vertex_normals(&mesh);
+    write_obj_with_normals(out_path, &mesh, &normals, PrecisionSpec::uniform(3));
+    let written = std::fs::read_to_string(out_path).unwrap();
+    let lines: Vec<&str> = written.lines().collect();
+    assert_eq!(lines[4..8].iter().filter(|line| line.starts_with("vn ")).count(), 4);
+    assert_eq!(lines[4], format!("vn {}", PrecisionSpec::uniform(3).format_point(&normals[0])));
+    assert_eq!(lines[8], "f 1//1 2//2 3//3");
+    assert_eq!(parse_obj(out_path).unwrap().triangles, mesh.triangles);
+}
+
+/// Write the mesh like [`write_obj`], adding one `vn` line per vertex holding its normal in `normals`,
+/// which every face references with `f v//v`.
+pub fn write_obj_with_normals(out_path: &str, mesh: &Mesh, normals: &[Vector3<f64>], precision: PrecisionSpec) {
+    crate::write_file(out_path, |writer| writer.write_all(obj_contents(&mesh.vertices, &mesh.triangles, mesh.groups.as_deref(), Some(normals), precision).as_bytes()))
+        .expect("Something went wrong writing the file");
+}
+
+/// Write the polygon mesh as a Wavefront OBJ file with one 1-based `f` line per face, grouped like [`write_obj`].
+pub fn write_obj_polygons(out_path: &str, mesh: &PolygonMesh, precision: PrecisionSpec) {
+    crate::write_file(out_path, |writer| writer.write_all(obj_contents(&mesh.vertices, &mesh.faces, mesh.groups.as_deref(), None, precision).as_bytes()))
+        .expect("Something went wrong writing the file");
+}
+
+/// Format points and faces as OBJ `v` and `f` lines, preceded by `g` and `usemtl` lines where the group or
+/// material of the faces, if given, changes, and with a `vn` line per point if `normals` are given.
+fn obj_contents<F: AsRef<[usize]>>(vertices: &[Vector3<f64>], faces: &[F], groups: Option<&[FaceGroup]>, normals: Option<&[Vector3<f64>]>, precision: PrecisionSpec) -> String {
+    let mut out_contents = String::new();
+    for point in vertices {
+        out_contents.push_str(&format!("v {}\n", precision.format_point(point)));
+    }
+    for normal in normals.unwrap_or_default() {
+        out_contents.push_str(&format!("vn {}\n", precision.format_point(normal)));
+    }
+    let mut current = FaceGroup::default();
+    for (f, face) in faces.iter().enumerate() {
+        if let Some(group) = groups.map(|groups| &groups[f]) {
+            if group.group != current.group {
+                out_contents.push_str(&obj_statement("g", &group.group));
+            }
+            if group.material != current.material {
+                out_contents.push_str(&obj_statement("usemtl", &group.material));
+            }
+            current = group.clone();
+        }
+        out_contents.push('f');
+        for index in face.as_ref() {
+            match normals {
+                Some(_) => out_contents.push_str(&format!(" {}//{}", index + 1, index + 1)),
+                None => out_contents.push_str(&format!(" {}", index + 1)),
+            }
+        }
+        out_contents.push('\n');
+    }
+    out_contents
+}
+
+/// Format a `g` or `usemtl` line, without a name for `None`.
+fn obj_statement(keyword: &str, name: &Option<String>) -> String {
+    match name {
+        Some(name) => format!("{} {}\n", keyword, name),
+        None => format!("{}\n", keyword),
+    }
+}
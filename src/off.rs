@@ -0,0 +1,123 @@
+//! Object File Format (OFF) support.
+
+use nalgebra::Vector3;
+
+use crate::{parse_token, validate_faces, Mesh, ParseError, PolygonMesh, PrecisionSpec};
+
+/// Parse an OFF file.
+/// After the `OFF` magic line comes a line with the vertex, face and edge counts, which may also follow the magic on
+/// its own line, as in `OFF 8 6 0`, then one line of
+/// coordinates per vertex and one line per face starting with its vertex count. Faces with more than
+/// three vertices are triangulated as a fan around their first vertex. Blank lines and `#` comments are skipped.
+pub fn parse_off(in_path: &str) -> Result<Mesh, ParseError> {
+    Ok(parse_off_polygons(in_path)?.triangulate())
+}
+
+/// Parse an OFF file like [`parse_off`], keeping faces with more than three vertices intact.
+pub fn parse_off_polygons(in_path: &str) -> Result<PolygonMesh, ParseError> {
+    let contents = crate::read_input_to_string(in_path)?;
+
+    let mut lines = contents
+        .lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, line))
+        .filter(|(_, line)| !line.trim().is_empty() && !line.trim_start().starts_with('#'));
+    let n_lines = contents.lines().count();
+    let eof = |what: &str| ParseError::UnexpectedEof { line: n_lines + 1, reason: format!("expected {}", what) };
+
+    let (line_no, line) = lines.next().ok_or_else(|| eof("'OFF' magic line"))?;
+    let mut header = line.split_whitespace();
+    if header.next() != Some("OFF") {
+        return Err(ParseError::MissingValue { line: line_no, reason: String::from("missing 'OFF' magic line") });
+    }
+
+    let (line_no, mut counts) = if header.clone().next().is_some() {
+        (line_no, header)
+    } else {
+        let (line_no, line) = lines.next().ok_or_else(|| eof("vertex and face counts"))?;
+        (line_no, line.split_whitespace())
+    };
+    let n_vertices = parse_token::<usize>(counts.next(), line_no, "number of vertices")?;
+    let n_faces = parse_token::<usize>(counts.next(), line_no, "number of faces")?;
+
+    let mut vertices: Vec<Vector3<f64>> = Vec::with_capacity(n_vertices.min(n_lines));
+    for _ in 0..n_vertices {
+        let (line_no, line) = lines.next().ok_or_else(|| eof("vertex coordinates"))?;
+        let mut coords = line.split_whitespace();
+        let x = parse_token::<f64>(coords.next(), line_no, "x coordinate")?;
+        let y = parse_token::<f64>(coords.next(), line_no, "y coordinate")?;
+        let z = parse_token::<f64>(coords.next(), line_no, "z coordinate")?;
+        vertices.push(Vector3::new(x, y, z));
+    }
+
+    let mut faces: Vec<Vec<usize>> = Vec::with_capacity(n_faces.min(n_lines));
+    for _ in 0..n_faces {
+        let (line_no, line) = lines.next().ok_or_else(|| eof("face indices"))?;
+        let mut tokens = line.split_whitespace();
+        let count = parse_token::<usize>(tokens.next(), line_no, "face vertex count")?;
+        if count < 3 {
+            return Err(ParseError::WrongIndexCount { line: line_no, count, polygon: true });
+        }
+        let face = (0..count)
+            .map(|_| parse_token::<usize>(tokens.next(), line_no, "vertex index"))
+            .collect::<Result<Vec<usize>, ParseError>>()?;
+        faces.push(face);
+    }
+
+    validate_faces(vertices.len(), &faces)?;
+    Ok(PolygonMesh { vertices, faces, colors: None, groups: None })
+}
+
+#[test]
+fn test_off_round_trip() {
+    let out_path = std::env::temp_dir().join("mesh_reorienter_cube.off");
+    let out_path = out_path.to_str().unwrap();
+
+    let mesh = parse_off("tests/cube.off").unwrap();
+    assert_eq!(mesh.vertices.len(), 8);
+    assert_eq!(mesh.triangles.len(), 12);
+
+    write_off(out_path, &mesh, PrecisionSpec::uniform(6));
+    assert_eq!(parse_off(out_path).unwrap(), mesh);
+}
+
+#[test]
+fn test_off_counts_on_magic_line() {
+    let in_path = std::env::temp_dir().join("mesh_reorienter_counts_on_magic_line.off");
+    let in_path = in_path.to_str().unwrap();
+    let cube = std::fs::read_to_string("tests/cube.off").unwrap();
+    std::fs::write(in_path, cube.replacen("OFF\n# unit cube with quad faces\n8 6 12", "OFF 8 6 12", 1)).unwrap();
+    assert_eq!(parse_off(in_path).unwrap(), parse_off("tests/cube.off").unwrap());
+
+    std::fs::write(in_path, "OFF 3 1 0\n0 0 0\n1 0 0\n0 1 0\n2 0 1\n").unwrap();
+    assert_eq!(parse_off(in_path).unwrap_err(), ParseError::WrongIndexCount { line: 5, count: 2, polygon: true });
+}
+
+/// Write the mesh as an OFF file. The edge count is written as 0, which readers accept as unknown.
+pub fn write_off(out_path: &str, mesh: &Mesh, precision: PrecisionSpec) {
+    crate::write_file(out_path, |writer| writer.write_all(off_contents(&mesh.vertices, &mesh.triangles, precision).as_bytes()))
+        .expect("Something went wrong writing the file");
+}
+
+/// Write the polygon mesh as an OFF file, keeping every face as a single vertex loop.
+pub fn write_off_polygons(out_path: &str, mesh: &PolygonMesh, precision: PrecisionSpec) {
+    crate::write_file(out_path, |writer| writer.write_all(off_contents(&mesh.vertices, &mesh.faces, precision).as_bytes()))
+        .expect("Something went wrong writing the file");
+}
+
+/// Format points and faces as an OFF file.
+fn off_contents<F: AsRef<[usize]>>(vertices: &[Vector3<f64>], faces: &[F], precision: PrecisionSpec) -> String {
+    let mut out_contents = String::from("OFF\n");
+    out_contents.push_str(&format!("{} {} 0\n", vertices.len(), faces.len()));
+    for point in vertices {
+        out_contents.push_str(&format!("{}\n", precision.format_point(point)));
+    }
+    for face in faces {
+        out_contents.push_str(&face.as_ref().len().to_string());
+        for index in face.as_ref() {
+            out_contents.push_str(&format!(" {}", index));
+        }
+        out_contents.push('\n');
+    }
+    out_contents
+}
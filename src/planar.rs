@@ -0,0 +1,49 @@
+//! Flat triangulations lying in a plane of constant z, such as those read from two-column point files.
+//!
+//! Every normal of a flat mesh is parallel to the z axis, so it is perpendicular to the ray from any
+//! centroid in the plane and the centroid approach of [`crate::reorient_mesh`] cannot decide anything.
+//! Orientation is instead a counter-clockwise check in the plane: a triangle is outward when its
+//! vertices turn counter-clockwise as seen from +z, which makes its normal point along +z.
+
+use crate::winding::flip_face;
+use crate::Mesh;
+
+#[test]
+fn test_is_planar() {
+    assert!(is_planar(&crate::parse_input("tests/planar.txt").unwrap()));
+    assert!(!is_planar(&crate::parse_input("tests/input.txt").unwrap()));
+    assert!(!is_planar(&Mesh::default()));
+}
+
+/// Whether the mesh has points and all of them share the same z coordinate.
+pub fn is_planar(mesh: &Mesh) -> bool {
+    mesh.vertices.first().is_some_and(|first| mesh.vertices.iter().all(|point| point.z == first.z))
+}
+
+#[test]
+fn test_orient_planar() {
+    let mut mesh = crate::parse_input("tests/planar.txt").unwrap();
+    assert_eq!(find_clockwise_triangles(&mesh), vec![1, 2]);
+
+    orient_planar(&mut mesh);
+    assert_eq!(mesh.triangles, vec![[0, 1, 2], [0, 2, 3], [1, 4, 2]]);
+    assert!(find_clockwise_triangles(&mesh).is_empty());
+}
+
+/// Indices, in increasing order, of the triangles whose vertices turn clockwise as seen from +z, so that their
+/// normal points along -z. Degenerate triangles, which turn neither way, are not included.
+pub fn find_clockwise_triangles(mesh: &Mesh) -> Vec<usize> {
+    (0..mesh.triangles.len())
+        .filter(|&t| {
+            let [v0, v1, v2] = mesh.triangles[t].map(|i| mesh.vertices[i]);
+            (v1 - v0).cross(&(v2 - v0)).z < 0.0
+        })
+        .collect()
+}
+
+/// Wind every triangle of a flat mesh counter-clockwise as seen from +z, see [`find_clockwise_triangles`].
+pub fn orient_planar(mesh: &mut Mesh) {
+    for t in find_clockwise_triangles(mesh) {
+        flip_face(&mut mesh.triangles[t]);
+    }
+}
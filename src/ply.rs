@@ -0,0 +1,585 @@
+//! Stanford PLY support.
+
+use nalgebra::Vector3;
+
+use crate::{parse_token, validate_faces, Mesh, ParseError, PolygonMesh, PrecisionSpec};
+
+/// Encoding of the data following the header of a PLY file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PlyFormat {
+    Ascii,
+    BinaryLittleEndian,
+}
+
+impl PlyFormat {
+    /// The name of the format on the `format` line of the header.
+    fn name(self) -> &'static str {
+        match self {
+            PlyFormat::Ascii => "ascii",
+            PlyFormat::BinaryLittleEndian => "binary_little_endian",
+        }
+    }
+}
+
+/// Type of a value declared in a PLY header.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PlyType {
+    Char,
+    UChar,
+    Short,
+    UShort,
+    Int,
+    UInt,
+    Float,
+    Double,
+}
+
+impl PlyType {
+    /// Parse a type under either its original name, such as `uchar`, or its sized name, such as `uint8`.
+    fn parse(name: Option<&str>, line: usize) -> Result<Self, ParseError> {
+        match name {
+            Some("char" | "int8") => Ok(PlyType::Char),
+            Some("uchar" | "uint8") => Ok(PlyType::UChar),
+            Some("short" | "int16") => Ok(PlyType::Short),
+            Some("ushort" | "uint16") => Ok(PlyType::UShort),
+            Some("int" | "int32") => Ok(PlyType::Int),
+            Some("uint" | "uint32") => Ok(PlyType::UInt),
+            Some("float" | "float32") => Ok(PlyType::Float),
+            Some("double" | "float64") => Ok(PlyType::Double),
+            Some(name) => Err(ParseError::InvalidNumber { line, reason: format!("unknown PLY type '{}'", name) }),
+            None => Err(ParseError::MissingValue { line, reason: String::from("missing property type") }),
+        }
+    }
+
+    /// Number of bytes a value of this type takes in the binary formats.
+    fn size(self) -> usize {
+        match self {
+            PlyType::Char | PlyType::UChar => 1,
+            PlyType::Short | PlyType::UShort => 2,
+            PlyType::Int | PlyType::UInt | PlyType::Float => 4,
+            PlyType::Double => 8,
+        }
+    }
+
+    /// Decode a little-endian value of this type from exactly [`PlyType::size`] bytes. Every type fits an `f64` exactly.
+    fn read_le(self, bytes: &[u8]) -> f64 {
+        fn array<const N: usize>(bytes: &[u8]) -> [u8; N] {
+            bytes.try_into().expect("the slice holds a value of the type")
+        }
+        match self {
+            PlyType::Char => bytes[0] as i8 as f64,
+            PlyType::UChar => bytes[0] as f64,
+            PlyType::Short => i16::from_le_bytes(array(bytes)) as f64,
+            PlyType::UShort => u16::from_le_bytes(array(bytes)) as f64,
+            PlyType::Int => i32::from_le_bytes(array(bytes)) as f64,
+            PlyType::UInt => u32::from_le_bytes(array(bytes)) as f64,
+            PlyType::Float => f32::from_le_bytes(array(bytes)) as f64,
+            PlyType::Double => f64::from_le_bytes(array(bytes)),
+        }
+    }
+}
+
+/// A property declared in a PLY header.
+#[derive(Debug, Clone, PartialEq)]
+enum PlyProperty {
+    /// A single value, such as `property float x`.
+    Scalar { name: String, ty: PlyType },
+    /// A count followed by that many values, such as `property list uchar int vertex_indices`.
+    List { name: String, count_ty: PlyType, item_ty: PlyType },
+}
+
+/// An element declared in a PLY header, such as `element vertex 8`, together with its properties.
+#[derive(Debug, Clone, PartialEq)]
+struct PlyElement {
+    name: String,
+    count: usize,
+    properties: Vec<PlyProperty>,
+}
+
+impl PlyElement {
+    /// Size in bytes of the smallest binary record of the element, the one whose lists are all empty.
+    fn min_record_len(&self) -> usize {
+        self.properties
+            .iter()
+            .map(|property| match property {
+                PlyProperty::Scalar { ty, .. } => ty.size(),
+                PlyProperty::List { count_ty, .. } => count_ty.size(),
+            })
+            .sum()
+    }
+}
+
+/// Parse the header of a PLY file, consuming the lines up to and including `end_header`.
+/// With `expected`, a file declaring another format is an error.
+fn parse_ply_header<'a>(lines: &mut impl Iterator<Item = (usize, &'a str)>, expected: Option<PlyFormat>) -> Result<(PlyFormat, Vec<PlyElement>), ParseError> {
+    match lines.next() {
+        Some((_, line)) if line.trim() == "ply" => {}
+        Some((line_no, _)) => return Err(ParseError::MissingValue { line: line_no, reason: String::from("missing 'ply' magic line") }),
+        None => return Err(ParseError::UnexpectedEof { line: 1, reason: String::from("expected 'ply' magic line") }),
+    }
+
+    let mut format = PlyFormat::Ascii;
+    let mut elements: Vec<PlyElement> = Vec::new();
+    let mut last_line = 1;
+    for (line_no, line) in lines.by_ref() {
+        last_line = line_no;
+        let mut tokens = line.split_whitespace();
+        match tokens.next() {
+            Some("format") => {
+                format = match tokens.next() {
+                    Some("ascii") => PlyFormat::Ascii,
+                    Some("binary_little_endian") => PlyFormat::BinaryLittleEndian,
+                    other => return Err(ParseError::InvalidNumber { line: line_no, reason: format!("unsupported PLY format '{}'", other.unwrap_or("")) }),
+                };
+                if let Some(expected) = expected.filter(|&expected| expected != format) {
+                    return Err(ParseError::InvalidNumber { line: line_no, reason: format!("expected the {} PLY format, found {}", expected.name(), format.name()) });
+                }
+            }
+            Some("element") => {
+                let name = tokens.next()
+                    .ok_or_else(|| ParseError::MissingValue { line: line_no, reason: String::from("missing element name") })?;
+                let count = parse_token::<usize>(tokens.next(), line_no, "element count")?;
+                elements.push(PlyElement { name: name.to_string(), count, properties: Vec::new() });
+            }
+            Some("property") => {
+                let element = elements.last_mut()
+                    .ok_or_else(|| ParseError::MissingValue { line: line_no, reason: String::from("property declared before any element") })?;
+                let property = match tokens.next() {
+                    Some("list") => {
+                        let count_ty = PlyType::parse(tokens.next(), line_no)?;
+                        let item_ty = PlyType::parse(tokens.next(), line_no)?;
+                        PlyProperty::List { name: tokens.next().unwrap_or_default().to_string(), count_ty, item_ty }
+                    }
+                    ty => PlyProperty::Scalar { ty: PlyType::parse(ty, line_no)?, name: tokens.next().unwrap_or_default().to_string() },
+                };
+                element.properties.push(property);
+            }
+            Some("end_header") => return Ok((format, elements)),
+            _ => {}
+        }
+    }
+    Err(ParseError::UnexpectedEof { line: last_line + 1, reason: String::from("expected 'end_header'") })
+}
+
+/// Length in bytes of the header of a PLY file, up to and including the line break after `end_header`,
+/// or the length of the whole file if no line holds `end_header`.
+fn ply_header_len(bytes: &[u8]) -> usize {
+    let mut len = 0;
+    for line in bytes.split_inclusive(|&byte| byte == b'\n') {
+        len += line.len();
+        if line.trim_ascii() == b"end_header" {
+            return len;
+        }
+    }
+    bytes.len()
+}
+
+/// The values of the data records following a PLY header, read either from text lines or from packed bytes.
+trait PlyValues {
+    /// Fail if the data left cannot hold the records of `element`, before reading any of them.
+    fn check_room(&self, element: &PlyElement) -> Result<(), ParseError>;
+    /// Move to the next record, of the element named `element`.
+    fn next_record(&mut self, element: &str) -> Result<(), ParseError>;
+    /// Read the next value of the record, a property named `name` of type `ty`.
+    fn value(&mut self, ty: PlyType, name: &str) -> Result<f64, ParseError>;
+    /// Read the next value of the record as a list length or a point index, which must be a non-negative integer.
+    fn index(&mut self, ty: PlyType, name: &str) -> Result<usize, ParseError>;
+    /// An error about the current record.
+    fn error(&self, reason: &str) -> ParseError;
+}
+
+/// Reads the records of an ASCII PLY file, one line per record.
+struct PlyText<'a, I> {
+    lines: I,
+    /// Number of lines in the file, to report where it ended too early.
+    n_lines: usize,
+    line_no: usize,
+    tokens: std::str::SplitWhitespace<'a>,
+}
+
+impl<'a, I: Iterator<Item = (usize, &'a str)>> PlyValues for PlyText<'a, I> {
+    /// Every record takes a line, so a count past the end of the file fails at the first missing line.
+    fn check_room(&self, _: &PlyElement) -> Result<(), ParseError> {
+        Ok(())
+    }
+
+    fn next_record(&mut self, element: &str) -> Result<(), ParseError> {
+        let (line_no, line) = self.lines.next()
+            .ok_or_else(|| ParseError::UnexpectedEof { line: self.n_lines + 1, reason: format!("expected {} data", element) })?;
+        (self.line_no, self.tokens) = (line_no, line.split_whitespace());
+        Ok(())
+    }
+
+    fn value(&mut self, _: PlyType, name: &str) -> Result<f64, ParseError> {
+        parse_token::<f64>(self.tokens.next(), self.line_no, name)
+    }
+
+    fn index(&mut self, _: PlyType, name: &str) -> Result<usize, ParseError> {
+        parse_token::<usize>(self.tokens.next(), self.line_no, name)
+    }
+
+    fn error(&self, reason: &str) -> ParseError {
+        ParseError::MissingValue { line: self.line_no, reason: reason.to_string() }
+    }
+}
+
+/// Reads the records of a binary little-endian PLY file, packed one after the other with no separator.
+struct PlyBytes<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+impl PlyValues for PlyBytes<'_> {
+    /// Records without properties take no bytes, so their count is bounded by nothing and they are rejected.
+    fn check_room(&self, element: &PlyElement) -> Result<(), ParseError> {
+        let min_len = element.min_record_len();
+        if element.count > 0 && min_len == 0 {
+            return Err(self.error(&format!("element '{}' has no properties", element.name)));
+        }
+        let remaining = self.bytes.len() - self.offset;
+        if element.count > remaining / min_len.max(1) {
+            return Err(self.error(&format!("file is too short for {} '{}' records of at least {} bytes", element.count, element.name, min_len)));
+        }
+        Ok(())
+    }
+
+    fn next_record(&mut self, _: &str) -> Result<(), ParseError> {
+        Ok(())
+    }
+
+    fn value(&mut self, ty: PlyType, name: &str) -> Result<f64, ParseError> {
+        let value = self.bytes
+            .get(self.offset..self.offset + ty.size())
+            .ok_or_else(|| ParseError::Binary { offset: self.offset, reason: format!("file ends before the {}", name) })?;
+        self.offset += ty.size();
+        Ok(ty.read_le(value))
+    }
+
+    fn index(&mut self, ty: PlyType, name: &str) -> Result<usize, ParseError> {
+        let offset = self.offset;
+        let value = self.value(ty, name)?;
+        if value < 0.0 || value.fract() != 0.0 {
+            return Err(ParseError::Binary { offset, reason: format!("{} {} is not a non-negative integer", name, value) });
+        }
+        Ok(value as usize)
+    }
+
+    fn error(&self, reason: &str) -> ParseError {
+        ParseError::Binary { offset: self.offset, reason: reason.to_string() }
+    }
+}
+
+/// Read the records of every element declared in the header, keeping the points, their colors and the faces.
+fn read_ply_records(elements: &[PlyElement], values: &mut impl PlyValues) -> Result<PolygonMesh, ParseError> {
+    let has_colors = elements.iter().any(|element| {
+        element.name == "vertex"
+            && ["red", "green", "blue"].iter().all(|channel| element.properties.iter().any(|property| matches!(property, PlyProperty::Scalar { name, .. } if name == channel)))
+    });
+
+    let mut vertices: Vec<Vector3<f64>> = Vec::new();
+    let mut colors: Vec<[u8; 3]> = Vec::new();
+    let mut faces: Vec<Vec<usize>> = Vec::new();
+    for element in elements {
+        values.check_room(element)?;
+        for _ in 0..element.count {
+            values.next_record(&element.name)?;
+            let mut position = [None; 3];
+            let mut color = [0; 3];
+            let mut face: Vec<usize> = Vec::new();
+            for property in &element.properties {
+                match property {
+                    PlyProperty::Scalar { name, ty } => {
+                        let value = values.value(*ty, name)?;
+                        match name.as_str() {
+                            "x" => position[0] = Some(value),
+                            "y" => position[1] = Some(value),
+                            "z" => position[2] = Some(value),
+                            // colors are stored as uchar, values outside 0..=255 saturate
+                            "red" => color[0] = value as u8,
+                            "green" => color[1] = value as u8,
+                            "blue" => color[2] = value as u8,
+                            _ => {}
+                        }
+                    }
+                    PlyProperty::List { name, count_ty, item_ty } => {
+                        let count = values.index(*count_ty, "list length")?;
+                        // other lists, such as the texture coordinates of scanners, are read and dropped
+                        let is_face = matches!(name.as_str(), "vertex_indices" | "vertex_index");
+                        for _ in 0..count {
+                            if is_face {
+                                face.push(values.index(*item_ty, name)?);
+                            } else {
+                                values.value(*item_ty, name)?;
+                            }
+                        }
+                    }
+                }
+            }
+            match element.name.as_str() {
+                "vertex" => {
+                    let [x, y, z] = position.map(|value| value.unwrap_or(0.0));
+                    vertices.push(Vector3::new(x, y, z));
+                    colors.push(color);
+                }
+                "face" => {
+                    if face.len() < 3 {
+                        return Err(values.error("face has fewer than three vertices"));
+                    }
+                    faces.push(face);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    validate_faces(vertices.len(), &faces)?;
+    Ok(PolygonMesh { vertices, faces, colors: has_colors.then_some(colors), groups: None })
+}
+
+/// Parse the contents of a PLY file in any supported format, or only in `expected` if it is given.
+fn ply_from_bytes(bytes: &[u8], expected: Option<PlyFormat>) -> Result<PolygonMesh, ParseError> {
+    let header_len = ply_header_len(bytes);
+    let header = String::from_utf8_lossy(&bytes[..header_len]);
+    let mut lines = header.lines().enumerate().map(|(i, line)| (i + 1, line));
+    let (format, elements) = parse_ply_header(&mut lines, expected)?;
+    match format {
+        PlyFormat::Ascii => {
+            let n_header_lines = header.lines().count();
+            let contents = std::str::from_utf8(&bytes[header_len..]).map_err(|e| {
+                let valid = &bytes[header_len..header_len + e.valid_up_to()];
+                let line = n_header_lines + valid.iter().filter(|&&byte| byte == b'\n').count() + 1;
+                ParseError::Syntax { line, reason: String::from("invalid UTF-8 in the ascii PLY data") }
+            })?;
+            let lines = contents.lines().enumerate().map(|(i, line)| (n_header_lines + i + 1, line));
+            let mut values = PlyText { lines, n_lines: n_header_lines + contents.lines().count(), line_no: n_header_lines, tokens: "".split_whitespace() };
+            read_ply_records(&elements, &mut values)
+        }
+        PlyFormat::BinaryLittleEndian => read_ply_records(&elements, &mut PlyBytes { bytes, offset: header_len }),
+    }
+}
+
+#[test]
+fn test_parse_ply_ascii() {
+    let mesh = parse_ply_ascii("tests/pyramid.ply").unwrap();
+    assert_eq!(mesh.vertices.len(), 5);
+    assert_eq!(mesh.vertices[4], Vector3::new(0.5, 0.5, 1.0));
+    assert_eq!(mesh.triangles.len(), 6);
+    assert_eq!(mesh.triangles[0], [0, 3, 2]);
+
+    let mut bytes = std::fs::read("tests/pyramid.ply").unwrap();
+    let second_point = ply_header_len(&bytes) + "0.0 0.0 0.0 -0.577 -0.577 -0.577\n".len();
+    bytes[second_point] = 0xff;
+    assert_eq!(ply_from_bytes(&bytes, None), Err(ParseError::Syntax { line: 15, reason: String::from("invalid UTF-8 in the ascii PLY data") }));
+}
+
+/// Parse an ASCII PLY file, see [`parse_ply`]. Faces with more than three vertices are triangulated as a fan
+/// around their first vertex.
+pub fn parse_ply_ascii(in_path: &str) -> Result<Mesh, ParseError> {
+    Ok(parse_ply_ascii_polygons(in_path)?.triangulate())
+}
+
+/// Parse an ASCII PLY file like [`parse_ply_ascii`], keeping faces with more than three vertices intact.
+pub fn parse_ply_ascii_polygons(in_path: &str) -> Result<PolygonMesh, ParseError> {
+    ply_from_bytes(&crate::read_input(in_path)?, Some(PlyFormat::Ascii))
+}
+
+/// The pyramid of `tests/pyramid.ply` as a binary little-endian PLY file, with float points, an ignored
+/// double property and faces listed as `uchar int`, used by the tests below.
+#[cfg(test)]
+fn pyramid_binary_bytes() -> Vec<u8> {
+    let header = "ply\nformat binary_little_endian 1.0\nelement vertex 5\nproperty float x\nproperty float y\nproperty float z\n\
+                  property double confidence\nelement face 5\nproperty list uchar int vertex_indices\nend_header\n";
+    let mut bytes = header.as_bytes().to_vec();
+    for point in [[0.0f32, 0.0, 0.0], [1.0, 0.0, 0.0], [1.0, 1.0, 0.0], [0.0, 1.0, 0.0], [0.5, 0.5, 1.0]] {
+        for coord in point {
+            bytes.extend_from_slice(&coord.to_le_bytes());
+        }
+        bytes.extend_from_slice(&0.9f64.to_le_bytes());
+    }
+    for face in [&[0i32, 3, 2, 1][..], &[0, 1, 4], &[1, 2, 4], &[2, 3, 4], &[3, 0, 4]] {
+        bytes.push(face.len() as u8);
+        for index in face {
+            bytes.extend_from_slice(&index.to_le_bytes());
+        }
+    }
+    bytes
+}
+
+#[test]
+fn test_parse_ply_binary() {
+    let in_path = std::env::temp_dir().join("mesh_reorienter_pyramid_binary.ply");
+    std::fs::write(&in_path, pyramid_binary_bytes()).unwrap();
+    let in_path = in_path.to_str().unwrap();
+
+    let mesh = parse_ply_binary(in_path).unwrap();
+    assert_eq!((mesh.vertices.len(), mesh.triangles.len()), (5, 6));
+    assert_eq!(mesh, parse_ply_ascii("tests/pyramid.ply").unwrap());
+    assert_eq!(parse_ply(in_path).unwrap(), mesh);
+    assert!(matches!(parse_ply_ascii(in_path), Err(ParseError::InvalidNumber { line: 2, .. })));
+}
+
+#[test]
+fn test_parse_ply_binary_truncated() {
+    let mut bytes = pyramid_binary_bytes();
+    bytes.truncate(bytes.len() - 2);
+    let header_len = ply_header_len(&bytes);
+    // after five 20-byte points, the quad and three triangles, the last face holds its count byte and two
+    // of its indices, then the file ends inside the third one
+    assert_eq!(
+        ply_from_bytes(&bytes, None),
+        Err(ParseError::Binary { offset: header_len + 5 * 20 + 17 + 3 * 13 + 9, reason: String::from("file ends before the vertex_indices") })
+    );
+
+    // a huge count is rejected up front rather than read record by record
+    let bytes = pyramid_binary_bytes();
+    let header_len = ply_header_len(&bytes);
+    let header = std::str::from_utf8(&bytes[..header_len]).unwrap().replace("element face 5", "element face 10000000000000");
+    let huge = [header.as_bytes(), &bytes[header_len..]].concat();
+    assert!(matches!(ply_from_bytes(&huge, None), Err(ParseError::Binary { reason, .. }) if reason.contains("too short")));
+    let empty = "ply\nformat binary_little_endian 1.0\nelement junk 10000000000000\nend_header\n";
+    assert_eq!(
+        ply_from_bytes(empty.as_bytes(), None),
+        Err(ParseError::Binary { offset: empty.len(), reason: String::from("element 'junk' has no properties") })
+    );
+
+    let mut bytes = pyramid_binary_bytes();
+    let last_index = bytes.len() - 4;
+    bytes[last_index..].copy_from_slice(&(-1i32).to_le_bytes());
+    assert!(matches!(ply_from_bytes(&bytes, None), Err(ParseError::Binary { offset, .. }) if offset == last_index));
+}
+
+#[test]
+fn test_parse_ply_skips_other_face_lists() {
+    let header = |format: &str| {
+        format!(
+            "ply\nformat {} 1.0\nelement vertex 3\nproperty float x\nproperty float y\nproperty float z\nelement face 1\n\
+             property list uchar float texcoord\nproperty list uchar int vertex_indices\nproperty list uchar float texcoord2\nend_header\n",
+            format
+        )
+    };
+    let ascii = header("ascii") + "0 0 0\n1 0 0\n0 1 0\n6 0.5 0.5 1 0.25 0 1 3 0 1 2 2 0.5 0.5\n";
+    let mesh = ply_from_bytes(ascii.as_bytes(), None).unwrap();
+    assert_eq!(mesh.faces, vec![vec![0, 1, 2]]);
+
+    let mut binary = header("binary_little_endian").into_bytes();
+    for coord in [0.0f32, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0] {
+        binary.extend_from_slice(&coord.to_le_bytes());
+    }
+    binary.push(6);
+    for coord in [0.5f32, 0.5, 1.0, 0.25, 0.0, 1.0] {
+        binary.extend_from_slice(&coord.to_le_bytes());
+    }
+    binary.push(3);
+    for index in [0i32, 1, 2] {
+        binary.extend_from_slice(&index.to_le_bytes());
+    }
+    binary.push(2);
+    for coord in [0.5f32, 0.5] {
+        binary.extend_from_slice(&coord.to_le_bytes());
+    }
+    assert_eq!(ply_from_bytes(&binary, None).unwrap(), mesh);
+}
+
+/// Parse a binary little-endian PLY file, see [`parse_ply`].
+pub fn parse_ply_binary(in_path: &str) -> Result<Mesh, ParseError> {
+    Ok(ply_from_bytes(&crate::read_input(in_path)?, Some(PlyFormat::BinaryLittleEndian))?.triangulate())
+}
+
+/// Parse a PLY file in the ASCII or binary little-endian format, as its header declares.
+/// Only the `x`, `y` and `z` properties of the `vertex` element, its `red`, `green` and `blue` properties
+/// if all three are declared, and the `vertex_indices` or `vertex_index` list of the `face` element are used;
+/// other properties, including other lists such as texture coordinates, and other elements are skipped.
+/// Faces with more than three vertices are triangulated as a fan around their first vertex.
+pub fn parse_ply(in_path: &str) -> Result<Mesh, ParseError> {
+    Ok(parse_ply_polygons(in_path)?.triangulate())
+}
+
+/// Parse a PLY file like [`parse_ply`], keeping faces with more than three vertices intact.
+pub fn parse_ply_polygons(in_path: &str) -> Result<PolygonMesh, ParseError> {
+    ply_from_bytes(&crate::read_input(in_path)?, None)
+}
+
+#[test]
+fn test_ply_colors_round_trip() {
+    let out_path = std::env::temp_dir().join("mesh_reorienter_colored.ply");
+    let out_path = out_path.to_str().unwrap();
+
+    let mut mesh = parse_ply_ascii("tests/colored.ply").unwrap();
+    assert_eq!(mesh.colors.as_ref().unwrap()[1], [0, 255, 0]);
+    let colors = mesh.colors.clone();
+    crate::make_winding_consistent(&mut mesh);
+
+    write_ply(out_path, &mesh, PrecisionSpec::uniform(6));
+    let written = parse_ply_ascii(out_path).unwrap();
+    assert_eq!(written.colors, colors);
+    assert_eq!(written, mesh);
+}
+
+/// Write the mesh as an ASCII PLY file, including the vertex colors if the mesh has them.
+pub fn write_ply(out_path: &str, mesh: &Mesh, precision: PrecisionSpec) {
+    crate::write_file(out_path, |writer| writer.write_all(ply_contents(&mesh.vertices, mesh.colors.as_deref(), None, &mesh.triangles, precision).as_bytes()))
+        .expect("Something went wrong writing the file");
+}
+
+#[test]
+fn test_write_ply_with_normals() {
+    let out_path = std::env::temp_dir().join("mesh_reorienter_normals.ply");
+    let out_path = out_path.to_str().unwrap();
+
+    let mesh = parse_ply_ascii("tests/colored.ply").unwrap();
+    let normals = crate::compute_vertex_normals(&mesh);
+    write_ply_with_normals(out_path, &mesh, &normals, PrecisionSpec::uniform(3));
+    let written = std::fs::read_to_string(out_path).unwrap();
+    assert!(written.contains("property double z\nproperty double nx\nproperty double ny\nproperty double nz\nproperty uchar red\n"));
+    let first_point = written.lines().skip_while(|&line| line != "end_header").nth(1).unwrap();
+    assert_eq!(first_point, format!("0.000 0.000 0.000 {} 255 0 0", PrecisionSpec::uniform(3).format_point(&normals[0])));
+    // the normals are skipped when reading the file back
+    assert_eq!(parse_ply_ascii(out_path).unwrap(), mesh);
+}
+
+/// Write the mesh like [`write_ply`], adding the `nx`, `ny` and `nz` properties to every vertex, holding its normal in `normals`.
+pub fn write_ply_with_normals(out_path: &str, mesh: &Mesh, normals: &[Vector3<f64>], precision: PrecisionSpec) {
+    crate::write_file(out_path, |writer| writer.write_all(ply_contents(&mesh.vertices, mesh.colors.as_deref(), Some(normals), &mesh.triangles, precision).as_bytes()))
+        .expect("Something went wrong writing the file");
+}
+
+/// Write the polygon mesh as an ASCII PLY file, keeping every face as a single vertex loop.
+pub fn write_ply_polygons(out_path: &str, mesh: &PolygonMesh, precision: PrecisionSpec) {
+    crate::write_file(out_path, |writer| writer.write_all(ply_contents(&mesh.vertices, mesh.colors.as_deref(), None, &mesh.faces, precision).as_bytes()))
+        .expect("Something went wrong writing the file");
+}
+
+/// Format points, optional normals and colors, and faces as an ASCII PLY file.
+fn ply_contents<F: AsRef<[usize]>>(vertices: &[Vector3<f64>], colors: Option<&[[u8; 3]]>, normals: Option<&[Vector3<f64>]>, faces: &[F], precision: PrecisionSpec) -> String {
+    let mut out_contents = String::from("ply\nformat ascii 1.0\n");
+    out_contents.push_str(&format!("element vertex {}\n", vertices.len()));
+    out_contents.push_str("property double x\nproperty double y\nproperty double z\n");
+    if normals.is_some() {
+        out_contents.push_str("property double nx\nproperty double ny\nproperty double nz\n");
+    }
+    if colors.is_some() {
+        out_contents.push_str("property uchar red\nproperty uchar green\nproperty uchar blue\n");
+    }
+    out_contents.push_str(&format!("element face {}\n", faces.len()));
+    out_contents.push_str("property list uchar int vertex_indices\nend_header\n");
+    for (i, point) in vertices.iter().enumerate() {
+        out_contents.push_str(&precision.format_point(point));
+        if let Some(normals) = normals {
+            out_contents.push(' ');
+            out_contents.push_str(&precision.format_point(&normals[i]));
+        }
+        if let Some(colors) = colors {
+            let [r, g, b] = colors[i];
+            out_contents.push_str(&format!(" {} {} {}", r, g, b));
+        }
+        out_contents.push('\n');
+    }
+    for face in faces {
+        out_contents.push_str(&face.as_ref().len().to_string());
+        for index in face.as_ref() {
+            out_contents.push_str(&format!(" {}", index));
+        }
+        out_contents.push('\n');
+    }
+    out_contents
+}
@@ -0,0 +1,178 @@
+//! Polygon meshes, whose faces are vertex loops of any length.
+//!
+//! Quad-dominant meshes can be reoriented without splitting their faces into triangles. The face
+//! normal is computed with Newell's method, which uses every vertex of the loop, and a misoriented
+//! face has its whole vertex loop reversed.
+
+use nalgebra::Vector3;
+
+use crate::winding::{flip_face, make_faces_consistent, Winding};
+use crate::{compute_area_weighted_centroid, mean_point, native_contents, newell_normal, parse_native, FaceGroup, Mesh, ParseError, ParseOptions, PrecisionSpec};
+
+/// A meshgrid of polygonal faces: the point coordinates and the vertex loops indexing them.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PolygonMesh {
+    pub vertices: Vec<Vector3<f64>>,
+    pub faces: Vec<Vec<usize>>,
+    /// Per-vertex RGB colors, parallel to `vertices`, when the input file carries them.
+    pub colors: Option<Vec<[u8; 3]>>,
+    /// Per-face OBJ group and material names, parallel to `faces`, when the input file carries them.
+    pub groups: Option<Vec<FaceGroup>>,
+}
+
+impl From<Mesh> for PolygonMesh {
+    fn from(mesh: Mesh) -> Self {
+        PolygonMesh {
+            vertices: mesh.vertices,
+            faces: mesh.triangles.iter().map(|triangle| triangle.to_vec()).collect(),
+            colors: mesh.colors,
+            groups: mesh.groups,
+        }
+    }
+}
+
+impl PolygonMesh {
+    /// Split every face into triangles as a fan around its first vertex. Every triangle keeps the group of its face.
+    pub fn triangulate(&self) -> Mesh {
+        let mut triangles: Vec<[usize; 3]> = Vec::new();
+        let mut groups: Vec<FaceGroup> = Vec::new();
+        for (f, face) in self.faces.iter().enumerate() {
+            for k in 1..face.len() - 1 {
+                triangles.push([face[0], face[k], face[k + 1]]);
+                if let Some(face_groups) = &self.groups {
+                    groups.push(face_groups[f].clone());
+                }
+            }
+        }
+        Mesh { vertices: self.vertices.clone(), triangles, colors: self.colors.clone(), groups: self.groups.is_some().then_some(groups) }
+    }
+
+    /// Remove the repeated vertices of every face loop, where a vertex directly follows itself, such as the
+    /// second `1` in `0 1 1 2`, including a last vertex repeating the first one. Such repeats add nothing to the
+    /// Newell normal, but can leave a face with fewer than three distinct vertices, and so without a normal.
+    /// Returns the indices of those degenerate faces, which are kept, in increasing order.
+    pub fn collapse_repeated_vertices(&mut self) -> Vec<usize> {
+        let mut degenerate: Vec<usize> = Vec::new();
+        for (f, face) in self.faces.iter_mut().enumerate() {
+            face.dedup();
+            while face.len() > 1 && face.first() == face.last() {
+                face.pop();
+            }
+            if face.len() < 3 {
+                degenerate.push(f);
+            }
+        }
+        degenerate
+    }
+}
+
+#[test]
+fn test_collapse_repeated_vertices() {
+    let mut mesh = parse_polygon_input("tests/quads.txt").unwrap();
+    let original = mesh.clone();
+    // the x = 0 side wound inwards, with a repeated vertex in the middle and the first vertex repeated at the end
+    mesh.faces[5] = vec![3, 7, 7, 4, 0, 3];
+    mesh.faces.push(vec![1, 2, 2, 1]);
+
+    assert_eq!(mesh.collapse_repeated_vertices(), vec![6]);
+    assert_eq!(mesh.faces[5], vec![3, 7, 4, 0]);
+    assert_eq!(mesh.faces[6], vec![1, 2]);
+
+    mesh.faces.pop();
+    reorient_polygons(&mut mesh);
+    assert_eq!(mesh, original);
+}
+
+#[test]
+fn test_parse_polygon_input() {
+    let mesh = parse_polygon_input("tests/quads.txt").unwrap();
+    assert_eq!(mesh.vertices.len(), 8);
+    assert_eq!(mesh.faces.len(), 6);
+    assert!(mesh.faces.iter().all(|face| face.len() == 4));
+    assert_eq!(mesh.triangulate().triangles.len(), 12);
+}
+
+/// Parse a file in the native text format whose faces may have any number of vertices from three upwards.
+pub fn parse_polygon_input(in_path: &str) -> Result<PolygonMesh, ParseError> {
+    parse_polygon_reader(crate::open_input(in_path)?)
+}
+
+/// Parse a polygon mesh in the native text format from any reader.
+pub fn parse_polygon_reader(reader: impl std::io::Read) -> Result<PolygonMesh, ParseError> {
+    let (vertices, faces) = parse_native(reader, true, |face| face.to_vec(), &ParseOptions::default(), None)?;
+    Ok(PolygonMesh { vertices, faces, colors: None, groups: None })
+}
+
+/// Write the polygon mesh in the native text format, one row of indices per face.
+pub fn write_polygons(out_path: &str, mesh: &PolygonMesh, precision: PrecisionSpec) {
+    crate::write_file(out_path, |writer| write_polygons_to(writer, mesh, precision))
+        .expect("Something went wrong writing the file");
+}
+
+/// Write the polygon mesh in the native text format to any writer.
+pub fn write_polygons_to<W: std::io::Write + ?Sized>(writer: &mut W, mesh: &PolygonMesh, precision: PrecisionSpec) -> std::io::Result<()> {
+    writer.write_all(native_contents(&mesh.vertices, &mesh.faces, precision, 0).as_bytes())
+}
+
+/// Compute the (unnormalized) normal of a face with Newell's method.
+fn polygon_normal(vertices: &[Vector3<f64>], face: &[usize]) -> Vector3<f64> {
+    let points: Vec<Vector3<f64>> = face.iter().map(|&i| vertices[i]).collect();
+    newell_normal(&points)
+}
+
+#[test]
+fn test_reorient_polygons_reverses_quad() {
+    let mut mesh = parse_polygon_input("tests/quads.txt").unwrap();
+    let original = mesh.clone();
+    // wind the x = 0 side inwards
+    mesh.faces[5] = vec![3, 7, 4, 0];
+
+    reorient_polygons(&mut mesh);
+    assert_eq!(mesh.faces[5], vec![3, 0, 4, 7]);
+    assert_eq!(mesh, original);
+}
+
+/// Reorient every face so that its Newell normal points away from the centroid of the vertices.
+pub fn reorient_polygons(mesh: &mut PolygonMesh) {
+    let centroid = mean_point(&mesh.vertices);
+    reorient_polygons_around(mesh, &centroid);
+}
+
+/// Reorient every face so that its Newell normal points away from `centroid`,
+/// reversing the vertex loop of the faces that point towards it.
+pub fn reorient_polygons_around(mesh: &mut PolygonMesh, centroid: &Vector3<f64>) {
+    for face in &mut mesh.faces {
+        let normal = polygon_normal(&mesh.vertices, face);
+        if normal.dot(&(mesh.vertices[face[0]] - centroid)) <= 0.0 {
+            flip_face(face);
+        }
+    }
+}
+
+/// Compute the area-weighted centroid of the surface, see [`compute_area_weighted_centroid`].
+pub fn polygon_area_weighted_centroid(mesh: &PolygonMesh) -> Vector3<f64> {
+    compute_area_weighted_centroid(&mesh.triangulate())
+}
+
+/// Make the winding of the faces consistent by flood fill over the edge adjacency, see [`crate::make_winding_consistent`].
+pub fn make_polygon_winding_consistent(mesh: &mut PolygonMesh) {
+    make_faces_consistent(&mut mesh.faces);
+}
+
+/// Reverse every face if the signed volume of the mesh is negative, see [`crate::orient_outward`].
+pub fn orient_polygons_outward(mesh: &mut PolygonMesh) {
+    if crate::signed_volume(&mesh.triangulate()) < 0.0 {
+        for face in &mut mesh.faces {
+            flip_face(face);
+        }
+    }
+}
+
+/// Convert a polygon mesh oriented with the counter-clockwise convention to the `winding` convention, see [`crate::apply_winding`].
+pub fn apply_polygon_winding(mesh: &mut PolygonMesh, winding: Winding) {
+    if winding == Winding::Clockwise {
+        for face in &mut mesh.faces {
+            flip_face(face);
+        }
+    }
+}
@@ -0,0 +1,259 @@
+//! Summary statistics of a mesh, for a quick sanity check after processing.
+
+use nalgebra::{Matrix3, Vector3};
+
+use crate::{build_edge_adjacency, component_mesh, signed_volume, split_components, Mesh};
+
+/// Statistics describing the size and shape of a mesh.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MeshStats {
+    pub vertex_count: usize,
+    pub triangle_count: usize,
+    /// Sum of the triangle areas.
+    pub surface_area: f64,
+    /// Volume enclosed by the surface, positive when the normals point outwards.
+    pub signed_volume: f64,
+    /// Smallest and largest coordinates on each axis, or `None` for a mesh without vertices.
+    pub bounding_box: Option<(Vector3<f64>, Vector3<f64>)>,
+    /// The same measures for each connected component, in the order of [`split_components`].
+    /// A component with a negative volume is inside out even when the total volume is positive.
+    pub components: Vec<ComponentStats>,
+}
+
+/// Size of one connected component of a mesh.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ComponentStats {
+    pub triangle_count: usize,
+    pub surface_area: f64,
+    /// Volume enclosed by the component, positive when its normals point outwards.
+    pub signed_volume: f64,
+}
+
+#[test]
+fn test_mesh_stats_components() {
+    // two unit tetrahedra of volume 1/6, the second one inside out
+    let stats = MeshStats::compute(&crate::parse_input("tests/one_inward.txt").unwrap());
+    assert_eq!(stats.components.len(), 2);
+    assert!(stats.signed_volume.abs() < 1e-12);
+    let area = 1.5 + 3.0f64.sqrt() / 2.0;
+    for (component, volume) in stats.components.iter().zip([1.0 / 6.0, -1.0 / 6.0]) {
+        assert_eq!(component.triangle_count, 4);
+        assert!((component.surface_area - area).abs() < 1e-12);
+        assert!((component.signed_volume - volume).abs() < 1e-12);
+    }
+}
+
+impl MeshStats {
+    /// Compute the statistics of the mesh and of each of its connected components.
+    pub fn compute(mesh: &Mesh) -> Self {
+        let components = split_components(mesh)
+            .iter()
+            .map(|triangles| {
+                let component = component_mesh(mesh, triangles);
+                ComponentStats { triangle_count: triangles.len(), surface_area: total_surface_area(&component), signed_volume: signed_volume(&component) }
+            })
+            .collect();
+        MeshStats {
+            vertex_count: mesh.vertices.len(),
+            triangle_count: mesh.triangles.len(),
+            surface_area: total_surface_area(mesh),
+            signed_volume: signed_volume(mesh),
+            bounding_box: bounding_box(mesh),
+            components,
+        }
+    }
+}
+
+/// Vertex, edge and face counts of a mesh and the Euler characteristic they give.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Topology {
+    /// Vertices used by at least one triangle, since unused points are not part of the surface.
+    pub vertex_count: usize,
+    pub edge_count: usize,
+    pub face_count: usize,
+    /// `V - E + F`.
+    pub euler_characteristic: i64,
+}
+
+impl Topology {
+    /// Count the vertices, edges and faces of the mesh, the edges from [`build_edge_adjacency`].
+    pub fn compute(mesh: &Mesh) -> Self {
+        let mut used = vec![false; mesh.vertices.len()];
+        for &i in mesh.triangles.iter().flatten() {
+            used[i] = true;
+        }
+        let vertex_count = used.iter().filter(|&&used| used).count();
+        let edge_count = build_edge_adjacency(mesh).len();
+        let face_count = mesh.triangles.len();
+        Topology { vertex_count, edge_count, face_count, euler_characteristic: vertex_count as i64 - edge_count as i64 + face_count as i64 }
+    }
+
+    /// The genus `(2 - chi) / 2` of a closed orientable surface, or `None` if the Euler characteristic is odd
+    /// or above 2, which no such surface has. The result is only meaningful for a single connected, closed and
+    /// manifold surface, see [`crate::analyze_edges`] and [`crate::split_components`].
+    pub fn genus(&self) -> Option<i64> {
+        (self.euler_characteristic <= 2 && self.euler_characteristic % 2 == 0).then(|| (2 - self.euler_characteristic) / 2)
+    }
+}
+
+#[test]
+fn test_euler_characteristic() {
+    let cube = crate::parse_obj("tests/cube.obj").unwrap();
+    assert_eq!(compute_euler_characteristic(&cube), 2);
+    assert_eq!(Topology::compute(&cube), Topology { vertex_count: 8, edge_count: 18, face_count: 12, euler_characteristic: 2 });
+    assert_eq!(Topology::compute(&cube).genus(), Some(0));
+
+    let torus = crate::parse_obj("tests/torus.obj").unwrap();
+    assert_eq!(compute_euler_characteristic(&torus), 0);
+    assert_eq!(Topology::compute(&torus).genus(), Some(1));
+
+    // two separate spheres, which no single closed surface matches
+    let two_tetrahedra = crate::parse_input("tests/two_tetrahedra.txt").unwrap();
+    assert_eq!(Topology::compute(&two_tetrahedra).genus(), None);
+}
+
+/// The Euler characteristic `V - E + F` of the mesh, see [`Topology`].
+pub fn compute_euler_characteristic(mesh: &Mesh) -> i64 {
+    Topology::compute(mesh).euler_characteristic
+}
+
+#[test]
+fn test_total_surface_area() {
+    // three right-angle faces of area 1/2 and the slanted face, an equilateral triangle of side sqrt(2)
+    let mesh = crate::parse_input("tests/input.txt").unwrap();
+    assert!((total_surface_area(&mesh) - (1.5 + 3.0f64.sqrt() / 2.0)).abs() < 1e-12);
+}
+
+/// Sum of the areas of the triangles of the mesh.
+pub fn total_surface_area(mesh: &Mesh) -> f64 {
+    mesh.triangles.iter().map(|triangle| triangle_area(mesh, triangle)).sum()
+}
+
+/// Area of a triangle, half the length of the cross product of its edges 0->1 and 0->2.
+pub fn triangle_area(mesh: &Mesh, triangle: &[usize; 3]) -> f64 {
+    let [v0, v1, v2] = triangle.map(|i| mesh.vertices[i]);
+    (v1 - v0).cross(&(v2 - v0)).norm() / 2.0
+}
+
+#[test]
+fn test_area_report() {
+    let mesh = crate::parse_input("tests/input.txt").unwrap();
+    let report = AreaReport::compute(&mesh).unwrap();
+    let slanted = 3.0f64.sqrt() / 2.0;
+    assert_eq!((report.smallest, report.min), (0, 0.5));
+    assert_eq!(report.largest, 3);
+    assert!((report.max - slanted).abs() < 1e-12);
+    let mean = (1.5 + slanted) / 4.0;
+    assert!((report.mean - mean).abs() < 1e-12);
+    let variance = (3.0 * (0.5 - mean).powi(2) + (slanted - mean).powi(2)) / 4.0;
+    assert!((report.std_dev - variance.sqrt()).abs() < 1e-12);
+
+    assert_eq!(AreaReport::compute(&Mesh::default()), None);
+}
+
+/// Distribution of the triangle areas of a mesh.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AreaReport {
+    pub min: f64,
+    /// Index of the first triangle of area `min`.
+    pub smallest: usize,
+    pub max: f64,
+    /// Index of the first triangle of area `max`.
+    pub largest: usize,
+    pub mean: f64,
+    /// Population standard deviation of the areas.
+    pub std_dev: f64,
+}
+
+impl AreaReport {
+    /// Compute the area distribution of the mesh, or `None` if it has no triangles.
+    pub fn compute(mesh: &Mesh) -> Option<Self> {
+        let areas: Vec<f64> = mesh.triangles.iter().map(|triangle| triangle_area(mesh, triangle)).collect();
+        let mut report = AreaReport { min: *areas.first()?, smallest: 0, max: areas[0], largest: 0, mean: 0.0, std_dev: 0.0 };
+        for (t, &area) in areas.iter().enumerate() {
+            if area < report.min {
+                (report.min, report.smallest) = (area, t);
+            }
+            if area > report.max {
+                (report.max, report.largest) = (area, t);
+            }
+        }
+        report.mean = areas.iter().sum::<f64>() / areas.len() as f64;
+        report.std_dev = (areas.iter().map(|area| (area - report.mean).powi(2)).sum::<f64>() / areas.len() as f64).sqrt();
+        Some(report)
+    }
+}
+
+#[test]
+fn test_bounding_box() {
+    let mesh = crate::parse_input("tests/input.txt").unwrap();
+    assert_eq!(bounding_box(&mesh), Some((Vector3::new(0.0, 0.0, 0.0), Vector3::new(1.0, 1.0, 1.0))));
+
+    let empty = Mesh { vertices: vec![], triangles: vec![], colors: None, groups: None };
+    assert_eq!(bounding_box(&empty), None);
+}
+
+/// Smallest and largest coordinates of the vertices on each axis, or `None` if the mesh has no vertices.
+pub fn bounding_box(mesh: &Mesh) -> Option<(Vector3<f64>, Vector3<f64>)> {
+    let first = *mesh.vertices.first()?;
+    Some(mesh.vertices.iter().fold((first, first), |(min, max), point| (min.inf(point), max.sup(point))))
+}
+
+/// Center, axes and side lengths of an oriented bounding box, see [`compute_obb`].
+pub type OrientedBox = (Vector3<f64>, [Vector3<f64>; 3], Vector3<f64>);
+
+#[test]
+fn test_compute_obb() {
+    // a 10 by 2 by 1 box, turned by 45 degrees around the z axis
+    let direction = Vector3::new(1.0, 1.0, 0.0).normalize();
+    let across = Vector3::new(-1.0, 1.0, 0.0).normalize();
+    let center = Vector3::new(3.0, -2.0, 1.0);
+    let mut vertices = Vec::new();
+    for a in [-5.0, 5.0] {
+        for b in [-1.0, 1.0] {
+            for c in [-0.5, 0.5] {
+                vertices.push(center + direction * a + across * b + Vector3::z() * c);
+            }
+        }
+    }
+    let mesh = Mesh { vertices, triangles: vec![], colors: None, groups: None };
+
+    let (obb_center, axes, extents) = compute_obb(&mesh).unwrap();
+    assert!((obb_center - center).norm() < 1e-9);
+    assert!((axes[0].dot(&direction).abs() - 1.0).abs() < 1e-9);
+    assert!((axes[1].dot(&across).abs() - 1.0).abs() < 1e-9);
+    assert!((extents - Vector3::new(10.0, 2.0, 1.0)).norm() < 1e-9);
+
+    assert_eq!(compute_obb(&Mesh::default()), None);
+}
+
+/// Oriented bounding box of the vertices, found from their principal axes, or `None` if the mesh has no vertices.
+/// Returns the center of the box, its three orthonormal axes and the length of the box along each of them,
+/// with the axes ordered from the longest side to the shortest.
+///
+/// The axes are the eigenvectors of the covariance matrix of the vertex positions, which gives a tight box for
+/// elongated meshes but not always the smallest possible one.
+pub fn compute_obb(mesh: &Mesh) -> Option<OrientedBox> {
+    if mesh.vertices.is_empty() {
+        return None;
+    }
+    let mean = mesh.vertices.iter().sum::<Vector3<f64>>() / mesh.vertices.len() as f64;
+    let covariance = mesh.vertices.iter().map(|point| (point - mean) * (point - mean).transpose()).sum::<Matrix3<f64>>()
+        / mesh.vertices.len() as f64;
+    let eigenvectors = covariance.symmetric_eigen().eigenvectors;
+
+    let mut sides: Vec<(Vector3<f64>, f64, f64)> = eigenvectors
+        .column_iter()
+        .map(|axis| {
+            let axis: Vector3<f64> = axis.into();
+            let (min, max) = mesh.vertices.iter().map(|point| (point - mean).dot(&axis)).fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), t| (min.min(t), max.max(t)));
+            (axis, min, max)
+        })
+        .collect();
+    sides.sort_by(|a, b| (b.2 - b.1).total_cmp(&(a.2 - a.1)));
+
+    let center = mean + sides.iter().map(|(axis, min, max)| axis * ((min + max) / 2.0)).sum::<Vector3<f64>>();
+    let axes = [sides[0].0, sides[1].0, sides[2].0];
+    let extents = Vector3::new(sides[0].2 - sides[0].1, sides[1].2 - sides[1].1, sides[2].2 - sides[2].1);
+    Some((center, axes, extents))
+}
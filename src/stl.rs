@@ -0,0 +1,191 @@
+//! STL support, in both the ASCII and the binary flavour.
+//!
+//! STL files store every facet with its own copy of the vertex coordinates, so the readers
+//! merge coincident vertices into a shared vertex list while building the triangles.
+
+use nalgebra::Vector3;
+
+use crate::cleanup::VertexMerger;
+use crate::{parse_token, Mesh, ParseError, PrecisionSpec};
+
+/// Vertices closer than this on every axis are merged into a single vertex.
+const STL_MERGE_EPSILON: f64 = 1e-9;
+
+#[test]
+fn test_parse_stl_ascii() {
+    let mesh = parse_stl_ascii("tests/tetra.stl").unwrap();
+    assert_eq!(mesh.vertices.len(), 4);
+    assert_eq!(mesh.triangles, vec![[0, 1, 2], [0, 3, 1], [0, 2, 3], [1, 3, 2]]);
+}
+
+/// Parse an ASCII STL file.
+/// The `vertex` lines of every `facet ... endfacet` block form one triangle; facet normals are ignored
+/// since they are recomputed from the winding anyway.
+pub fn parse_stl_ascii(in_path: &str) -> Result<Mesh, ParseError> {
+    let contents = crate::read_input_to_string(in_path)?;
+    stl_ascii_from_str(&contents)
+}
+
+/// Parse the contents of an ASCII STL file.
+fn stl_ascii_from_str(contents: &str) -> Result<Mesh, ParseError> {
+    let mut merger = VertexMerger::new(STL_MERGE_EPSILON);
+    let mut triangles: Vec<[usize; 3]> = Vec::new();
+    let mut facet: Vec<usize> = Vec::with_capacity(3);
+    for (i, line) in contents.lines().enumerate() {
+        let line_no = i + 1;
+        let mut tokens = line.split_whitespace();
+        match tokens.next() {
+            Some("facet") => facet.clear(),
+            Some("vertex") => {
+                let x = parse_token::<f64>(tokens.next(), line_no, "x coordinate")?;
+                let y = parse_token::<f64>(tokens.next(), line_no, "y coordinate")?;
+                let z = parse_token::<f64>(tokens.next(), line_no, "z coordinate")?;
+                facet.push(merger.index_of(Vector3::new(x, y, z)));
+            }
+            Some("endfacet") => {
+                if facet.len() != 3 {
+                    return Err(ParseError::MissingValue { line: line_no, reason: format!("facet has {} vertices, expected 3", facet.len()) });
+                }
+                triangles.push([facet[0], facet[1], facet[2]]);
+            }
+            _ => {}
+        }
+    }
+
+    Ok(Mesh { vertices: merger.vertices, triangles, colors: None, groups: None })
+}
+
+/// Size in bytes of the binary STL header: an 80-byte comment followed by the u32 triangle count.
+const STL_BINARY_HEADER_LEN: usize = 84;
+/// Size in bytes of one binary STL facet: 12 f32 values (normal and three vertices) and a u16 attribute.
+const STL_BINARY_FACET_LEN: usize = 50;
+
+/// Build a binary STL buffer holding the given triangles, used by the tests below.
+#[cfg(test)]
+fn stl_binary_bytes(triangles: &[[[f32; 3]; 3]]) -> Vec<u8> {
+    let mut bytes = vec![0u8; 80];
+    bytes.extend_from_slice(&(triangles.len() as u32).to_le_bytes());
+    for triangle in triangles {
+        bytes.extend_from_slice(&[0u8; 12]);
+        for vertex in triangle {
+            for coord in vertex {
+                bytes.extend_from_slice(&coord.to_le_bytes());
+            }
+        }
+        bytes.extend_from_slice(&[0u8; 2]);
+    }
+    bytes
+}
+
+#[test]
+fn test_parse_stl_binary() {
+    let bytes = stl_binary_bytes(&[
+        [[0.0, 0.0, 0.0], [0.0, 0.0, 1.0], [0.0, 1.0, 0.0]],
+        [[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 0.0, 1.0]],
+        [[0.0, 0.0, 0.0], [0.0, 1.0, 0.0], [1.0, 0.0, 0.0]],
+        [[0.0, 0.0, 1.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]],
+    ]);
+    let in_path = std::env::temp_dir().join("mesh_reorienter_tetra_binary.stl");
+    std::fs::write(&in_path, &bytes).unwrap();
+
+    let mesh = parse_stl_binary(in_path.to_str().unwrap()).unwrap();
+    assert_eq!(mesh.triangles.len(), 4);
+    assert_eq!(mesh.vertices.len(), 4);
+    assert_eq!(parse_stl(in_path.to_str().unwrap()).unwrap(), mesh);
+}
+
+#[test]
+fn test_parse_stl_binary_truncated() {
+    let mut bytes = stl_binary_bytes(&[[[0.0, 0.0, 0.0], [0.0, 0.0, 1.0], [0.0, 1.0, 0.0]]]);
+    bytes.truncate(bytes.len() - 10);
+    assert!(matches!(stl_binary_from_bytes(&bytes), Err(ParseError::Binary { offset: 84, .. })));
+}
+
+/// Parse a binary STL file.
+/// The 80-byte header is skipped, then the little-endian u32 triangle count is read,
+/// followed by one 50-byte record per facet.
+pub fn parse_stl_binary(in_path: &str) -> Result<Mesh, ParseError> {
+    let bytes = crate::read_input(in_path)?;
+    stl_binary_from_bytes(&bytes)
+}
+
+/// Parse the contents of a binary STL file.
+fn stl_binary_from_bytes(bytes: &[u8]) -> Result<Mesh, ParseError> {
+    if bytes.len() < STL_BINARY_HEADER_LEN {
+        return Err(ParseError::Binary { offset: 0, reason: String::from("file is too short for a binary STL header") });
+    }
+    let n_triangles = u32::from_le_bytes([bytes[80], bytes[81], bytes[82], bytes[83]]) as usize;
+
+    let read_f32 = |offset: usize| f32::from_le_bytes([bytes[offset], bytes[offset + 1], bytes[offset + 2], bytes[offset + 3]]) as f64;
+
+    let mut merger = VertexMerger::new(STL_MERGE_EPSILON);
+    let mut triangles: Vec<[usize; 3]> = Vec::with_capacity(n_triangles.min(bytes.len() / STL_BINARY_FACET_LEN));
+    for t in 0..n_triangles {
+        let facet = STL_BINARY_HEADER_LEN + t * STL_BINARY_FACET_LEN;
+        if facet + STL_BINARY_FACET_LEN > bytes.len() {
+            return Err(ParseError::Binary { offset: facet, reason: format!("file ends inside facet {} of {}", t, n_triangles) });
+        }
+        let mut triangle = [0; 3];
+        for (k, index) in triangle.iter_mut().enumerate() {
+            // the first 12 bytes of a facet hold its normal, which is skipped
+            let vertex = facet + 12 + k * 12;
+            *index = merger.index_of(Vector3::new(read_f32(vertex), read_f32(vertex + 4), read_f32(vertex + 8)));
+        }
+        triangles.push(triangle);
+    }
+
+    Ok(Mesh { vertices: merger.vertices, triangles, colors: None, groups: None })
+}
+
+#[test]
+fn test_parse_stl_detects_ascii() {
+    assert_eq!(parse_stl("tests/tetra.stl").unwrap(), parse_stl_ascii("tests/tetra.stl").unwrap());
+}
+
+/// Parse an STL file, detecting whether it is ASCII or binary.
+/// A file is read as ASCII when it starts with `solid` and parses as ASCII STL. Binary files whose
+/// header happens to start with `solid` are recognised because they yield no ASCII facets.
+pub fn parse_stl(in_path: &str) -> Result<Mesh, ParseError> {
+    let bytes = crate::read_input(in_path)?;
+    if bytes.starts_with(b"solid") {
+        if let Ok(contents) = std::str::from_utf8(&bytes) {
+            let binary_len_matches = bytes.len() >= STL_BINARY_HEADER_LEN
+                && bytes.len() == STL_BINARY_HEADER_LEN
+                    + STL_BINARY_FACET_LEN * u32::from_le_bytes([bytes[80], bytes[81], bytes[82], bytes[83]]) as usize;
+            match stl_ascii_from_str(contents) {
+                Ok(mesh) if !mesh.triangles.is_empty() || !binary_len_matches => return Ok(mesh),
+                Err(err) if !binary_len_matches => return Err(err),
+                _ => {}
+            }
+        }
+    }
+    stl_binary_from_bytes(&bytes)
+}
+
+#[test]
+fn test_write_stl_round_trip() {
+    let out_path = std::env::temp_dir().join("mesh_reorienter_tetra.stl");
+    let out_path = out_path.to_str().unwrap();
+
+    let mesh = parse_stl("tests/tetra.stl").unwrap();
+    write_stl(out_path, &mesh, PrecisionSpec::uniform(6));
+    assert_eq!(parse_stl_ascii(out_path).unwrap(), mesh);
+}
+
+/// Write the mesh as an ASCII STL file, with one facet per triangle. Every facet repeats the coordinates
+/// of its vertices and carries the unit normal of the triangle, or a zero normal for a degenerate one.
+pub fn write_stl(out_path: &str, mesh: &Mesh, precision: PrecisionSpec) {
+    let mut out_contents = String::from("solid mesh\n");
+    for triangle in &mesh.triangles {
+        let [v0, v1, v2] = triangle.map(|i| mesh.vertices[i]);
+        let normal = (v1 - v0).cross(&(v2 - v0)).try_normalize(0.0).unwrap_or_default();
+        out_contents.push_str(&format!("  facet normal {}\n    outer loop\n", precision.format_point(&normal)));
+        for vertex in [v0, v1, v2] {
+            out_contents.push_str(&format!("      vertex {}\n", precision.format_point(&vertex)));
+        }
+        out_contents.push_str("    endloop\n  endfacet\n");
+    }
+    out_contents.push_str("endsolid mesh\n");
+    crate::write_file(out_path, |writer| writer.write_all(out_contents.as_bytes()))
+        .expect("Something went wrong writing the file");
+}
@@ -0,0 +1,400 @@
+//! Winding consistency through flood fill over the triangle adjacency.
+//!
+//! Two triangles sharing an edge are consistently wound when they traverse that edge in opposite
+//! directions. Starting from a seed triangle, the orientation is propagated to every neighbour,
+//! which works for any surface shape, unlike the centroid comparison of [`crate::reorient_mesh`].
+
+use std::collections::{HashMap, VecDeque};
+
+use nalgebra::Vector3;
+
+use crate::{find_misoriented_triangles, signed_volume, split_components, Mesh, NormalBackend};
+
+/// Map every undirected edge, stored as `(min, max)` vertex indices, to the faces containing it.
+/// Faces are vertex loops of any length, so the same code serves triangles and polygons.
+fn edge_face_map<F: AsRef<[usize]>>(faces: &[F]) -> HashMap<(usize, usize), Vec<usize>> {
+    let mut edges: HashMap<(usize, usize), Vec<usize>> = HashMap::new();
+    for (t, face) in faces.iter().enumerate() {
+        for (a, b) in face_edges(face.as_ref()) {
+            edges.entry((a.min(b), a.max(b))).or_default().push(t);
+        }
+    }
+    edges
+}
+
+#[test]
+fn test_build_edge_adjacency() {
+    let mesh = crate::parse_input("tests/input.txt").unwrap();
+    let adjacency = build_edge_adjacency(&mesh);
+    assert_eq!(adjacency.len(), 6);
+    assert!(adjacency.values().all(|sharing| sharing.len() == 2));
+    assert_eq!(adjacency[&(0, 2)], vec![0, 1]);
+}
+
+/// Map every undirected edge of the mesh, stored as `(min, max)` vertex indices, to the indices of
+/// the triangles containing it, in increasing order.
+pub fn build_edge_adjacency(mesh: &Mesh) -> HashMap<(usize, usize), Vec<usize>> {
+    edge_face_map(&mesh.triangles)
+}
+
+/// The directed edges of a face, following its vertex loop.
+fn face_edges(face: &[usize]) -> impl Iterator<Item = (usize, usize)> + '_ {
+    (0..face.len()).map(move |k| (face[k], face[(k + 1) % face.len()]))
+}
+
+/// Whether `face` traverses the edge from `a` to `b` in that direction.
+fn traverses(face: &[usize], a: usize, b: usize) -> bool {
+    face_edges(face).any(|edge| edge == (a, b))
+}
+
+/// Reverse the vertex loop of a face while keeping its first vertex, which for a triangle swaps the last two vertices.
+pub(crate) fn flip_face(face: &mut [usize]) {
+    face[1..].reverse();
+}
+
+/// Whether every edge is traversed in opposite directions by the faces sharing it.
+#[cfg(test)]
+fn is_consistent<F: AsRef<[usize]>>(faces: &[F]) -> bool {
+    edge_face_map(faces).iter().all(|(&(a, b), sharing)| {
+        sharing.iter().filter(|&&t| traverses(faces[t].as_ref(), a, b)).count() <= 1
+            && sharing.iter().filter(|&&t| traverses(faces[t].as_ref(), b, a)).count() <= 1
+    })
+}
+
+#[test]
+fn test_make_winding_consistent_torus() {
+    let mut mesh = crate::parse_obj("tests/torus.obj").unwrap();
+    assert!(!is_consistent(&mesh.triangles));
+
+    // the centroid lies in the hole of the torus, so comparing against it flips the inner faces wrongly
+    let mut centroid_mesh = mesh.clone();
+    crate::reorient_mesh(&mut centroid_mesh);
+    assert!(!is_consistent(&centroid_mesh.triangles));
+
+    make_winding_consistent(&mut mesh);
+    assert!(is_consistent(&mesh.triangles));
+}
+
+#[test]
+fn test_make_winding_consistent_deterministic() {
+    let mut mesh = crate::parse_obj("tests/torus.obj").unwrap();
+    // shuffle the triangles with a fixed linear congruential sequence, so that neighbours are far apart in the list
+    let mut state: u64 = 12345;
+    for i in (1..mesh.triangles.len()).rev() {
+        state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        mesh.triangles.swap(i, (state >> 33) as usize % (i + 1));
+    }
+
+    let mut first = mesh.clone();
+    make_winding_consistent(&mut first);
+    let mut second = mesh.clone();
+    make_winding_consistent(&mut second);
+    assert_eq!(first, second);
+    assert_eq!(first.triangles[0], mesh.triangles[0]);
+    assert!(is_consistent(&first.triangles));
+}
+
+/// Make the winding of the mesh consistent by flood fill over the edge adjacency.
+/// The seed of every connected group of triangles is its lowest-index triangle, which is never flipped:
+/// the group takes its orientation, and every neighbour traversing a shared edge in the same direction as
+/// the triangle it was reached from is flipped. The neighbours are visited in a fixed order, so the result
+/// only depends on the triangle list and running the propagation again gives the same output.
+pub fn make_winding_consistent(mesh: &mut Mesh) {
+    make_faces_consistent(&mut mesh.triangles);
+}
+
+#[test]
+fn test_find_inconsistent_triangles() {
+    let mesh = crate::parse_input("tests/input.txt").unwrap();
+    let flips = find_inconsistent_triangles(&mesh);
+    assert_eq!(flips, vec![1, 3]);
+
+    let mut flipped = mesh.clone();
+    flip_triangles(&mut flipped, &flips);
+    let mut consistent = mesh.clone();
+    make_winding_consistent(&mut consistent);
+    assert_eq!(flipped, consistent);
+}
+
+/// Indices, in increasing order, of the triangles that [`make_winding_consistent`] would flip, without changing the mesh.
+pub fn find_inconsistent_triangles(mesh: &Mesh) -> Vec<usize> {
+    faces_to_flip(&mesh.triangles)
+}
+
+/// Flip the triangles at the given indices, such as those found by [`find_inconsistent_triangles`].
+pub fn flip_triangles(mesh: &mut Mesh, indices: &[usize]) {
+    for &t in indices {
+        flip_face(&mut mesh.triangles[t]);
+    }
+}
+
+#[test]
+fn test_count_algorithm_disagreements() {
+    let tetrahedron = crate::parse_input("tests/input.txt").unwrap();
+    assert_eq!(count_algorithm_disagreements(&tetrahedron, &crate::compute_centroid(&tetrahedron), NormalBackend::CrossProduct), 0);
+
+    // the centroid of the band lies outside it, so the centroid approach flips some of its outer faces
+    let crescent = crate::parse_obj("tests/crescent.obj").unwrap();
+    assert!(count_algorithm_disagreements(&crescent, &crate::compute_centroid(&crescent), crate::NormalBackend::CrossProduct) > 0);
+
+    // a smaller copy nested inside the tetrahedron and wound inward: the flood fill keeps each component
+    // wound as its seed, so the copy is reversed as a whole and the two approaches agree on both components
+    let mut nested = tetrahedron.clone();
+    crate::reorient_mesh(&mut nested);
+    let centroid = crate::compute_centroid(&nested);
+    let n = nested.vertices.len();
+    nested.vertices.extend(tetrahedron.vertices.iter().map(|v| centroid + (v - centroid) / 2.0));
+    nested.triangles.extend(nested.triangles.clone().iter().map(|&[a, b, c]| [a + n, c + n, b + n]));
+    assert_eq!(count_algorithm_disagreements(&nested, &crate::compute_centroid(&nested), NormalBackend::CrossProduct), 0);
+}
+
+/// Number of triangles on which the centroid approach, against `centroid` with `backend`, and the flood fill of
+/// [`make_winding_consistent`] disagree about the winding. The flood fill keeps the winding of the seed triangle of
+/// every connected component, so the two are compared up to reversing each component as a whole, counting the
+/// smaller of the two disagreements per component. On a convex closed mesh both give the same winding and the count is 0.
+pub fn count_algorithm_disagreements(mesh: &Mesh, centroid: &Vector3<f64>, backend: NormalBackend) -> usize {
+    let mut centroid_flips = vec![false; mesh.triangles.len()];
+    for t in find_misoriented_triangles(mesh, centroid, backend) {
+        centroid_flips[t] = true;
+    }
+    let mut flood_flips = vec![false; mesh.triangles.len()];
+    for t in find_inconsistent_triangles(mesh) {
+        flood_flips[t] = true;
+    }
+    split_components(mesh)
+        .iter()
+        .map(|component| {
+            let disagreements = component.iter().filter(|&&t| centroid_flips[t] != flood_flips[t]).count();
+            disagreements.min(component.len() - disagreements)
+        })
+        .sum()
+}
+
+#[test]
+fn test_only_winding_changed() {
+    let original = [[0, 1, 2], [0, 3, 2]];
+    assert!(only_winding_changed(&original, &[[0, 1, 2], [0, 2, 3]]));
+    assert!(!only_winding_changed(&original, &[[0, 2, 3], [0, 1, 2]]));
+    assert!(!only_winding_changed(&original, &[[0, 1, 2]]));
+    assert!(!only_winding_changed(&original, &[[1, 2, 0], [0, 3, 2]]));
+}
+
+/// Whether `triangles` holds the `original` triangles at the same positions, each either unchanged or with its
+/// last two indices swapped. This is what every reorientation guarantees: triangles are never reordered, removed
+/// or rotated, so the i-th output triangle is the i-th input triangle.
+pub fn only_winding_changed(original: &[[usize; 3]], triangles: &[[usize; 3]]) -> bool {
+    original.len() == triangles.len()
+        && original.iter().zip(triangles).all(|(&[a, b, c], triangle)| *triangle == [a, b, c] || *triangle == [a, c, b])
+}
+
+#[test]
+fn test_copy_reference_winding() {
+    let reference = crate::parse_input("tests/input.txt").unwrap();
+    // the same surface with its points listed in reverse order, one triangle flipped and one new triangle
+    let mut mesh = Mesh {
+        vertices: reference.vertices.iter().rev().copied().chain([Vector3::new(1.0, 1.0, 1.0)]).collect(),
+        triangles: vec![[3, 2, 1], [3, 1, 0], [3, 0, 2], [2, 0, 1], [4, 2, 1]],
+        colors: None,
+        groups: None,
+    };
+    assert_eq!(copy_reference_winding(&mut mesh, &reference), vec![true, true, true, true, false]);
+    assert_eq!(mesh.triangles, vec![[3, 2, 1], [3, 0, 1], [3, 0, 2], [2, 1, 0], [4, 2, 1]]);
+}
+
+/// Give every triangle of `mesh` made of the same three points as a triangle of `reference` the winding of that
+/// triangle, flipping it if needed. Points are compared by their exact coordinates rather than by index, so the
+/// reference may number its points differently. Returns, for every triangle, whether it was matched.
+pub fn copy_reference_winding(mesh: &mut Mesh, reference: &Mesh) -> Vec<bool> {
+    // adding zero turns -0.0 into 0.0, so that both compare equal
+    let key = |point: &Vector3<f64>| [point.x, point.y, point.z].map(|coord| (coord + 0.0).to_bits());
+    let mut windings: HashMap<[[u64; 3]; 3], [[u64; 3]; 3]> = HashMap::new();
+    for triangle in &reference.triangles {
+        let points = triangle.map(|i| key(&reference.vertices[i]));
+        let mut sorted = points;
+        sorted.sort_unstable();
+        windings.entry(sorted).or_insert(points);
+    }
+
+    let mut matched = vec![false; mesh.triangles.len()];
+    for (t, triangle) in mesh.triangles.iter_mut().enumerate() {
+        let points = triangle.map(|i| key(&mesh.vertices[i]));
+        let mut sorted = points;
+        sorted.sort_unstable();
+        let Some(winding) = windings.get(&sorted) else {
+            continue;
+        };
+        let start = winding.iter().position(|point| *point == points[0]).expect("the triangles have the same points");
+        if winding[(start + 1) % 3] != points[1] {
+            flip_face(triangle);
+        }
+        matched[t] = true;
+    }
+    matched
+}
+
+/// Flood fill behind [`make_winding_consistent`], for faces of any length.
+pub(crate) fn make_faces_consistent<F: AsRef<[usize]> + AsMut<[usize]>>(faces: &mut [F]) {
+    for t in faces_to_flip(faces) {
+        flip_face(faces[t].as_mut());
+    }
+}
+
+/// Flood fill behind [`find_inconsistent_triangles`], for faces of any length.
+/// The faces are left untouched: the flips decided so far are tracked on the side, and a flipped
+/// face traverses each of its edges in the opposite direction to the one stored.
+pub(crate) fn faces_to_flip<F: AsRef<[usize]>>(faces: &[F]) -> Vec<usize> {
+    let edges = edge_face_map(faces);
+    let mut visited = vec![false; faces.len()];
+    let mut flipped = vec![false; faces.len()];
+    let mut queue: VecDeque<usize> = VecDeque::new();
+
+    for seed in 0..faces.len() {
+        if visited[seed] {
+            continue;
+        }
+        visited[seed] = true;
+        queue.push_back(seed);
+
+        while let Some(t) = queue.pop_front() {
+            for (a, b) in face_edges(faces[t].as_ref()) {
+                let (a, b) = if flipped[t] { (b, a) } else { (a, b) };
+                for &neighbour in &edges[&(a.min(b), a.max(b))] {
+                    if visited[neighbour] {
+                        continue;
+                    }
+                    visited[neighbour] = true;
+                    flipped[neighbour] = traverses(faces[neighbour].as_ref(), a, b);
+                    queue.push_back(neighbour);
+                }
+            }
+        }
+    }
+    (0..faces.len()).filter(|&t| flipped[t]).collect()
+}
+
+#[test]
+fn test_analyze_edges() {
+    use nalgebra::Vector3;
+
+    // three triangles hinged on the edge 0-1, like the pages of a book
+    let mesh = Mesh {
+        vertices: vec![
+            Vector3::new(0.0, 0.0, 0.0),
+            Vector3::new(0.0, 0.0, 1.0),
+            Vector3::new(1.0, 0.0, 0.0),
+            Vector3::new(0.0, 1.0, 0.0),
+            Vector3::new(-1.0, 0.0, 0.0),
+        ],
+        triangles: vec![[0, 1, 2], [1, 0, 3], [0, 1, 4]],
+        colors: None,
+        groups: None,
+    };
+    let report = analyze_edges(&mesh);
+    assert_eq!(report.non_manifold_edges, vec![(0, 1)]);
+    assert_eq!(report.boundary_edges.len(), 6);
+
+    let closed = crate::parse_obj("tests/cube.obj").unwrap();
+    assert_eq!(analyze_edges(&closed), EdgeReport::default());
+}
+
+/// Edges of a mesh that keep it from being a closed manifold surface, each stored as `(min, max)` vertex indices.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EdgeReport {
+    /// Edges belonging to a single triangle, on the border of a hole or of an open surface.
+    pub boundary_edges: Vec<(usize, usize)>,
+    /// Edges shared by three or more triangles, around which the orientation is ambiguous.
+    pub non_manifold_edges: Vec<(usize, usize)>,
+}
+
+/// Find the boundary and non-manifold edges of the mesh, in increasing order.
+/// On a watertight surface every edge is shared by exactly two triangles and both lists are empty.
+pub fn analyze_edges(mesh: &Mesh) -> EdgeReport {
+    let mut report = EdgeReport::default();
+    for (edge, sharing) in build_edge_adjacency(mesh) {
+        match sharing.len() {
+            1 => report.boundary_edges.push(edge),
+            2 => {}
+            _ => report.non_manifold_edges.push(edge),
+        }
+    }
+    report.boundary_edges.sort_unstable();
+    report.non_manifold_edges.sort_unstable();
+    report
+}
+
+#[test]
+fn test_orient_outward() {
+    let mut mesh = crate::parse_obj("tests/cube.obj").unwrap();
+    for triangle in &mut mesh.triangles {
+        triangle.swap(1, 2);
+    }
+    assert!(signed_volume(&mesh) < 0.0);
+
+    orient_outward(&mut mesh);
+    assert!((signed_volume(&mesh) - 1.0).abs() < 1e-12);
+}
+
+/// Flip every triangle if the signed volume of the mesh is negative, so that the normals point outwards.
+/// The winding should already be consistent, see [`make_winding_consistent`].
+pub fn orient_outward(mesh: &mut Mesh) {
+    if is_inside_out(mesh) {
+        flip_all(mesh);
+    }
+}
+
+#[test]
+fn test_flip_all() {
+    let input = crate::parse_input("tests/input.txt").unwrap();
+    let mut mesh = input.clone();
+    flip_all(&mut mesh);
+    assert_eq!(mesh.triangles[0], [0, 2, 1]);
+    flip_all(&mut mesh);
+    assert_eq!(mesh.triangles, input.triangles);
+}
+
+/// Flip every triangle unconditionally, for meshes known to be uniformly inside-out.
+pub fn flip_all(mesh: &mut Mesh) {
+    for triangle in &mut mesh.triangles {
+        flip_face(triangle);
+    }
+}
+
+#[test]
+fn test_apply_winding() {
+    let input = crate::parse_input("tests/input.txt").unwrap();
+    let centroid = crate::compute_centroid(&input);
+    let reversed = |mesh: &Mesh| (0..input.triangles.len()).filter(|&t| mesh.triangles[t] != input.triangles[t]).collect::<Vec<_>>();
+
+    let mut ccw = input.clone();
+    crate::reorient_mesh_around(&mut ccw, &centroid);
+    apply_winding(&mut ccw, Winding::CounterClockwise);
+    let mut cw = input.clone();
+    crate::reorient_mesh_around(&mut cw, &centroid);
+    apply_winding(&mut cw, Winding::Clockwise);
+
+    assert_eq!(reversed(&ccw), vec![1, 3]);
+    assert_eq!(reversed(&cw), vec![0, 2]);
+}
+
+/// Vertex order of a triangle whose normal points outwards, as seen from outside the surface.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Winding {
+    /// Counter-clockwise, the convention of the cross product and of [`crate::reorient_mesh`].
+    #[default]
+    CounterClockwise,
+    /// Clockwise, as expected by some renderers and engines.
+    Clockwise,
+}
+
+/// Convert a mesh oriented with the counter-clockwise convention to the `winding` convention.
+/// For [`Winding::Clockwise`] every triangle is flipped, which amounts to negating the outward test.
+pub fn apply_winding(mesh: &mut Mesh, winding: Winding) {
+    if winding == Winding::Clockwise {
+        flip_all(mesh);
+    }
+}
+
+/// Whether the signed volume of the mesh is negative, in which case [`orient_outward`] would flip every triangle.
+pub fn is_inside_out(mesh: &Mesh) -> bool {
+    signed_volume(mesh) < 0.0
+}
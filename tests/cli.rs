@@ -0,0 +1,573 @@
+use std::process::Command;
+
+#[test]
+fn test_no_arguments_prints_usage() {
+    let output = Command::new(env!("CARGO_BIN_EXE_mesh-reorienter")).output().unwrap();
+    assert_eq!(output.status.code(), Some(2));
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("missing input path"));
+    assert!(stderr.contains("Usage: mesh-reorienter"));
+}
+
+#[test]
+fn test_dry_run_reports_flips() {
+    let output = Command::new(env!("CARGO_BIN_EXE_mesh-reorienter"))
+        .args(["--dry-run", "tests/input.txt"])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), "2 of 4 triangles would be flipped\n1 3\n");
+}
+
+#[test]
+fn test_check_consistent_mesh() {
+    let output = Command::new(env!("CARGO_BIN_EXE_mesh-reorienter"))
+        .args(["--check", "tests/reordered.txt"])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(0));
+    assert!(output.stdout.is_empty());
+}
+
+#[test]
+fn test_check_inconsistent_mesh() {
+    let output = Command::new(env!("CARGO_BIN_EXE_mesh-reorienter"))
+        .args(["--check", "tests/input.txt"])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(1));
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("2 of 4 triangles are not consistently oriented"));
+    assert!(stderr.contains("triangle 3: 1 2 3"));
+}
+
+#[test]
+fn test_range_limits_reorientation() {
+    let out_path = std::env::temp_dir().join("mesh_reorienter_range.txt");
+    let status = Command::new(env!("CARGO_BIN_EXE_mesh-reorienter"))
+        .args(["--centroid", "vertex", "--range", "2:4", "tests/input.txt"])
+        .arg(&out_path)
+        .status()
+        .unwrap();
+    assert!(status.success());
+
+    // the centroid approach flips triangles 1 and 3, but only triangle 3 lies in the range
+    let input = std::fs::read_to_string("tests/input.txt").unwrap();
+    let written = std::fs::read_to_string(&out_path).unwrap();
+    let input_rows: Vec<&str> = input.lines().skip(6).collect();
+    let written_rows: Vec<&str> = written.lines().skip(6).collect();
+    assert_eq!(written_rows, vec![input_rows[0], input_rows[1], input_rows[2], "1 3 2"]);
+}
+
+#[test]
+fn test_no_reorient_only_triangulates() {
+    let out_path = std::env::temp_dir().join("mesh_reorienter_no_reorient.obj");
+    let status = Command::new(env!("CARGO_BIN_EXE_mesh-reorienter"))
+        .args(["--no-reorient", "tests/quads.obj"])
+        .arg(&out_path)
+        .status()
+        .unwrap();
+    assert!(status.success());
+
+    // every quad becomes a fan of two triangles in its own vertex order, including the inward one
+    let written = std::fs::read_to_string(&out_path).unwrap();
+    let faces: Vec<&str> = written.lines().filter(|line| line.starts_with("f ")).collect();
+    assert_eq!(faces, vec!["f 1 4 3", "f 1 3 2", "f 1 5 6", "f 1 6 2"]);
+}
+
+#[test]
+fn test_stable_order_keeps_triangle_positions() {
+    let out_path = std::env::temp_dir().join("mesh_reorienter_stable_order.txt");
+    let status = Command::new(env!("CARGO_BIN_EXE_mesh-reorienter"))
+        .args(["--stable-order", "--components", "--outward", "tests/two_tetrahedra.txt"])
+        .arg(&out_path)
+        .status()
+        .unwrap();
+    assert!(status.success());
+
+    // every output row is the input row at the same position, possibly with its last two indices swapped
+    let input = std::fs::read_to_string("tests/two_tetrahedra.txt").unwrap();
+    let written = std::fs::read_to_string(&out_path).unwrap();
+    let input_rows: Vec<&str> = input.lines().skip(10).collect();
+    let written_rows: Vec<&str> = written.lines().skip(10).collect();
+    assert_eq!(written_rows.len(), input_rows.len());
+    for (input_row, written_row) in input_rows.iter().zip(&written_rows) {
+        let [a, b, c]: [&str; 3] = input_row.split(' ').collect::<Vec<_>>().try_into().unwrap();
+        assert!(*written_row == *input_row || *written_row == [a, c, b].join(" "), "{} became {}", input_row, written_row);
+    }
+    assert_ne!(written_rows, input_rows);
+}
+
+#[test]
+fn test_stable_order_rejects_triangle_list_changes() {
+    for option in ["--double-sided", "--split-output"] {
+        let out_path = std::env::temp_dir().join("mesh_reorienter_stable_order_rejected.txt");
+        let _ = std::fs::remove_file(&out_path);
+        let output = Command::new(env!("CARGO_BIN_EXE_mesh-reorienter"))
+            .args(["--stable-order", option, "--centroid", "vertex", "tests/input.txt"])
+            .arg(&out_path)
+            .output()
+            .unwrap();
+        assert_eq!(output.status.code(), Some(2), "{}", option);
+        assert!(String::from_utf8(output.stderr).unwrap().contains(&format!("{} cannot be combined with --stable-order", option)));
+        assert!(!out_path.exists());
+    }
+}
+
+#[test]
+fn test_warns_when_centroid_outside() {
+    let output = Command::new(env!("CARGO_BIN_EXE_mesh-reorienter"))
+        .args(["--dry-run", "--centroid", "vertex", "tests/crescent.obj"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert!(String::from_utf8(output.stderr).unwrap().contains("Warning: the centroid lies outside the surface"));
+}
+
+#[test]
+fn test_no_warning_when_centroid_inside() {
+    let output = Command::new(env!("CARGO_BIN_EXE_mesh-reorienter"))
+        .args(["--dry-run", "--centroid", "vertex", "tests/cube.obj"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert!(output.stderr.is_empty());
+}
+
+#[test]
+fn test_output_format_on_standard_output() {
+    let output = Command::new(env!("CARGO_BIN_EXE_mesh-reorienter"))
+        .args(["--output-format", "obj", "tests/input.txt", "-"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let lines: Vec<&str> = stdout.lines().collect();
+    assert_eq!(lines.len(), 8);
+    assert_eq!(lines[0], "v 0.0 0.0 0.0");
+    assert!(lines[..4].iter().all(|line| line.starts_with("v ")));
+    assert!(lines[4..].iter().all(|line| line.starts_with("f ")));
+}
+
+#[test]
+fn test_reference_point_matches_centroid_on_convex_mesh() {
+    let reoriented = |reference: &[&str], name: &str| {
+        let out_path = std::env::temp_dir().join(name);
+        let status = Command::new(env!("CARGO_BIN_EXE_mesh-reorienter"))
+            .args(reference)
+            .args(["tests/cube.obj"])
+            .arg(&out_path)
+            .status()
+            .unwrap();
+        assert!(status.success());
+        std::fs::read_to_string(&out_path).unwrap()
+    };
+
+    // the centre of the cube is a known interior point, and the vertex centroid of a convex mesh is one too
+    let with_reference = reoriented(&["--reference", "0.5", "0.5", "0.5"], "mesh_reorienter_reference.obj");
+    assert_eq!(with_reference, reoriented(&["--centroid", "vertex"], "mesh_reorienter_reference_centroid.obj"));
+}
+
+#[test]
+fn test_progress_silent_when_stderr_is_not_a_terminal() {
+    let out_path = std::env::temp_dir().join("mesh_reorienter_progress.obj");
+    let output = Command::new(env!("CARGO_BIN_EXE_mesh-reorienter"))
+        .args(["--progress", "--centroid", "vertex", "tests/cube.obj"])
+        .arg(&out_path)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    // standard error is piped here, so no bar is drawn into it
+    assert!(output.stderr.is_empty());
+}
+
+#[test]
+fn test_obb_prints_box() {
+    let output = Command::new(env!("CARGO_BIN_EXE_mesh-reorienter"))
+        .args(["--obb", "--dry-run", "tests/cube.obj"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("oriented bounding box center: (0.5, 0.5, 0.5)"), "{}", stderr);
+    assert_eq!(stderr.matches("side length 1").count(), 3, "{}", stderr);
+}
+
+#[test]
+fn test_flip_mask_matches_flips() {
+    let out_path = std::env::temp_dir().join("mesh_reorienter_flip_mask.txt");
+    let mask_path = std::env::temp_dir().join("mesh_reorienter_flip_mask.mask");
+    let status = Command::new(env!("CARGO_BIN_EXE_mesh-reorienter"))
+        .args(["--centroid", "vertex", "tests/input.txt"])
+        .arg(&out_path)
+        .arg("--flip-mask")
+        .arg(&mask_path)
+        .status()
+        .unwrap();
+    assert!(status.success());
+
+    let input = std::fs::read_to_string("tests/input.txt").unwrap();
+    let written = std::fs::read_to_string(&out_path).unwrap();
+    let flipped: Vec<&str> = input.lines().skip(6).zip(written.lines().skip(6)).map(|(before, after)| if before == after { "0" } else { "1" }).collect();
+    assert_eq!(flipped, vec!["0", "1", "0", "1"]);
+    assert_eq!(std::fs::read_to_string(&mask_path).unwrap().lines().collect::<Vec<_>>(), flipped);
+}
+
+#[test]
+fn test_reference_mesh_only_reorients_new_triangles() {
+    // the input with one more point and one more triangle, wound inwards
+    let in_path = std::env::temp_dir().join("mesh_reorienter_edited.txt");
+    let out_path = std::env::temp_dir().join("mesh_reorienter_edited_out.txt");
+    std::fs::write(&in_path, "5\n0.0 0.0 0.0\n0.0 0.0 1.0\n0.0 1.0 0.0\n1.0 0.0 0.0\n1.0 1.0 1.0\n5\n0 1 2\n0 3 2\n0 3 1\n1 2 3\n4 1 2\n").unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_mesh-reorienter"))
+        .args(["--centroid", "vertex", "--reference-mesh", "tests/input.txt"])
+        .arg(&in_path)
+        .arg(&out_path)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert!(String::from_utf8(output.stderr).unwrap().contains("Kept the winding of 4 of 5 triangles"));
+
+    // the reference keeps triangles 1 and 3 inward, which the centroid approach alone would flip
+    let written = std::fs::read_to_string(&out_path).unwrap();
+    assert_eq!(written.lines().skip(7).collect::<Vec<_>>(), vec!["0 1 2", "0 3 2", "0 3 1", "1 2 3", "4 2 1"]);
+}
+
+#[test]
+fn test_max_flip_ratio_aborts() {
+    // the input with its first triangle wound inwards too, so that three of the four triangles get flipped
+    let in_path = std::env::temp_dir().join("mesh_reorienter_mostly_inward.txt");
+    std::fs::write(&in_path, "4\n0.0 0.0 0.0\n0.0 0.0 1.0\n0.0 1.0 0.0\n1.0 0.0 0.0\n4\n0 2 1\n0 3 2\n0 3 1\n1 2 3\n").unwrap();
+    let run = |ratio: &str, name: &str| {
+        let out_path = std::env::temp_dir().join(name);
+        let _ = std::fs::remove_file(&out_path);
+        let output = Command::new(env!("CARGO_BIN_EXE_mesh-reorienter"))
+            .args(["--centroid", "vertex", "--max-flip-ratio", ratio])
+            .arg(&in_path)
+            .arg(&out_path)
+            .output()
+            .unwrap();
+        (output, out_path.exists())
+    };
+
+    let (output, written) = run("0.5", "mesh_reorienter_max_flip_ratio_abort.txt");
+    assert_eq!(output.status.code(), Some(1));
+    assert!(!written);
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("3 of 4 triangles would be flipped"));
+    assert!(stderr.contains("--flip-all") && stderr.contains("--robust-reference"));
+
+    let (output, written) = run("0.75", "mesh_reorienter_max_flip_ratio_ok.txt");
+    assert!(output.status.success());
+    assert!(written);
+}
+
+#[test]
+fn test_max_flip_ratio_ignores_whole_mesh_reversal() {
+    let run = |triangles: &str, options: &[&str], name: &str| {
+        let in_path = std::env::temp_dir().join(format!("{}.in", name));
+        std::fs::write(&in_path, format!("4\n0.0 0.0 0.0\n0.0 0.0 1.0\n0.0 1.0 0.0\n1.0 0.0 0.0\n4\n{}", triangles)).unwrap();
+        let out_path = std::env::temp_dir().join(name);
+        Command::new(env!("CARGO_BIN_EXE_mesh-reorienter")).args(options).args(["--max-flip-ratio", "0.1"]).arg(&in_path).arg(&out_path).output().unwrap()
+    };
+    // the tetrahedron of input.txt wound consistently outwards, and inwards
+    let outward = "0 1 2\n0 2 3\n0 3 1\n1 3 2\n";
+    let inward = "0 2 1\n0 3 2\n0 1 3\n1 2 3\n";
+
+    // reversing the whole mesh is not a decision about single triangles
+    assert!(run(inward, &["--outward"], "mesh_reorienter_max_flip_ratio_outward.txt").status.success());
+    assert!(run(outward, &["--centroid", "vertex", "--winding", "cw"], "mesh_reorienter_max_flip_ratio_cw.txt").status.success());
+
+    // the centroid approach flips every triangle itself, and --outward rules out the --flip-all hint
+    let output = run(inward, &["--centroid", "vertex", "--outward"], "mesh_reorienter_max_flip_ratio_hint.txt");
+    assert_eq!(output.status.code(), Some(1));
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("4 of 4 triangles would be flipped"));
+    assert!(!stderr.contains("--flip-all") && stderr.contains("--robust-reference"));
+
+    // the point dumped for --robust-reference is not a --reference of the user's, so the hint stays out
+    let dump_path = std::env::temp_dir().join("mesh_reorienter_max_flip_ratio_dump.txt");
+    let output = run(inward, &["--robust-reference", "--outward", "--dump-reference", dump_path.to_str().unwrap()], "mesh_reorienter_max_flip_ratio_dumped.txt");
+    assert_eq!(output.status.code(), Some(1));
+    assert!(!String::from_utf8(output.stderr).unwrap().contains("use --robust-reference"));
+}
+
+#[test]
+fn test_planar_input_wound_counter_clockwise() {
+    let output = Command::new(env!("CARGO_BIN_EXE_mesh-reorienter"))
+        .args(["--dry-run", "--centroid", "vertex", "tests/planar.txt"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), "2 of 3 triangles would be flipped\n1 2\n");
+}
+
+#[test]
+fn test_empty_meshes_are_written_unchanged() {
+    for (input, name) in [("tests/empty.txt", "mesh_reorienter_empty.txt"), ("tests/no_triangles.txt", "mesh_reorienter_no_triangles.txt")] {
+        let out_path = std::env::temp_dir().join(name);
+        let output = Command::new(env!("CARGO_BIN_EXE_mesh-reorienter"))
+            .args(["--centroid", "area", "--outward", input])
+            .arg(&out_path)
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        assert!(output.stderr.is_empty(), "{}", String::from_utf8_lossy(&output.stderr));
+        assert_eq!(std::fs::read_to_string(&out_path).unwrap(), std::fs::read_to_string(input).unwrap());
+    }
+}
+
+#[test]
+fn test_subcommands() {
+    let run = |args: &[&str]| Command::new(env!("CARGO_BIN_EXE_mesh-reorienter")).args(args).output().unwrap();
+
+    let output = run(&["check", "tests/input.txt"]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8(output.stderr).unwrap().contains("2 of 4 triangles are not consistently oriented"));
+
+    let output = run(&["stats", "tests/input.txt"]);
+    assert!(output.status.success());
+    assert!(output.stdout.is_empty());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("triangles: 4\n"));
+    assert!(stderr.contains("triangles that would be flipped: 2\n"));
+    assert!(!stderr.contains("component 0"));
+
+    // the second tetrahedron is inside out, which the total volume of 0 hides
+    let output = run(&["stats", "tests/one_inward.txt"]);
+    assert!(output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("component 0: 4 triangles, surface area 2.36"));
+    assert!(stderr.contains("component 1: 4 triangles, surface area 2.36"));
+    assert!(stderr.contains(", signed volume -0.1666"));
+
+    // convert keeps the winding of every triangle, reorient fixes it
+    let out_path = std::env::temp_dir().join("mesh_reorienter_subcommand_convert.txt");
+    assert!(run(&["convert", "tests/input.txt", out_path.to_str().unwrap()]).status.success());
+    assert_eq!(std::fs::read_to_string(&out_path).unwrap().trim_end(), std::fs::read_to_string("tests/input.txt").unwrap().trim_end());
+
+    let out_path = std::env::temp_dir().join("mesh_reorienter_subcommand_reorient.txt");
+    assert!(run(&["reorient", "tests/input.txt", out_path.to_str().unwrap()]).status.success());
+    assert_eq!(std::fs::read_to_string(&out_path).unwrap().trim_end(), std::fs::read_to_string("tests/reordered.txt").unwrap().trim_end());
+}
+
+#[test]
+fn test_convert_obj_to_ply() {
+    let out_path = std::env::temp_dir().join("mesh_reorienter_convert_cli.ply");
+    let output = Command::new(env!("CARGO_BIN_EXE_mesh-reorienter"))
+        .args(["convert", "tests/cube.obj", out_path.to_str().unwrap(), "--weld", "1e-9"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let mesh = mesh_reorienter::parse_obj("tests/cube.obj").unwrap();
+    let converted = mesh_reorienter::parse_ply_ascii(out_path.to_str().unwrap()).unwrap();
+    assert_eq!(converted.triangles, mesh.triangles);
+    assert_eq!(converted.vertices, mesh.vertices);
+}
+
+#[test]
+fn test_topology_prints_genus() {
+    let output = Command::new(env!("CARGO_BIN_EXE_mesh-reorienter"))
+        .args(["stats", "tests/torus.obj", "--topology"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("Euler characteristic: 0\ngenus: 1\n"));
+    assert!(!stderr.contains("Warning"));
+
+    let output = Command::new(env!("CARGO_BIN_EXE_mesh-reorienter"))
+        .args(["stats", "tests/planar.txt", "--topology"])
+        .output()
+        .unwrap();
+    assert!(String::from_utf8(output.stderr).unwrap().contains("Warning: the surface is not a closed manifold"));
+}
+
+#[test]
+fn test_scale_doubles_coordinates() {
+    let out_path = std::env::temp_dir().join("mesh_reorienter_scaled.txt");
+    let output = Command::new(env!("CARGO_BIN_EXE_mesh-reorienter"))
+        .args(["--scale", "2.0", "tests/reordered.txt", out_path.to_str().unwrap()])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let original = mesh_reorienter::parse_input("tests/reordered.txt").unwrap();
+    let scaled = mesh_reorienter::parse_input(out_path.to_str().unwrap()).unwrap();
+    assert_eq!(scaled.triangles, original.triangles);
+    assert_eq!(scaled.vertices, original.vertices.iter().map(|point| point * 2.0).collect::<Vec<_>>());
+}
+
+#[test]
+fn test_translate_shifts_coordinates() {
+    let out_path = std::env::temp_dir().join("mesh_reorienter_translated.txt");
+    let output = Command::new(env!("CARGO_BIN_EXE_mesh-reorienter"))
+        .args(["--translate", "1,-2,0.5", "--precision", "6", "tests/input.txt", out_path.to_str().unwrap()])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let expected = mesh_reorienter::parse_input("tests/reordered.txt").unwrap();
+    let translated = mesh_reorienter::parse_input(out_path.to_str().unwrap()).unwrap();
+    assert_eq!(translated.triangles, expected.triangles);
+    for (point, original) in translated.vertices.iter().zip(&expected.vertices) {
+        assert!((point - original - nalgebra::Vector3::new(1.0, -2.0, 0.5)).norm() < 1e-9);
+    }
+}
+
+#[test]
+fn test_degenerate_triangle_normal_is_zero() {
+    let out_path = std::env::temp_dir().join("mesh_reorienter_degenerate_out.txt");
+    let normals_path = std::env::temp_dir().join("mesh_reorienter_degenerate_normals_cli.txt");
+    let output = Command::new(env!("CARGO_BIN_EXE_mesh-reorienter"))
+        .args(["convert", "tests/degenerate.txt", out_path.to_str().unwrap(), "--emit-normals", normals_path.to_str().unwrap(), "-p", "1"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert!(String::from_utf8(output.stderr).unwrap().contains("Warning: 1 degenerate triangles have no normal and were written as 0 0 0"));
+    let normals = std::fs::read_to_string(&normals_path).unwrap();
+    assert!(!normals.contains("NaN"));
+    assert_eq!(normals.lines().last(), Some("0.0 0.0 0.0"));
+}
+
+#[test]
+fn test_split_output_writes_every_component() {
+    let out_path = std::env::temp_dir().join("mesh_reorienter_split.txt");
+    let output = Command::new(env!("CARGO_BIN_EXE_mesh-reorienter"))
+        .args(["--split-output", "tests/two_tetrahedra.txt", out_path.to_str().unwrap()])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let mesh = mesh_reorienter::parse_input("tests/two_tetrahedra.txt").unwrap();
+    for k in 0..2 {
+        let part_path = std::env::temp_dir().join(format!("mesh_reorienter_split_{}.txt", k));
+        let part = mesh_reorienter::parse_input(part_path.to_str().unwrap()).unwrap();
+        assert_eq!(part.vertices, mesh.vertices[4 * k..4 * k + 4]);
+        // each tetrahedron is wound outwards around its own centroid
+        assert_eq!(part.triangles, vec![[0, 1, 2], [0, 2, 3], [0, 3, 1], [1, 3, 2]]);
+    }
+    assert!(!std::env::temp_dir().join("mesh_reorienter_split_2.txt").exists());
+}
+
+#[test]
+fn test_compare_algorithms() {
+    let output = Command::new(env!("CARGO_BIN_EXE_mesh-reorienter"))
+        .args(["stats", "tests/input.txt", "--compare-algorithms"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert!(String::from_utf8(output.stderr).unwrap().contains("the centroid approach and the flood fill disagree on 0 of 4 triangles\n"));
+}
+
+#[test]
+fn test_sort_triangles_gives_identical_files() {
+    // every process seeds its hash maps differently, so identical files show that nothing depends on their order
+    let run = |input: &str, out_name: &str| {
+        let out_path = std::env::temp_dir().join(out_name);
+        let output = Command::new(env!("CARGO_BIN_EXE_mesh-reorienter"))
+            .args(["--sort-triangles", "--weld", "1e-6", input, out_path.to_str().unwrap()])
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        std::fs::read(out_path).unwrap()
+    };
+    let first = run("tests/torus.obj", "mesh_reorienter_sorted_1.txt");
+    assert_eq!(run("tests/torus.obj", "mesh_reorienter_sorted_2.txt"), first);
+    assert_eq!(run("tests/input.txt", "mesh_reorienter_sorted_3.txt"), run("tests/reordered.txt", "mesh_reorienter_sorted_4.txt"));
+}
+
+#[test]
+fn test_repair_reports_every_fix() {
+    let out_path = std::env::temp_dir().join("mesh_reorienter_repaired.txt");
+    let output = Command::new(env!("CARGO_BIN_EXE_mesh-reorienter"))
+        .args(["repair", "tests/messy.txt", out_path.to_str().unwrap()])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stderr).unwrap(),
+        "merged vertices: 1\ndropped degenerate triangles: 1\nflipped inconsistent triangles: 1\nflipped inside-out triangles: 4\n"
+    );
+    assert_eq!(std::fs::read_to_string(&out_path).unwrap().trim_end(), std::fs::read_to_string("tests/reordered.txt").unwrap().trim_end());
+
+    // the triangle the flood fill flipped is flipped back with the whole surface, so three of the four end up flipped
+    let output = Command::new(env!("CARGO_BIN_EXE_mesh-reorienter"))
+        .args(["repair", "--stats", "tests/messy.txt", out_path.to_str().unwrap()])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert!(String::from_utf8(output.stderr).unwrap().contains("flipped triangles: 3\n"));
+}
+
+#[test]
+fn test_double_sided_appends_reversed_triangles() {
+    let out_path = std::env::temp_dir().join("mesh_reorienter_double_sided.txt");
+    let output = Command::new(env!("CARGO_BIN_EXE_mesh-reorienter"))
+        .args(["--double-sided", "tests/input.txt", out_path.to_str().unwrap()])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let mesh = mesh_reorienter::parse_input(out_path.to_str().unwrap()).unwrap();
+    let expected = mesh_reorienter::parse_input("tests/reordered.txt").unwrap();
+    let n = expected.triangles.len();
+    assert_eq!(mesh.triangles.len(), 2 * n);
+    assert_eq!(mesh.triangles[..n], expected.triangles[..]);
+    for (&[a, b, c], twin) in mesh.triangles[..n].iter().zip(&mesh.triangles[n..]) {
+        assert_eq!(*twin, [a, c, b]);
+    }
+}
+
+#[test]
+fn test_dump_reference_writes_the_centroid() {
+    let reference_path = std::env::temp_dir().join("mesh_reorienter_reference.txt");
+    let output = Command::new(env!("CARGO_BIN_EXE_mesh-reorienter"))
+        .args(["--centroid", "vertex", "--dump-reference", reference_path.to_str().unwrap(), "--dry-run", "tests/tetra_shifted.txt"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let written = std::fs::read_to_string(&reference_path).unwrap();
+    let coordinates: Vec<f64> = written.split_whitespace().map(|coord| coord.parse().unwrap()).collect();
+    let centroid = mesh_reorienter::compute_centroid(&mesh_reorienter::parse_input("tests/tetra_shifted.txt").unwrap());
+    assert_eq!(written.lines().count(), 1);
+    assert_eq!(coordinates, vec![centroid.x, centroid.y, centroid.z]);
+
+    // the point follows the written coordinates
+    let out_path = std::env::temp_dir().join("mesh_reorienter_reference_scaled.txt");
+    let output = Command::new(env!("CARGO_BIN_EXE_mesh-reorienter"))
+        .args(["--centroid", "vertex", "--dump-reference", reference_path.to_str().unwrap(), "--scale", "2", "--translate", "1,0,0", "tests/tetra_shifted.txt"])
+        .arg(&out_path)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let written = std::fs::read_to_string(&reference_path).unwrap();
+    let coordinates: Vec<f64> = written.split_whitespace().map(|coord| coord.parse().unwrap()).collect();
+    assert_eq!(coordinates, vec![centroid.x * 2.0 + 1.0, centroid.y * 2.0, centroid.z * 2.0]);
+
+    // a planar mesh is oriented without the reference point
+    let planar_path = std::env::temp_dir().join("mesh_reorienter_reference_planar.txt");
+    let _ = std::fs::remove_file(&planar_path);
+    let output = Command::new(env!("CARGO_BIN_EXE_mesh-reorienter"))
+        .args(["--centroid", "vertex", "--dump-reference", planar_path.to_str().unwrap(), "--dry-run", "tests/planar.txt"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert!(String::from_utf8(output.stderr).unwrap().contains("planar"));
+    assert!(!planar_path.exists());
+}
+
+#[test]
+fn test_embed_normals() {
+    let run = |args: &[&str]| Command::new(env!("CARGO_BIN_EXE_mesh-reorienter")).args(args).output().unwrap();
+
+    let out_path = std::env::temp_dir().join("mesh_reorienter_embedded_normals.obj");
+    assert!(run(&["--embed-normals", "tests/input.txt", out_path.to_str().unwrap()]).status.success());
+    let written = std::fs::read_to_string(&out_path).unwrap();
+    assert_eq!(written.lines().filter(|line| line.starts_with("vn ")).count(), 4);
+    assert!(written.lines().filter(|line| line.starts_with("f ")).all(|line| line.split_whitespace().skip(1).all(|vertex| vertex.contains("//"))));
+
+    let output = run(&["--embed-normals", "tests/input.txt", "out.txt"]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8(output.stderr).unwrap().contains("--embed-normals needs an OBJ or PLY output"));
+}
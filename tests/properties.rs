@@ -0,0 +1,181 @@
+//! Property tests of the parsers over generated inputs.
+//!
+//! Two invariants are checked:
+//! - writing any mesh with [`write_output_to`] and parsing the result gives back the same mesh, for coordinates
+//!   that the precision represents exactly;
+//! - [`parse_reader`] and the parsers of the other formats are total: whatever bytes they are given, they return
+//!   `Ok` or `Err` and never panic.
+//!
+//! The inputs come from a small seeded generator rather than a property-testing crate, so every run checks the
+//! same cases and a failure names the case that reproduces it.
+
+use mesh_reorienter::{
+    parse_bin, parse_json, parse_obj, parse_off, parse_ply, parse_reader, parse_reader_with, parse_stl, write_bin, write_output_to, Mesh, ParseError, ParseOptions,
+    PrecisionSpec,
+};
+use nalgebra::Vector3;
+
+/// Number of generated cases per property.
+const CASES: u64 = 500;
+
+/// The splitmix64 generator: tiny, seedable and good enough to spread test cases.
+struct Rng(u64);
+
+impl Rng {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// A number in `0..n`, which must not be 0.
+    fn below(&mut self, n: usize) -> usize {
+        (self.next_u64() % n as u64) as usize
+    }
+
+    /// A coordinate with at most 10 binary fractional digits, written exactly by 17 decimals.
+    fn coordinate(&mut self) -> f64 {
+        (self.below(1 << 21) as f64 - (1 << 20) as f64) / 1024.0
+    }
+
+    /// Overwrite, remove or insert up to four bytes of `bytes`, inserting characters of the text formats.
+    fn mutate(&mut self, bytes: &mut Vec<u8>) {
+        for _ in 0..1 + self.below(4) {
+            if bytes.is_empty() {
+                return;
+            }
+            let at = self.below(bytes.len());
+            match self.below(3) {
+                0 => bytes[at] = self.next_u64() as u8,
+                1 => {
+                    bytes.remove(at);
+                }
+                _ => bytes.insert(at, b"0123456789 \n-"[self.below(13)]),
+            }
+        }
+    }
+}
+
+/// A mesh of up to 50 points and 100 triangles indexing them, possibly repeating indices.
+fn random_mesh(rng: &mut Rng) -> Mesh {
+    let n_points = rng.below(50);
+    let vertices: Vec<Vector3<f64>> = (0..n_points).map(|_| Vector3::new(rng.coordinate(), rng.coordinate(), rng.coordinate())).collect();
+    let n_triangles = if n_points == 0 { 0 } else { rng.below(100) };
+    let triangles: Vec<[usize; 3]> = (0..n_triangles).map(|_| [rng.below(n_points), rng.below(n_points), rng.below(n_points)]).collect();
+    Mesh { vertices, triangles, ..Mesh::default() }
+}
+
+#[test]
+fn test_write_then_parse_is_identity() {
+    for case in 0..CASES {
+        let mesh = random_mesh(&mut Rng(case));
+        let mut bytes: Vec<u8> = Vec::new();
+        write_output_to(&mut bytes, &mesh, PrecisionSpec::uniform(17)).unwrap();
+        assert_eq!(parse_reader(bytes.as_slice()), Ok(mesh), "case {}", case);
+    }
+}
+
+#[test]
+fn test_parse_random_bytes_never_panics() {
+    // bytes drawn mostly from the characters of the format, so that the parser gets past the first line
+    const ALPHABET: &[u8] = b"0123456789 \t\n\r.,-+eE#/naifINF\xff\x00";
+    for case in 0..CASES {
+        let mut rng = Rng(case);
+        let len = rng.below(200);
+        let bytes: Vec<u8> = (0..len).map(|_| ALPHABET[rng.below(ALPHABET.len())]).collect();
+        let _ = parse_reader(bytes.as_slice());
+        let _ = parse_reader_with(bytes.as_slice(), &ParseOptions { no_counts: true, ..ParseOptions::default() });
+    }
+}
+
+#[test]
+fn test_parse_huge_counts_never_panics() {
+    // the declared counts alone must not make the parser allocate room for that many rows
+    for contents in ["18446744073709551615\n0 0 0\n", "1\n0 0 0\n18446744073709551615\n0 0 0\n"] {
+        assert!(parse_reader(contents.as_bytes()).is_err());
+    }
+}
+
+#[test]
+fn test_parse_mutated_files_never_panics() {
+    let original = std::fs::read("tests/input.txt").unwrap();
+    for case in 0..CASES {
+        let mut rng = Rng(case);
+        let mut bytes = original.clone();
+        rng.mutate(&mut bytes);
+        for options in [ParseOptions::default(), ParseOptions { planar: true, ..ParseOptions::default() }, ParseOptions { indexed_points: true, index_base: 1, ..ParseOptions::default() }] {
+            let _ = parse_reader_with(bytes.as_slice(), &options);
+        }
+    }
+}
+
+/// Valid files of every other format for the mutations to start from, with binary PLY and STL next to the text ones.
+fn format_seeds() -> Vec<(&'static str, Vec<u8>)> {
+    let tetrahedron = mesh_reorienter::parse_input("tests/input.txt").unwrap();
+    let mut binary_ply = b"ply\nformat binary_little_endian 1.0\nelement vertex 4\nproperty float x\nproperty float y\nproperty float z\n\
+        element face 4\nproperty list uchar int vertex_indices\nend_header\n"
+        .to_vec();
+    let mut binary_stl = vec![0u8; 80];
+    binary_stl.extend((tetrahedron.triangles.len() as u32).to_le_bytes());
+    for point in &tetrahedron.vertices {
+        binary_ply.extend(point.iter().flat_map(|&c| (c as f32).to_le_bytes()));
+    }
+    for triangle in &tetrahedron.triangles {
+        binary_ply.push(3);
+        binary_ply.extend(triangle.iter().flat_map(|&i| (i as i32).to_le_bytes()));
+        binary_stl.extend([0f32; 3].iter().flat_map(|c| c.to_le_bytes()));
+        binary_stl.extend(triangle.iter().flat_map(|&i| tetrahedron.vertices[i].iter().flat_map(|&c| (c as f32).to_le_bytes()).collect::<Vec<_>>()));
+        binary_stl.extend([0, 0]);
+    }
+    let bin_path = std::env::temp_dir().join("mesh_reorienter_fuzz_seed.bin");
+    write_bin(bin_path.to_str().unwrap(), &tetrahedron);
+
+    let mut seeds = Vec::new();
+    for (extension, path) in [("json", "tests/cube.json"), ("ply", "tests/pyramid.ply"), ("ply", "tests/colored.ply"), ("obj", "tests/cube.obj"), ("off", "tests/cube.off"), ("stl", "tests/tetra.stl")] {
+        seeds.push((extension, std::fs::read(path).unwrap()));
+    }
+    seeds.extend([("ply", binary_ply), ("stl", binary_stl), ("bin", std::fs::read(&bin_path).unwrap())]);
+    for (extension, bytes) in &seeds {
+        let path = std::env::temp_dir().join(format!("mesh_reorienter_fuzz_check.{}", extension));
+        std::fs::write(&path, bytes).unwrap();
+        assert!(parse_with_extension(extension, path.to_str().unwrap()).is_ok(), "seed {}", extension);
+    }
+    seeds
+}
+
+/// Parse the file at `path` with the parser of the format named by `extension`.
+fn parse_with_extension(extension: &str, path: &str) -> Result<Mesh, ParseError> {
+    match extension {
+        "json" => parse_json(path),
+        "ply" => parse_ply(path),
+        "obj" => parse_obj(path),
+        "off" => parse_off(path),
+        "stl" => parse_stl(path),
+        "bin" => parse_bin(path),
+        _ => unreachable!("no seed has the extension {}", extension),
+    }
+}
+
+#[test]
+fn test_parse_other_formats_never_panics() {
+    // the parsers take paths, so every case goes through a file of the format's extension
+    let seeds = format_seeds();
+    for case in 0..CASES {
+        let mut rng = Rng(case);
+        for (extension, seed) in &seeds {
+            let path = std::env::temp_dir().join(format!("mesh_reorienter_fuzz_{}.{}", case % 8, extension));
+            let path = path.to_str().unwrap();
+            let mut mutated = seed.clone();
+            rng.mutate(&mut mutated);
+            let mut truncated = seed.clone();
+            truncated.truncate(rng.below(seed.len() + 1));
+            let random: Vec<u8> = (0..rng.below(200)).map(|_| rng.next_u64() as u8).collect();
+            for bytes in [mutated, truncated, random] {
+                std::fs::write(path, &bytes).unwrap();
+                let _ = parse_with_extension(extension, path);
+            }
+        }
+    }
+}
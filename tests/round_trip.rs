@@ -0,0 +1,32 @@
+use mesh_reorienter::{meshes_approx_equal, parse_input, parse_obj, write_output, PrecisionSpec};
+
+#[test]
+fn test_round_trip_preserves_points() {
+    let out_path = std::env::temp_dir().join("mesh_reorienter_round_trip.txt");
+    let out_path = out_path.to_str().unwrap();
+
+    let mesh = parse_input("tests/input.txt").unwrap();
+    write_output(out_path, &mesh, PrecisionSpec::uniform(17));
+    let written = parse_input(out_path).unwrap();
+
+    assert_eq!(mesh.vertices.len(), written.vertices.len());
+    for (a, b) in mesh.vertices.iter().zip(written.vertices.iter()) {
+        for k in 0..3 {
+            assert_eq!(a[k].to_bits(), b[k].to_bits());
+        }
+    }
+    assert_eq!(mesh.triangles, written.triangles);
+}
+
+#[test]
+fn test_round_trip_within_precision() {
+    let out_path = std::env::temp_dir().join("mesh_reorienter_round_trip_torus.txt");
+    let out_path = out_path.to_str().unwrap();
+
+    // three decimals round every coordinate by at most half a thousandth
+    let mesh = parse_obj("tests/torus.obj").unwrap();
+    write_output(out_path, &mesh, PrecisionSpec::uniform(3));
+    let written = parse_input(out_path).unwrap();
+    assert_ne!(written, mesh);
+    assert!(meshes_approx_equal(&mesh, &written, 5e-4 + 1e-12));
+}